@@ -18,28 +18,91 @@ use std::{
 /// Maximum allowed size for a serialised Chunk to grow to.
 pub const MAX_CHUNK_SIZE_IN_BYTES: u64 = 1024 * 1024 + 10 * 1024;
 
+/// Maximum size of a value to be inlined into its owning data structure rather
+/// than stored as a separate Chunk.
+pub const MAX_INLINE_SIZE_IN_BYTES: usize = 1024;
+
+/// Domain separator for the names of app-scoped chunk addresses.
+const APP_SCOPE_DOMAIN: &[u8] = b"sn_data_types::chunk::app_scope";
+
+/// Domain separator for the names of chunks whose content is encoded.
+const CONTENT_ENCODING_DOMAIN: &[u8] = b"sn_data_types::chunk::content_encoding";
+
+/// Returns the address a chunk of the given `kind` holding `value`, with no content
+/// encoding, would be stored at, without constructing the chunk, e.g. to check whether
+/// it's already stored before uploading it. Private chunks are addressed by their `owner`
+/// as well, so it's required for them and ignored for public ones.
+pub fn predict_address(
+    kind: Kind,
+    value: &[u8],
+    owner: Option<&PublicKey>,
+) -> Result<Address, Error> {
+    match (kind, owner) {
+        (Kind::Pub, _) => Ok(public_address(value, None)),
+        (Kind::Private, Some(owner)) => Ok(private_address(value, owner, None)),
+        (Kind::Private, None) => Err(Error::InvalidOperation),
+    }
+}
+
+// The content encoding of a chunk is part of its name when set, for the chunk to be
+// decoded as it was encoded.
+fn public_address(value: &[u8], encoding: Option<ContentEncoding>) -> Address {
+    Address::Public(match encoding {
+        None => XorName::from_content(&[value]),
+        Some(encoding) => XorName::from_content(&[
+            CONTENT_ENCODING_DOMAIN,
+            encoding.as_http_header().as_bytes(),
+            value,
+        ]),
+    })
+}
+
+fn private_address(value: &[u8], owner: &PublicKey, encoding: Option<ContentEncoding>) -> Address {
+    Address::Private(match encoding {
+        None => XorName::from_content(&[value, &owner.to_bytes()]),
+        Some(encoding) => XorName::from_content(&[
+            CONTENT_ENCODING_DOMAIN,
+            encoding.as_http_header().as_bytes(),
+            value,
+            &owner.to_bytes(),
+        ]),
+    })
+}
+
 /// Private Chunk: an immutable chunk of data which can be deleted. Can only be fetched
 /// by the listed owner.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct PrivateChunk {
-    /// Network address. Omitted when serialising and calculated from the `value` and `owner` when
-    /// deserialising.
+    /// Network address. Omitted when serialising and calculated from the `value`, `owner` and
+    /// `encoding` when deserialising.
     address: Address,
     /// Contained chunk.
     value: Vec<u8>,
     /// Contains a set of owners of this chunk.
     owner: PublicKey,
+    /// Encoding the value was stored with, if any.
+    encoding: Option<ContentEncoding>,
 }
 
 impl PrivateChunk {
     /// Creates a new instance of `PrivateChunk`.
     pub fn new(value: Vec<u8>, owner: PublicKey) -> Self {
-        let address = Address::Private(XorName::from_content(&[&value, &owner.to_bytes()]));
+        Self::with_encoding(value, owner, None)
+    }
+
+    /// Creates a new instance of `PrivateChunk` whose value is encoded with `encoding`.
+    pub fn new_encoded(value: Vec<u8>, owner: PublicKey, encoding: ContentEncoding) -> Self {
+        Self::with_encoding(value, owner, Some(encoding))
+    }
+
+    fn with_encoding(value: Vec<u8>, owner: PublicKey, encoding: Option<ContentEncoding>) -> Self {
+        let address = private_address(&value, &owner, encoding);
 
         Self {
             address,
             value,
             owner,
+            encoding,
         }
     }
 
@@ -53,6 +116,11 @@ impl PrivateChunk {
         &self.owner
     }
 
+    /// Returns the encoding the value was stored with, if any.
+    pub fn encoding(&self) -> Option<ContentEncoding> {
+        self.encoding
+    }
+
     /// Returns the address.
     pub fn address(&self) -> &Address {
         &self.address
@@ -78,22 +146,22 @@ impl PrivateChunk {
         self.serialised_size() <= MAX_CHUNK_SIZE_IN_BYTES
     }
 
-    fn serialised_structure(&self) -> (&[u8], &PublicKey) {
-        (&self.value, &self.owner)
+    fn serialised_structure(&self) -> (&[u8], &PublicKey, Option<ContentEncoding>) {
+        (&self.value, &self.owner, self.encoding)
     }
 }
 
 impl Serialize for PrivateChunk {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        // Address is omitted since it's derived from value + owner
+        // Address is omitted since it's derived from value + owner + encoding
         self.serialised_structure().serialize(serialiser)
     }
 }
 
 impl<'de> Deserialize<'de> for PrivateChunk {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let (value, owner) = Deserialize::deserialize(deserializer)?;
-        Ok(Self::new(value, owner))
+        let (value, owner, encoding) = Deserialize::deserialize(deserializer)?;
+        Ok(Self::with_encoding(value, owner, encoding))
     }
 }
 
@@ -107,19 +175,31 @@ impl Debug for PrivateChunk {
 /// Public Chunk: an immutable chunk of data which cannot be deleted.
 #[derive(Hash, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct PublicChunk {
-    /// Network address. Omitted when serialising and calculated from the `value` when
-    /// deserialising.
+    /// Network address. Omitted when serialising and calculated from the `value` and
+    /// `encoding` when deserialising.
     address: Address,
     /// Contained chunk.
     value: Vec<u8>,
+    /// Encoding the value was stored with, if any.
+    encoding: Option<ContentEncoding>,
 }
 
 impl PublicChunk {
     /// Creates a new instance of `Chunk`.
     pub fn new(value: Vec<u8>) -> Self {
+        Self::with_encoding(value, None)
+    }
+
+    /// Creates a new instance of `Chunk` whose value is encoded with `encoding`.
+    pub fn new_encoded(value: Vec<u8>, encoding: ContentEncoding) -> Self {
+        Self::with_encoding(value, Some(encoding))
+    }
+
+    fn with_encoding(value: Vec<u8>, encoding: Option<ContentEncoding>) -> Self {
         Self {
-            address: Address::Public(XorName::from_content(&[&value])),
+            address: public_address(&value, encoding),
             value,
+            encoding,
         }
     }
 
@@ -128,6 +208,11 @@ impl PublicChunk {
         &self.value
     }
 
+    /// Returns the encoding the value was stored with, if any.
+    pub fn encoding(&self) -> Option<ContentEncoding> {
+        self.encoding
+    }
+
     /// Returns the address.
     pub fn address(&self) -> &Address {
         &self.address
@@ -156,14 +241,14 @@ impl PublicChunk {
 
 impl Serialize for PublicChunk {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        self.value.serialize(serialiser)
+        (&self.value, self.encoding).serialize(serialiser)
     }
 }
 
 impl<'de> Deserialize<'de> for PublicChunk {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let value: Vec<u8> = Deserialize::deserialize(deserializer)?;
-        Ok(PublicChunk::new(value))
+        let (value, encoding) = Deserialize::deserialize(deserializer)?;
+        Ok(PublicChunk::with_encoding(value, encoding))
     }
 }
 
@@ -221,6 +306,23 @@ impl Address {
         }
     }
 
+    /// Constructs the private `Address` of the content with the given hash within
+    /// the namespace of an app, so that each app's private chunks are partitioned
+    /// from those of other apps.
+    pub fn scoped(app_id: &str, content_hash: &XorName) -> Self {
+        Address::Private(XorName::from_content(&[
+            APP_SCOPE_DOMAIN,
+            app_id.as_bytes(),
+            &content_hash.0,
+        ]))
+    }
+
+    /// Returns true if this is the address of the content with the given hash
+    /// within the namespace of the app.
+    pub fn is_scoped_to(&self, app_id: &str, content_hash: &XorName) -> bool {
+        *self == Self::scoped(app_id, content_hash)
+    }
+
     /// Returns the kind.
     pub fn kind(&self) -> Kind {
         match self {
@@ -246,6 +348,25 @@ impl Address {
         self.kind().is_private()
     }
 
+    /// Returns the xor distance between the name of this address and `name`.
+    pub fn distance(&self, name: &XorName) -> XorName {
+        let mut distance = *self.name();
+        for (byte, other) in distance.0.iter_mut().zip(name.0.iter()) {
+            *byte ^= other;
+        }
+        distance
+    }
+
+    /// Returns up to `n` of the `section_members` closest to this address, i.e. the
+    /// nodes expected to hold the chunk, ordered from the closest to the furthest.
+    pub fn closest_holders(&self, section_members: &[XorName], n: usize) -> Vec<XorName> {
+        let mut holders = section_members.to_vec();
+        holders.sort_by(|lhs, rhs| self.name().cmp_distance(lhs, rhs));
+        holders.dedup();
+        holders.truncate(n);
+        holders
+    }
+
     /// Returns the Address serialised and encoded in z-base-32.
     pub fn encode_to_zbase32(&self) -> Result<String, Error> {
         utils::encode(&self)
@@ -257,8 +378,61 @@ impl Address {
     }
 }
 
+/// Encoding applied to the content of a Chunk, e.g. for gateways to set the
+/// `Content-Encoding` header when serving it over HTTP.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum ContentEncoding {
+    /// The content is stored as it is.
+    Identity,
+    /// The content is gzip compressed.
+    Gzip,
+    /// The content is zstd compressed.
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Returns the name of the encoding as used in HTTP headers.
+    pub fn as_http_header(self) -> &'static str {
+        match self {
+            ContentEncoding::Identity => "identity",
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Zstd => "zstd",
+        }
+    }
+
+    /// Decodes `value`, which is expected to be encoded with this encoding, failing
+    /// with `Error::ExceededSize` rather than decoding it beyond `MAX_CHUNK_SIZE_IN_BYTES`.
+    #[cfg(feature = "compression")]
+    pub fn decode(self, value: &[u8]) -> Result<Vec<u8>, Error> {
+        use std::io::{self, Read};
+
+        let to_error = |err: io::Error| {
+            Error::Serialisation(format!("Could not decode {:?} content: {}", self, err))
+        };
+        let decoder: Box<dyn Read + '_> = match self {
+            ContentEncoding::Identity => return Ok(value.to_vec()),
+            ContentEncoding::Gzip => Box::new(flate2::read::GzDecoder::new(value)),
+            ContentEncoding::Zstd => {
+                Box::new(zstd::stream::read::Decoder::new(value).map_err(to_error)?)
+            }
+        };
+        let mut decoded = Vec::new();
+        let _ = decoder
+            .take(MAX_CHUNK_SIZE_IN_BYTES + 1)
+            .read_to_end(&mut decoded)
+            .map_err(to_error)?;
+        if decoded.len() as u64 > MAX_CHUNK_SIZE_IN_BYTES {
+            return Err(Error::ExceededSize);
+        }
+        Ok(decoded)
+    }
+}
+
 /// Object storing an Chunk variant.
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+///
+/// Encoded as a `ChunkLayout`, so Chunks encoded with an earlier layout can still
+/// be decoded.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum Chunk {
     /// Private Chunk.
     Private(PrivateChunk),
@@ -266,6 +440,44 @@ pub enum Chunk {
     Public(PublicChunk),
 }
 
+/// Layouts a `Chunk` has been encoded with, the earliest first. Variants are only
+/// ever appended, for those encoded before to keep their tag.
+#[derive(Deserialize)]
+enum ChunkLayout {
+    /// Value and owner of a Private Chunk from before content encodings were stored.
+    Private(Vec<u8>, PublicKey),
+    /// Value of a Public Chunk from before content encodings were stored.
+    Public(Vec<u8>),
+    /// Private Chunk.
+    PrivateV1(PrivateChunk),
+    /// Public Chunk.
+    PublicV1(PublicChunk),
+}
+
+impl Serialize for Chunk {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Chunk::Private(chunk) => {
+                serialiser.serialize_newtype_variant("ChunkLayout", 2, "PrivateV1", chunk)
+            }
+            Chunk::Public(chunk) => {
+                serialiser.serialize_newtype_variant("ChunkLayout", 3, "PublicV1", chunk)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Chunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ChunkLayout::deserialize(deserializer)? {
+            ChunkLayout::Private(value, owner) => Chunk::Private(PrivateChunk::new(value, owner)),
+            ChunkLayout::Public(value) => Chunk::Public(PublicChunk::new(value)),
+            ChunkLayout::PrivateV1(chunk) => Chunk::Private(chunk),
+            ChunkLayout::PublicV1(chunk) => Chunk::Public(chunk),
+        })
+    }
+}
+
 impl Chunk {
     /// Returns the address.
     pub fn address(&self) -> &Address {
@@ -311,6 +523,24 @@ impl Chunk {
         }
     }
 
+    /// Returns the encoding the value was stored with, if any.
+    pub fn content_encoding(&self) -> Option<ContentEncoding> {
+        match self {
+            Chunk::Private(chunk) => chunk.encoding(),
+            Chunk::Public(chunk) => chunk.encoding(),
+        }
+    }
+
+    /// Returns the value, decoded with the encoding it was stored with if any,
+    /// or as it is otherwise.
+    #[cfg(feature = "compression")]
+    pub fn decoded_value(&self) -> Result<Vec<u8>, Error> {
+        match self.content_encoding() {
+            Some(encoding) => encoding.decode(self.value()),
+            None => Ok(self.value().clone()),
+        }
+    }
+
     /// Returns `true` if the size is valid.
     pub fn validate_size(&self) -> bool {
         match self {
@@ -340,10 +570,80 @@ impl From<PublicChunk> for Chunk {
     }
 }
 
+/// A value small enough to be held inline by the data structure owning it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct InlineChunk(Vec<u8>);
+
+impl InlineChunk {
+    /// Creates a new instance of `InlineChunk`, failing if the value is larger
+    /// than `MAX_INLINE_SIZE_IN_BYTES`.
+    pub fn new(value: Vec<u8>) -> Result<Self, Error> {
+        if value.len() > MAX_INLINE_SIZE_IN_BYTES {
+            return Err(Error::ExceededSize);
+        }
+        Ok(Self(value))
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+/// A value either inlined or stored as a separate Chunk, depending on its size.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum MaybeChunk {
+    /// Value held inline.
+    Inline(InlineChunk),
+    /// Address of the Chunk holding the value.
+    Chunk(Address),
+}
+
+impl MaybeChunk {
+    /// Inlines `value` if it's no larger than `MAX_INLINE_SIZE_IN_BYTES`. Otherwise
+    /// the value is put into a Chunk (private if an owner is given), which is
+    /// returned alongside for the caller to store.
+    pub fn new(value: Vec<u8>, owner: Option<PublicKey>) -> (Self, Option<Chunk>) {
+        if value.len() <= MAX_INLINE_SIZE_IN_BYTES {
+            return (MaybeChunk::Inline(InlineChunk(value)), None);
+        }
+
+        let chunk: Chunk = match owner {
+            Some(owner) => PrivateChunk::new(value, owner).into(),
+            None => PublicChunk::new(value).into(),
+        };
+        (MaybeChunk::Chunk(*chunk.address()), Some(chunk))
+    }
+
+    /// Returns true if the value is held inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self, MaybeChunk::Inline(_))
+    }
+
+    /// Returns the value if it's held inline.
+    pub fn inline_value(&self) -> Option<&Vec<u8>> {
+        match self {
+            MaybeChunk::Inline(inline) => Some(inline.value()),
+            MaybeChunk::Chunk(_) => None,
+        }
+    }
+
+    /// Returns the address of the Chunk holding the value if it's not held inline.
+    pub fn chunk_address(&self) -> Option<&Address> {
+        match self {
+            MaybeChunk::Inline(_) => None,
+            MaybeChunk::Chunk(address) => Some(address),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Address, PrivateChunk, PublicChunk, PublicKey, XorName};
-    use crate::{utils, Result};
+    use super::{
+        predict_address, Address, Chunk, ContentEncoding, InlineChunk, Kind, MaybeChunk,
+        PrivateChunk, PublicChunk, PublicKey, XorName, MAX_INLINE_SIZE_IN_BYTES,
+    };
+    use crate::{utils, Error, Result};
     use hex::encode;
     use rand::{self, Rng, SeedableRng};
     use rand_xorshift::XorShiftRng;
@@ -406,6 +706,184 @@ mod tests {
         XorShiftRng::seed_from_u64(seed)
     }
 
+    #[test]
+    fn closest_holders() {
+        let address = Address::Public(XorName([0; 32]));
+        let members: Vec<XorName> = [7u8, 1, 255, 3, 1]
+            .iter()
+            .map(|byte| {
+                let mut name = XorName([0; 32]);
+                name.0[0] = *byte;
+                name
+            })
+            .collect();
+
+        let holders = address.closest_holders(&members, 3);
+        let first_bytes: Vec<u8> = holders.iter().map(|name| name.0[0]).collect();
+        assert_eq!(first_bytes, vec![1, 3, 7]);
+
+        assert_eq!(address.closest_holders(&members, 10).len(), 4);
+        assert!(address.closest_holders(&members, 0).is_empty());
+        assert_eq!(address.distance(&members[2]), members[2]);
+    }
+
+    #[test]
+    fn content_encoding_is_only_what_was_stored() -> Result<()> {
+        // Values that happen to start with a magic number aren't taken as encoded
+        let gzip_magic = vec![0x1f, 0x8b, 0x08, 0x00];
+        let plain = Chunk::from(PublicChunk::new(gzip_magic.clone()));
+        assert_eq!(plain.content_encoding(), None);
+        #[cfg(feature = "compression")]
+        assert_eq!(plain.decoded_value()?, gzip_magic);
+
+        let gzip = Chunk::from(PublicChunk::new_encoded(
+            gzip_magic.clone(),
+            ContentEncoding::Gzip,
+        ));
+        assert_eq!(gzip.content_encoding(), Some(ContentEncoding::Gzip));
+        assert_ne!(gzip.name(), plain.name());
+
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let private = PrivateChunk::new(gzip_magic.clone(), owner);
+        let zstd = PrivateChunk::new_encoded(gzip_magic, owner, ContentEncoding::Zstd);
+        assert_eq!(private.encoding(), None);
+        assert_eq!(zstd.encoding(), Some(ContentEncoding::Zstd));
+        assert_ne!(zstd.name(), private.name());
+
+        // The encoding survives a round trip
+        for chunk in [gzip, Chunk::from(zstd)] {
+            let parsed: Chunk = utils::deserialise(&utils::serialise(&chunk)?)?;
+            assert_eq!(parsed, chunk);
+            assert_eq!(parsed.name(), chunk.name());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_earliest_layout() -> Result<()> {
+        // Chunks as encoded before content encodings were stored
+        let public: Chunk = utils::deserialise(
+            &hex::decode("010000000c000000000000007075626c6963206368756e6b")
+                .map_err(|err| Error::Serialisation(err.to_string()))?,
+        )?;
+        assert_eq!(
+            public,
+            Chunk::from(PublicChunk::new(b"public chunk".to_vec()))
+        );
+        assert_eq!(public.content_encoding(), None);
+
+        let owner = ed25519_dalek::PublicKey::from(
+            &ed25519_dalek::SecretKey::from_bytes(&[1; 32])
+                .map_err(|err| Error::Serialisation(err.to_string()))?,
+        );
+        let private: Chunk = utils::deserialise(
+            &hex::decode(
+                "000000000d0000000000000070726976617465206368756e6b00000000200000000000\
+                 00008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
+            )
+            .map_err(|err| Error::Serialisation(err.to_string()))?,
+        )?;
+        assert_eq!(
+            private,
+            Chunk::from(PrivateChunk::new(
+                b"private chunk".to_vec(),
+                PublicKey::Ed25519(owner)
+            ))
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decoded_value() -> Result<()> {
+        let value = b"some compressible text ".repeat(64);
+        let encoded =
+            zstd::encode_all(&value[..], 3).map_err(|err| Error::Serialisation(err.to_string()))?;
+        let chunk = Chunk::from(PublicChunk::new_encoded(encoded, ContentEncoding::Zstd));
+        assert_eq!(chunk.content_encoding(), Some(ContentEncoding::Zstd));
+        assert_eq!(chunk.decoded_value()?, value);
+
+        // Values decoding beyond the largest chunk are rejected, whatever the encoding
+        let oversized = vec![0; super::MAX_CHUNK_SIZE_IN_BYTES as usize + 1];
+        let zstd_bomb = zstd::encode_all(&oversized[..], 3)
+            .map_err(|err| Error::Serialisation(err.to_string()))?;
+        assert_eq!(
+            ContentEncoding::Zstd.decode(&zstd_bomb),
+            Err(Error::ExceededSize)
+        );
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &oversized)
+            .map_err(|err| Error::Serialisation(err.to_string()))?;
+        let gzip_bomb = encoder
+            .finish()
+            .map_err(|err| Error::Serialisation(err.to_string()))?;
+        assert_eq!(
+            ContentEncoding::Gzip.decode(&gzip_bomb),
+            Err(Error::ExceededSize)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn maybe_chunk_inlines_small_values() {
+        let small = vec![0; MAX_INLINE_SIZE_IN_BYTES];
+        let (maybe_chunk, chunk) = MaybeChunk::new(small.clone(), None);
+        assert!(maybe_chunk.is_inline());
+        assert_eq!(maybe_chunk.inline_value(), Some(&small));
+        assert!(chunk.is_none());
+
+        let large = vec![0; MAX_INLINE_SIZE_IN_BYTES + 1];
+        assert!(InlineChunk::new(large.clone()).is_err());
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let (maybe_chunk, chunk) = MaybeChunk::new(large.clone(), Some(owner));
+        assert!(!maybe_chunk.is_inline());
+        let chunk = chunk.expect("a chunk to store");
+        assert_eq!(maybe_chunk.chunk_address(), Some(chunk.address()));
+        assert!(chunk.is_private());
+        assert_eq!(chunk.value(), &large);
+    }
+
+    #[test]
+    fn scoped_addresses() {
+        let content_hash = XorName::from_content(&[b"chunk content"]);
+        let address = Address::scoped("app.one", &content_hash);
+
+        assert!(address.is_private());
+        assert_eq!(address, Address::scoped("app.one", &content_hash));
+        assert_ne!(address, Address::scoped("app.two", &content_hash));
+        assert_ne!(*address.name(), content_hash);
+
+        assert!(address.is_scoped_to("app.one", &content_hash));
+        assert!(!address.is_scoped_to("app.two", &content_hash));
+        assert!(!Address::Private(content_hash).is_scoped_to("app.one", &content_hash));
+    }
+
+    #[test]
+    fn predicted_addresses() {
+        let value = b"Hello".to_vec();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        let public = PublicChunk::new(value.clone());
+        assert_eq!(
+            predict_address(Kind::Pub, &value, None),
+            Ok(*public.address())
+        );
+        assert_eq!(
+            predict_address(Kind::Pub, &value, Some(&owner)),
+            Ok(*public.address())
+        );
+
+        let private = PrivateChunk::new(value.clone(), owner);
+        assert_eq!(
+            predict_address(Kind::Private, &value, Some(&owner)),
+            Ok(*private.address())
+        );
+        assert_eq!(
+            predict_address(Kind::Private, &value, None),
+            Err(Error::InvalidOperation)
+        );
+    }
+
     #[test]
     fn zbase32_encode_decode_chunk_address() -> Result<()> {
         let name = XorName::random();
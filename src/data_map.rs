@@ -0,0 +1,247 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{ChunkAddress, Error, Result, XorName, MAX_CHUNK_SIZE_IN_BYTES};
+use serde::{Deserialize, Serialize};
+
+/// Version of the `DataMap` format defined here.
+pub const DATA_MAP_VERSION: u16 = 1;
+
+/// Details of one of the chunks some content was self-encrypted into.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct ChunkInfo {
+    /// Hash of the chunk's content before encryption.
+    pub src_hash: XorName,
+    /// Hash of the encrypted chunk, i.e. the name it's stored under.
+    pub dst_hash: XorName,
+    /// Size in bytes of the chunk's content before encryption.
+    pub size: u64,
+}
+
+/// Link of a `ChunkChain`, committing to the link of the chunk before it.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct ChainLink {
+    /// Hash of the encrypted chunk, i.e. the name it's stored under.
+    pub dst_hash: XorName,
+    /// Hash of the link of the chunk before, all zeros for the first chunk.
+    pub prev: XorName,
+}
+
+impl ChainLink {
+    /// Returns the hash of the link, which the link of the next chunk commits to.
+    pub fn hash(&self) -> XorName {
+        XorName::from_content(&[&self.dst_hash.0, &self.prev.0])
+    }
+}
+
+/// The chunks of some content chained in order, the link of each committing to the
+/// one before it. Knowing only the head of the chain, a partial download can tell
+/// whether chunks have been dropped or reordered, without the whole `DataMap`.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct ChunkChain {
+    links: Vec<ChainLink>,
+}
+
+impl ChunkChain {
+    /// Chains the chunks stored under the given names, in order.
+    pub fn new(dst_hashes: impl IntoIterator<Item = XorName>) -> Self {
+        let mut links: Vec<ChainLink> = vec![];
+        for dst_hash in dst_hashes {
+            let prev = links.last().map_or(XorName([0; 32]), ChainLink::hash);
+            links.push(ChainLink { dst_hash, prev });
+        }
+        Self { links }
+    }
+
+    /// Returns the links, in order.
+    pub fn links(&self) -> &[ChainLink] {
+        &self.links
+    }
+
+    /// Returns the hash of the last link, which commits to all the chunks,
+    /// all zeros if there are none.
+    pub fn head(&self) -> XorName {
+        self.links.last().map_or(XorName([0; 32]), ChainLink::hash)
+    }
+
+    /// Checks `links` are the first ones of a chain, in order, returning
+    /// `Err(Error::BrokenChunkChain)` with the position of the first one that isn't.
+    pub fn verify_prefix(links: &[ChainLink]) -> Result<()> {
+        let mut prev = XorName([0; 32]);
+        for (index, link) in links.iter().enumerate() {
+            if link.prev != prev {
+                return Err(Error::BrokenChunkChain(index as u64));
+            }
+            prev = link.hash();
+        }
+        Ok(())
+    }
+
+    /// Checks `links` are the whole chain ending at `head`, in order, i.e.
+    /// none of them have been dropped, even at the end, or reordered.
+    pub fn verify(links: &[ChainLink], head: XorName) -> Result<()> {
+        Self::verify_prefix(links)?;
+        let last = links.last().map_or(XorName([0; 32]), ChainLink::hash);
+        if last == head {
+            Ok(())
+        } else {
+            Err(Error::BrokenChunkChain(links.len() as u64))
+        }
+    }
+}
+
+impl From<&DataMap> for ChunkChain {
+    fn from(data_map: &DataMap) -> Self {
+        Self::new(data_map.chunks().iter().map(|chunk| chunk.dst_hash))
+    }
+}
+
+/// The output of self-encrypting some content: the chunks, in order, needed to
+/// decrypt and put it back together.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct DataMap {
+    /// Version of the format.
+    version: u16,
+    /// The chunks, in the order their content is to be put back together.
+    chunks: Vec<ChunkInfo>,
+}
+
+impl DataMap {
+    /// Creates a new `DataMap` of the current version out of the given chunks.
+    pub fn new(chunks: Vec<ChunkInfo>) -> Result<Self> {
+        let data_map = Self {
+            version: DATA_MAP_VERSION,
+            chunks,
+        };
+        data_map.validate()?;
+        Ok(data_map)
+    }
+
+    /// Returns the version of the format.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the chunks.
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.chunks
+    }
+
+    /// Returns the size in bytes of the content.
+    pub fn content_size(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.size).sum()
+    }
+
+    /// Returns the addresses the encrypted chunks are stored at, in order.
+    pub fn chunk_addresses(&self) -> Vec<ChunkAddress> {
+        self.chunks
+            .iter()
+            .map(|chunk| ChunkAddress::Public(chunk.dst_hash))
+            .collect()
+    }
+
+    /// Checks the `DataMap` is of the supported version and lists at least one
+    /// chunk, none of them empty or larger than a Chunk can be.
+    /// A `DataMap` deserialised from untrusted bytes should be checked with this.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != DATA_MAP_VERSION {
+            return Err(Error::UnsupportedProtocol {
+                theirs: self.version,
+                ours: DATA_MAP_VERSION,
+            });
+        }
+        if self.chunks.is_empty() {
+            return Err(Error::InvalidOperation);
+        }
+        for chunk in &self.chunks {
+            if chunk.size == 0 {
+                return Err(Error::InvalidOperation);
+            }
+            if chunk.size > MAX_CHUNK_SIZE_IN_BYTES {
+                return Err(Error::ExceededSize);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkChain, ChunkInfo, DataMap, DATA_MAP_VERSION};
+    use crate::{utils, Error, Result, XorName, MAX_CHUNK_SIZE_IN_BYTES};
+
+    fn chunk_info(size: u64) -> ChunkInfo {
+        ChunkInfo {
+            src_hash: XorName::random(),
+            dst_hash: XorName::random(),
+            size,
+        }
+    }
+
+    #[test]
+    fn chunk_chain_detects_truncation_and_reordering() -> Result<()> {
+        let data_map = DataMap::new(vec![chunk_info(10), chunk_info(20), chunk_info(5)])?;
+        let chain = ChunkChain::from(&data_map);
+        let head = chain.head();
+        let links = chain.links();
+        assert_eq!(links.len(), 3);
+
+        ChunkChain::verify(links, head)?;
+        ChunkChain::verify_prefix(&links[..2])?;
+        assert_eq!(
+            ChunkChain::verify(&links[..2], head),
+            Err(Error::BrokenChunkChain(2))
+        );
+        assert_eq!(
+            ChunkChain::verify_prefix(&links[1..]),
+            Err(Error::BrokenChunkChain(0))
+        );
+        let reordered = vec![links[0], links[2], links[1]];
+        assert_eq!(
+            ChunkChain::verify(&reordered, head),
+            Err(Error::BrokenChunkChain(1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn data_map_validation() -> Result<()> {
+        let data_map = DataMap::new(vec![chunk_info(10), chunk_info(20), chunk_info(5)])?;
+        assert_eq!(data_map.version(), DATA_MAP_VERSION);
+        assert_eq!(data_map.content_size(), 35);
+        assert_eq!(data_map.chunk_addresses().len(), 3);
+
+        let decoded: DataMap = utils::deserialise(&utils::serialise(&data_map)?)?;
+        decoded.validate()?;
+        assert_eq!(decoded, data_map);
+
+        assert_eq!(DataMap::new(vec![]), Err(Error::InvalidOperation));
+        assert_eq!(
+            DataMap::new(vec![chunk_info(0)]),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(
+            DataMap::new(vec![chunk_info(MAX_CHUNK_SIZE_IN_BYTES + 1)]),
+            Err(Error::ExceededSize)
+        );
+
+        let future = DataMap {
+            version: DATA_MAP_VERSION + 1,
+            ..data_map
+        };
+        assert_eq!(
+            future.validate(),
+            Err(Error::UnsupportedProtocol {
+                theirs: DATA_MAP_VERSION + 1,
+                ours: DATA_MAP_VERSION,
+            })
+        );
+        Ok(())
+    }
+}
@@ -8,10 +8,10 @@
 // Software.
 
 use super::register::Address;
-use crate::PublicKey;
+use crate::{PublicKey, Token};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     result,
 };
@@ -35,6 +35,9 @@ impl<'a, T> Debug for ErrorDebug<'a, T> {
 }
 
 /// Main error type for the crate.
+///
+/// Errors are sent between nodes serialised, each variant by its index: new variants
+/// are only ever added at the end, so the existing ones keep theirs.
 #[derive(Error, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant)]
@@ -104,6 +107,86 @@ pub enum Error {
     /// The CRDT operation cannot be applied as it targets a different content address.
     #[error("The CRDT operation cannot be applied as it targets a different content address.")]
     CrdtWrongAddress(Address),
+    /// The version expected by a write precondition does not match the current version.
+    #[error("Version mismatch: expected {expected}, but the current version is {actual}")]
+    VersionMismatch {
+        /// The version the writer expected to be current.
+        expected: u64,
+        /// The actual current version.
+        actual: u64,
+    },
+    /// The last entry is not the one a conditional append expected.
+    #[error(
+        "Last entry mismatch: expected {expected:?}, but the current last entry is {actual:?}"
+    )]
+    LastEntryMismatch {
+        /// Hash of the last entry the writer expected, `None` for no entries.
+        expected: Option<[u8; 32]>,
+        /// Hash of the actual last entry, `None` for no entries.
+        actual: Option<[u8; 32]>,
+    },
+    /// An entry does not satisfy the schema of the data.
+    #[error("Schema violation: {0}")]
+    SchemaViolation(String),
+    /// The data already holds the maximum number of entries its policy allows.
+    #[error("Data is full, it cannot hold more than {max} entries")]
+    DataFull {
+        /// The maximum number of entries.
+        max: u64,
+    },
+    /// The balance does not cover the amount to debit.
+    #[error("Insufficient balance: {balance} is less than the {amount} to debit")]
+    InsufficientBalance {
+        /// The current balance.
+        balance: Token,
+        /// The amount to debit.
+        amount: Token,
+    },
+    /// The protocol version of a peer is not one this node supports.
+    #[error("Unsupported protocol version {theirs}, the supported version is {ours}")]
+    UnsupportedProtocol {
+        /// The protocol version used by the peer.
+        theirs: u16,
+        /// The latest protocol version supported locally.
+        ours: u16,
+    },
+    /// Failed to derive a shared key, or to encrypt or decrypt with it.
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+    /// The CRDT operation has already been applied, so applying it again has no effect.
+    #[error("The CRDT operation has already been applied")]
+    OpAlreadyApplied,
+    /// The operation references parent entries which are not held yet. They need
+    /// to be fetched and applied before the operation can be applied.
+    #[error("CRDT operation references {} unknown parent entries", hashes.len())]
+    MissingParents {
+        /// Hashes of the parent entries not held.
+        hashes: BTreeSet<[u8; 32]>,
+    },
+    /// The msg of a transfer is not valid.
+    #[error("Invalid transfer msg: {0}")]
+    InvalidMsg(String),
+    /// A chain of section keys doesn't prove the last key descends from the trusted one.
+    #[error("Invalid section proof: {0}")]
+    InvalidSectionProof(String),
+    /// The entry validator used doesn't match the one the policy of the data requires.
+    #[error("Entry validator doesn't match the one set in the policy")]
+    ValidatorMismatch,
+    /// A chain of chunks doesn't link up at the given position, i.e. chunks have
+    /// been dropped or reordered there.
+    #[error("Chunk chain broken at link {0}")]
+    BrokenChunkChain(u64),
+    /// As many ops from the given source as can be held are already waiting for their
+    /// dependencies, so the op can't be held until it's ready.
+    #[error("Too many buffered operations from PublicKey: {0}")]
+    OpBufferFull(PublicKey),
+    /// The entry would have more entries in its causal past than the policy of the
+    /// data allows.
+    #[error("Too many entries, the data cannot hold more than {max} entries")]
+    TooManyEntries {
+        /// The maximum number of entries.
+        max: u64,
+    },
 }
 
 pub(crate) fn convert_bincode_error(err: bincode::Error) -> Error {
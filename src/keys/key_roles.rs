@@ -0,0 +1,95 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Public keys tagged with the role of their holder, so APIs expecting the key of a
+//! section can't be handed the key of a client by mistake. They are serialised as the
+//! bare `PublicKey`, so using them doesn't change the wire format.
+
+use super::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Deref,
+};
+
+macro_rules! key_role {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+        #[serde(transparent)]
+        pub struct $name(PublicKey);
+
+        impl $name {
+            /// Tags `key` with this role.
+            pub fn new(key: PublicKey) -> Self {
+                Self(key)
+            }
+
+            /// Returns the untagged key.
+            pub fn public_key(&self) -> PublicKey {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = PublicKey;
+
+            fn deref(&self) -> &PublicKey {
+                &self.0
+            }
+        }
+
+        impl From<$name> for PublicKey {
+            fn from(key: $name) -> Self {
+                key.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+                Display::fmt(&self.0, formatter)
+            }
+        }
+    };
+}
+
+key_role!(
+    /// Key of a section, i.e. the BLS public key of its elders.
+    SectionKey
+);
+
+key_role!(
+    /// Key identifying a node.
+    NodeKey
+);
+
+key_role!(
+    /// Key of a client.
+    ClientKey
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientKey, SectionKey};
+    use crate::{utils, Keypair, PublicKey, Result};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn key_roles_serialise_as_public_key() -> Result<()> {
+        let key = Keypair::new_ed25519(&mut OsRng).public_key();
+        let section_key = SectionKey::new(key);
+
+        assert_eq!(utils::serialise(&section_key)?, utils::serialise(&key)?);
+        let client_key: ClientKey = utils::deserialise(&utils::serialise(&key)?)?;
+        assert_eq!(PublicKey::from(client_key), key);
+        assert_eq!(section_key.to_bytes(), key.to_bytes());
+
+        Ok(())
+    }
+}
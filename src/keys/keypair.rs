@@ -14,10 +14,10 @@
 //! secret key.
 
 use crate::{Error, Result};
-use crate::{PublicKey, SecretKey, Signature, SignatureShare};
+use crate::{PublicKey, SecretKey, Signature, SignatureShare, ThresholdKey};
 
 use ed25519_dalek::Signer;
-use rand::{CryptoRng, Rng};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
@@ -49,6 +49,23 @@ impl OwnerType {
         }
     }
 
+    /// Returns a reference to the key set in case this is a Multi owner.
+    pub fn pk_set(&self) -> Option<&PublicKeySet> {
+        match self {
+            Self::Single(_) => None,
+            Self::Multi(key_set) => Some(key_set),
+        }
+    }
+
+    /// Verifies `signature` is the section signature over `payload`, i.e. a BLS
+    /// signature aggregated from the shares of a Multi owner's key set.
+    ///
+    /// Returns `Err(Error::InvalidOwnerNotPublicKeySet)` if this is a Single owner.
+    pub fn verify_section<T: AsRef<[u8]>>(&self, signature: &Signature, payload: T) -> Result<()> {
+        let key_set = self.pk_set().ok_or(Error::InvalidOwnerNotPublicKeySet)?;
+        ThresholdKey::new(key_set.clone()).verify_threshold_sig(payload, signature)
+    }
+
     ///
     pub fn verify<T: Serialize>(&self, signature: &Signature, data: &T) -> bool {
         let data = match bincode::serialize(&data) {
@@ -154,7 +171,7 @@ impl Eq for Keypair {}
 
 impl Keypair {
     /// Constructs a random Ed25519 keypair.
-    pub fn new_ed25519<T: CryptoRng + Rng>(rng: &mut T) -> Self {
+    pub fn new_ed25519<T: CryptoRng + RngCore>(rng: &mut T) -> Self {
         let keypair = ed25519_dalek::Keypair::generate(rng);
         Self::Ed25519(Arc::new(keypair))
     }
@@ -237,9 +254,8 @@ impl Debug for BlsKeypairShare {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "BlsKeyPairShare {{ index: {:?}, secret: {:?}, public: {:?}, public_key_set: PkSet {{ public_key: {:?} }} }}",
+            "BlsKeyPairShare {{ index: {:?}, secret: .., public: {:?}, public_key_set: PkSet {{ public_key: {:?} }} }}",
             self.index,
-            self.secret,
             self.public,
             self.public_key_set.public_key()
         )
@@ -278,4 +294,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn secrets_are_redacted_unless_exposed() -> Result<()> {
+        for keypair in gen_keypairs() {
+            let secret_key = keypair.secret_key()?;
+            let secret = secret_key.expose_secret()?;
+            let hex_secret = hex::encode(&secret);
+
+            let printed = vec![
+                format!("{:?}", keypair),
+                format!("{:?}", secret_key),
+                format!("{}", secret_key),
+            ];
+            for printed in printed {
+                assert!(printed.ends_with("(..)"), "{}", printed);
+                assert!(!printed.contains(&hex_secret));
+            }
+            if let Keypair::BlsShare(share) = &keypair {
+                assert!(format!("{:?}", share).contains("secret: .."));
+            }
+        }
+
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let secret = keypair.secret_key()?.expose_secret()?;
+        let restored = SecretKey::ed25519_from_hex(&hex::encode(secret))?;
+        assert_eq!(
+            Keypair::from(match restored {
+                SecretKey::Ed25519(secret) => secret,
+                SecretKey::BlsShare(_) => panic!("unexpected BLS secret key"),
+            }),
+            keypair
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn owner_type_verify_section() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let sk_set = threshold_crypto::SecretKeySet::random(1, &mut rng);
+        let owner = OwnerType::Multi(sk_set.public_keys());
+        assert_eq!(owner.pk_set(), Some(&sk_set.public_keys()));
+
+        let payload = b"payload";
+        let shares: Vec<_> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).sign(payload)))
+            .collect();
+        let sig = sk_set
+            .public_keys()
+            .combine_signatures(shares.iter().map(|(i, share)| (*i, share)))
+            .map_err(|_| Error::InvalidSignature)?;
+        owner.verify_section(&Signature::Bls(sig.clone()), payload)?;
+        assert_eq!(
+            owner.verify_section(&Signature::Bls(sig.clone()), b"other payload"),
+            Err(Error::InvalidSignature)
+        );
+
+        let single = OwnerType::Single(Keypair::new_ed25519(&mut rng).public_key());
+        assert_eq!(single.pk_set(), None);
+        assert_eq!(
+            single.verify_section(&Signature::Bls(sig), payload),
+            Err(Error::InvalidOwnerNotPublicKeySet)
+        );
+        Ok(())
+    }
 }
@@ -13,14 +13,21 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
+mod key_formats;
+mod key_roles;
 mod keypair;
 mod node_keypairs;
 mod public_key;
 mod secret_key;
+mod shared_key;
 mod signature;
+mod threshold_key;
 
 pub use self::signature::*;
+pub use key_roles::*;
 pub use keypair::*;
 pub use node_keypairs::*;
 pub use public_key::*;
 pub use secret_key::*;
+pub use shared_key::*;
+pub use threshold_key::*;
@@ -7,48 +7,114 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::keys::{BlsKeypairShare, SignatureShare};
+use crate::keys::{BlsKeypairShare, NodeKey, SignatureShare};
 use crate::{PublicKey, Signature};
 use ed25519_dalek::Keypair as Ed25519Keypair;
-use rand::{CryptoRng, Rng};
-use serde::{Deserialize, Serialize};
+use rand_core::{CryptoRng, RngCore};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use signature::Signer;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+    mem,
+};
 use threshold_crypto::{
     serde_impl::SerdeSecret, PublicKeySet, SecretKeyShare as BlsSecretKeyShare,
 };
 use xor_name::XorName;
 
+/// Number of section key epochs a node keeps its BLS keypair share of.
+pub const MAX_RETAINED_EPOCHS: usize = 8;
+
 /// This is used at a network node for holding the
 /// obligatory Ed25519 keypair needed as Adult, and
 /// then a BLS keypair share when being promoted to Elder.
 /// (Also the corresponding public keys).
 /// The Ed25519 is kept as Elder, in case it is demoted.
-#[derive(Serialize, Deserialize)]
+/// The BLS keypair shares of past section key epochs are kept as well,
+/// so that artifacts signed before a key rotation can still be verified.
 pub struct NodeKeypairs {
     ed25519: Ed25519Keypair,
     bls: Option<BlsKeypairShare>,
+    bls_epochs: BTreeMap<u64, BlsKeypairShare>,
+}
+
+impl Serialize for NodeKeypairs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.ed25519, &self.bls, &self.bls_epochs).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeKeypairs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NodeKeypairsVisitor;
+
+        impl<'de> de::Visitor<'de> for NodeKeypairsVisitor {
+            type Value = NodeKeypairs;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                write!(formatter, "NodeKeypairs")
+            }
+
+            fn visit_seq<V: de::SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
+                let ed25519 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let bls = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                // Keypairs encoded before the shares of past epochs were kept end with
+                // the current share, and bincode fails to read anything past it.
+                let bls_epochs = seq.next_element().ok().flatten().unwrap_or_default();
+                Ok(NodeKeypairs {
+                    ed25519,
+                    bls,
+                    bls_epochs,
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple(3, NodeKeypairsVisitor)
+    }
 }
 
 impl NodeKeypairs {
     /// Constructs a `NodeKeypairs` with a random Ed25519 keypair and no BLS keys.
-    pub fn new<T: CryptoRng + Rng>(rng: &mut T) -> Self {
+    pub fn new<T: CryptoRng + RngCore>(rng: &mut T) -> Self {
         let ed25519 = Ed25519Keypair::generate(rng);
 
-        Self { ed25519, bls: None }
+        Self {
+            ed25519,
+            bls: None,
+            bls_epochs: BTreeMap::new(),
+        }
     }
 
     /// Constructs a `NodeKeypairs` whose name is in the interval [start, end] (both endpoints inclusive).
-    pub fn within_range<T: CryptoRng + Rng>(start: &XorName, end: &XorName, rng: &mut T) -> Self {
+    pub fn within_range<T: CryptoRng + RngCore>(
+        start: &XorName,
+        end: &XorName,
+        rng: &mut T,
+    ) -> Self {
         let mut ed25519 = Ed25519Keypair::generate(rng);
         loop {
             let name: XorName = PublicKey::Ed25519(ed25519.public).into();
             if name >= *start && name <= *end {
-                return Self { ed25519, bls: None };
+                return Self {
+                    ed25519,
+                    bls: None,
+                    bls_epochs: BTreeMap::new(),
+                };
             }
             ed25519 = Ed25519Keypair::generate(rng);
         }
     }
 
+    /// Returns the Ed25519 public key identifying the node.
+    pub fn node_key(&self) -> NodeKey {
+        NodeKey::new(PublicKey::Ed25519(self.ed25519.public))
+    }
+
     /// Returns the BLS if any, else the Ed25519.
     pub fn public_key(&self) -> PublicKey {
         if let Some(keys) = &self.bls {
@@ -105,7 +171,161 @@ impl NodeKeypairs {
     }
 
     /// Clears the `NodeKeypairs`'s BLS keypair share, i.e. sets it to `None`.
+    /// The shares of past epochs are kept.
     pub fn clear_bls_keys(&mut self) {
         self.bls = None;
     }
+
+    /// Stores the BLS keypair share of the section key `epoch`. If it's the latest
+    /// epoch held, it becomes the `NodeKeypairs`'s BLS keypair share used for signing.
+    /// Only the latest `MAX_RETAINED_EPOCHS` epochs are kept.
+    pub fn add_share(
+        &mut self,
+        epoch: u64,
+        index: usize,
+        secret_share: BlsSecretKeyShare,
+        public_set: PublicKeySet,
+    ) {
+        let public = secret_share.public_key_share();
+        let share = BlsKeypairShare {
+            index,
+            secret: SerdeSecret(secret_share),
+            public,
+            public_key_set: public_set,
+        };
+        let _ = self.bls_epochs.insert(epoch, share.clone());
+        if self.latest_epoch() == Some(epoch) {
+            self.bls = Some(share);
+        }
+
+        while self.bls_epochs.len() > MAX_RETAINED_EPOCHS {
+            let oldest = self.bls_epochs.keys().next().copied();
+            if let Some(oldest) = oldest {
+                let _ = self.bls_epochs.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the BLS keypair share of the section key `epoch`, if still held.
+    pub fn share_at(&self, epoch: u64) -> Option<&BlsKeypairShare> {
+        self.bls_epochs.get(&epoch)
+    }
+
+    /// Returns the latest section key epoch a BLS keypair share is held for.
+    pub fn latest_epoch(&self) -> Option<u64> {
+        self.bls_epochs.keys().next_back().copied()
+    }
+
+    /// Drops the BLS keypair shares of the epochs before `epoch`, e.g. once
+    /// no artifacts signed with them need to be verified anymore. If the share
+    /// used for signing is dropped, the one of the latest epoch left, if any, is
+    /// used instead.
+    pub fn prune_epochs_before(&mut self, epoch: u64) {
+        let retained = self.bls_epochs.split_off(&epoch);
+        let pruned = mem::replace(&mut self.bls_epochs, retained);
+        let is_pruned = |bls: &BlsKeypairShare| pruned.values().any(|share| share == bls);
+        if self.bls.as_ref().is_some_and(is_pruned) {
+            self.bls = self.bls_epochs.values().next_back().cloned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use threshold_crypto::SecretKeySet;
+
+    #[test]
+    fn bls_shares_of_past_epochs_are_kept() {
+        let mut rng = rand::thread_rng();
+        let mut keypairs = NodeKeypairs::new(&mut OsRng);
+        let sk_sets: Vec<_> = (0..MAX_RETAINED_EPOCHS + 2)
+            .map(|_| SecretKeySet::random(1, &mut rng))
+            .collect();
+
+        keypairs.add_share(
+            1,
+            0,
+            sk_sets[1].secret_key_share(0),
+            sk_sets[1].public_keys(),
+        );
+        keypairs.add_share(
+            0,
+            0,
+            sk_sets[0].secret_key_share(0),
+            sk_sets[0].public_keys(),
+        );
+
+        // Adding the share of an older epoch doesn't replace the current one
+        assert_eq!(keypairs.latest_epoch(), Some(1));
+        assert_eq!(keypairs.public_key_set(), Some(&sk_sets[1].public_keys()));
+        let share = keypairs
+            .share_at(0)
+            .map(|share| share.public_key_set.clone());
+        assert_eq!(share, Some(sk_sets[0].public_keys()));
+
+        for (epoch, sk_set) in sk_sets.iter().enumerate().skip(2) {
+            keypairs.add_share(
+                epoch as u64,
+                0,
+                sk_set.secret_key_share(0),
+                sk_set.public_keys(),
+            );
+        }
+        assert_eq!(
+            keypairs.latest_epoch(),
+            Some(MAX_RETAINED_EPOCHS as u64 + 1)
+        );
+        assert!(keypairs.share_at(1).is_none());
+        assert!(keypairs.share_at(2).is_some());
+
+        keypairs.prune_epochs_before(5);
+        assert!(keypairs.share_at(4).is_none());
+        assert!(keypairs.share_at(5).is_some());
+
+        // Demotion keeps past shares to verify with
+        keypairs.clear_bls_keys();
+        assert!(keypairs.public_key_set().is_none());
+        assert!(keypairs.share_at(5).is_some());
+    }
+
+    #[test]
+    fn pruning_the_latest_epoch_drops_its_share() {
+        let mut rng = rand::thread_rng();
+        let mut keypairs = NodeKeypairs::new(&mut OsRng);
+        let node_key = PublicKey::Ed25519(keypairs.ed25519.public);
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        keypairs.add_share(3, 0, sk_set.secret_key_share(0), sk_set.public_keys());
+
+        keypairs.prune_epochs_before(3);
+        assert_eq!(keypairs.public_key_set(), Some(&sk_set.public_keys()));
+
+        keypairs.prune_epochs_before(4);
+        assert!(keypairs.share_at(3).is_none());
+        assert!(keypairs.public_key_set().is_none());
+        assert_eq!(keypairs.public_key(), node_key);
+        assert!(keypairs.sign_using_bls(b"data").is_none());
+    }
+
+    #[test]
+    fn keypairs_encoded_before_epochs_still_decode() -> crate::Result<()> {
+        let mut rng = rand::thread_rng();
+        let mut keypairs = NodeKeypairs::new(&mut OsRng);
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        keypairs.add_share(1, 0, sk_set.secret_key_share(0), sk_set.public_keys());
+
+        let decoded: NodeKeypairs =
+            crate::utils::deserialise(&crate::utils::serialise(&keypairs)?)?;
+        assert_eq!(decoded.latest_epoch(), Some(1));
+        assert_eq!(decoded.public_key(), keypairs.public_key());
+
+        // Layout from before the shares of past epochs were kept
+        let earliest = crate::utils::serialise(&(&keypairs.ed25519, &keypairs.bls))?;
+        let decoded: NodeKeypairs = crate::utils::deserialise(&earliest)?;
+        assert_eq!(decoded.latest_epoch(), None);
+        assert_eq!(decoded.public_key(), keypairs.public_key());
+        assert_eq!(decoded.node_key(), keypairs.node_key());
+        Ok(())
+    }
 }
@@ -183,9 +183,9 @@ impl From<PublicKey> for XorName {
             PublicKey::Bls(pub_key) => pub_key.to_bytes(),
             PublicKey::BlsShare(pub_key) => pub_key.to_bytes(),
         };
-        let mut xor_name = XorName::random();
-        xor_name.0.clone_from_slice(&bytes[..XOR_NAME_LEN]);
-        xor_name
+        let mut name = [0; XOR_NAME_LEN];
+        name.clone_from_slice(&bytes[..XOR_NAME_LEN]);
+        XorName(name)
     }
 }
 
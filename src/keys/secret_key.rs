@@ -13,13 +13,15 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
-use crate::{Error, Result};
+use crate::{utils, Error, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Display, Formatter};
 use threshold_crypto::{self, serde_impl::SerdeSecret};
 // TODO: remove clones. We need to restructure to hold keypair ones and only require references for this.
 /// Wrapper for different secret key types.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// `Debug` and `Display` don't print the secret; `expose_secret` has to be called to get it.
+#[derive(Serialize, Deserialize)]
 pub enum SecretKey {
     /// Ed25519 secretkey.
     Ed25519(ed25519_dalek::SecretKey),
@@ -49,6 +51,27 @@ impl SecretKey {
         })?;
         Ok(Self::Ed25519(ed25519_sk))
     }
+
+    /// Returns the bytes of the secret key, i.e. the raw 32 bytes of an Ed25519 key,
+    /// or the serialised share of a BLS key.
+    ///
+    /// This is the only way to get the secret out of the key; take care where it ends up.
+    pub fn expose_secret(&self) -> Result<Vec<u8>> {
+        match self {
+            Self::Ed25519(secret) => Ok(secret.to_bytes().to_vec()),
+            Self::BlsShare(secret) => utils::serialise(secret),
+        }
+    }
+}
+
+impl Debug for SecretKey {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SecretKey::")?;
+        match self {
+            Self::Ed25519(_) => write!(formatter, "Ed25519(..)"),
+            Self::BlsShare(_) => write!(formatter, "BlsShare(..)"),
+        }
+    }
 }
 
 impl Display for SecretKey {
@@ -0,0 +1,155 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{PublicKey, SecretKey};
+use crate::{Error, Result};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use curve25519_dalek::{edwards::CompressedEdwardsY, scalar::Scalar};
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+use std::{
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// Length in bytes of a `SymmetricKey`.
+pub const SYMMETRIC_KEY_LEN: usize = 32;
+
+/// Length in bytes of the nonce prefixed to encrypted payloads.
+const NONCE_LEN: usize = 12;
+
+/// HKDF info binding derived keys to their use here.
+const SHARED_KEY_INFO: &[u8] = b"sn_data_types::keys::shared_key";
+
+/// Symmetric key shared between two identities, used to encrypt data for each other.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SymmetricKey([u8; SYMMETRIC_KEY_LEN]);
+
+impl SymmetricKey {
+    /// Encrypts `plaintext` with ChaCha20-Poly1305, returning the random nonce
+    /// used followed by the ciphertext.
+    pub fn encrypt<T: CryptoRng + RngCore>(
+        &self,
+        rng: &mut T,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .cipher()
+            .encrypt(&Nonce::from(nonce), plaintext)
+            .map_err(|_| Error::Encryption("Could not encrypt payload".to_string()))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Decrypts a payload encrypted with `encrypt`, failing if it's been tampered with.
+    pub fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>> {
+        if encrypted.len() < NONCE_LEN {
+            return Err(Error::Encryption("Payload is too short".to_string()));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce
+            .try_into()
+            .map_err(|_| Error::Encryption("Payload is too short".to_string()))?;
+
+        self.cipher()
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| Error::Encryption("Could not decrypt payload".to_string()))
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&Key::from(self.0))
+    }
+}
+
+impl Debug for SymmetricKey {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SymmetricKey(..)")
+    }
+}
+
+/// Derives the key shared between the owner of `my_secret` and the owner of `their_public`,
+/// i.e. the same key is derived on both ends. The X25519 Diffie-Hellman secret computed from
+/// the Ed25519 keys is expanded with HKDF-SHA256.
+/// Only Ed25519 keys are supported, as BLS keys don't expose the operations required.
+pub fn derive_shared_key(my_secret: &SecretKey, their_public: &PublicKey) -> Result<SymmetricKey> {
+    let (secret, public) = match (my_secret, their_public) {
+        (SecretKey::Ed25519(secret), PublicKey::Ed25519(public)) => (secret, public),
+        _ => {
+            return Err(Error::Encryption(
+                "Shared keys can only be derived from Ed25519 keys".to_string(),
+            ))
+        }
+    };
+
+    // Same expansion of the secret key as done by Ed25519 signing.
+    let hash = Sha512::digest(secret.as_bytes());
+    let mut scalar_bytes = [0u8; 32];
+    scalar_bytes.copy_from_slice(&hash[..32]);
+    scalar_bytes[0] &= 248;
+    scalar_bytes[31] &= 127;
+    scalar_bytes[31] |= 64;
+    let scalar = Scalar::from_bits(scalar_bytes);
+
+    let point = CompressedEdwardsY(public.to_bytes())
+        .decompress()
+        .ok_or_else(|| Error::Encryption("Invalid Ed25519 public key".to_string()))?
+        .to_montgomery();
+    let shared_secret = (point * scalar).to_bytes();
+    // Low order points yield a secret known to anyone.
+    if shared_secret == [0u8; 32] {
+        return Err(Error::Encryption("Invalid Ed25519 public key".to_string()));
+    }
+
+    let mut key = [0u8; SYMMETRIC_KEY_LEN];
+    Hkdf::<Sha256>::new(None, &shared_secret)
+        .expand(SHARED_KEY_INFO, &mut key)
+        .map_err(|_| Error::Encryption("Could not expand shared secret".to_string()))?;
+
+    Ok(SymmetricKey(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn shared_key_roundtrip() -> Result<()> {
+        let alice = Keypair::new_ed25519(&mut OsRng);
+        let bob = Keypair::new_ed25519(&mut OsRng);
+        let eve = Keypair::new_ed25519(&mut OsRng);
+
+        let alice_key = derive_shared_key(&alice.secret_key()?, &bob.public_key())?;
+        let bob_key = derive_shared_key(&bob.secret_key()?, &alice.public_key())?;
+        assert_eq!(alice_key, bob_key);
+
+        let encrypted = alice_key.encrypt(&mut OsRng, b"shared data")?;
+        assert_eq!(bob_key.decrypt(&encrypted)?, b"shared data".to_vec());
+
+        let eve_key = derive_shared_key(&eve.secret_key()?, &alice.public_key())?;
+        assert!(eve_key.decrypt(&encrypted).is_err());
+
+        let mut tampered = encrypted;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(bob_key.decrypt(&tampered).is_err());
+
+        Ok(())
+    }
+}
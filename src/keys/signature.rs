@@ -13,13 +13,15 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
-use crate::utils;
+use crate::{utils, Error, Keypair, PublicKey, Result};
 
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     fmt::{self, Debug, Formatter},
     hash::{Hash, Hasher},
 };
+use threshold_crypto::PublicKeySet;
 
 /// A signature share, with its index in the combined collection.
 #[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
@@ -30,6 +32,36 @@ pub struct SignatureShare {
     pub share: threshold_crypto::SignatureShare,
 }
 
+impl SignatureShare {
+    /// Constructs a signature share over `payload` with its `index`, checking it's
+    /// the share of the key at that index in `pk_set`.
+    pub fn new<T: AsRef<[u8]>>(
+        index: usize,
+        share: threshold_crypto::SignatureShare,
+        pk_set: &PublicKeySet,
+        payload: T,
+    ) -> Result<Self> {
+        let share = Self { index, share };
+        share.verify_against(pk_set, payload)?;
+
+        Ok(share)
+    }
+
+    /// Returns `Ok(())` if this is a valid share over `payload` of the key at its
+    /// index in `pk_set`, so shares of other key sets or with a wrong index can
+    /// be told apart before being combined, and `Err(Error::InvalidSignature)` otherwise.
+    pub fn verify_against<T: AsRef<[u8]>>(&self, pk_set: &PublicKeySet, payload: T) -> Result<()> {
+        if pk_set
+            .public_key_share(self.index)
+            .verify(&self.share, payload)
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+}
+
 /// Wrapper for different signature types.
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
@@ -58,6 +90,27 @@ impl Signature {
             _ => None,
         }
     }
+
+    /// Returns the algorithm of the signature.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
+            Self::Bls(_) => SignatureAlgorithm::Bls,
+            Self::BlsShare(_) => SignatureAlgorithm::BlsShare,
+        }
+    }
+
+    /// Verifies this signature of `public_key` over `payload`, as `PublicKey::verify` does,
+    /// additionally rejecting malleable Ed25519 signatures, i.e. non-canonical ones
+    /// or those involving low-order points for the key or the signature's `R`.
+    pub fn verify_strict<T: AsRef<[u8]>>(&self, public_key: &PublicKey, payload: T) -> Result<()> {
+        match (public_key, self) {
+            (PublicKey::Ed25519(pub_key), Self::Ed25519(sig)) => pub_key
+                .verify_strict(payload.as_ref(), sig)
+                .map_err(|_| Error::InvalidSignature),
+            _ => public_key.verify(self, payload),
+        }
+    }
 }
 
 impl From<threshold_crypto::Signature> for Signature {
@@ -85,6 +138,66 @@ impl From<(usize, threshold_crypto::SignatureShare)> for Signature {
     }
 }
 
+/// Algorithm a signature was made with.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum SignatureAlgorithm {
+    /// Ed25519.
+    Ed25519,
+    /// BLS.
+    Bls,
+    /// BLS signature share.
+    BlsShare,
+}
+
+/// A signature along with who made it, with which algorithm and when, for
+/// long-lived records (e.g. receipts or attestations) to stay auditable.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct SignatureBundle {
+    /// Signature over the payload and `created_at`.
+    pub sig: Signature,
+    /// Algorithm of `sig`.
+    pub algo: SignatureAlgorithm,
+    /// Key which made `sig`.
+    pub signer: PublicKey,
+    /// Time the signature was made at, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl SignatureBundle {
+    /// Signs `payload` along with the `created_at` time with the keypair.
+    pub fn new(keypair: &Keypair, payload: &[u8], created_at: u64) -> Result<Self> {
+        let sig = keypair.sign(&Self::bytes_to_sign(payload, created_at)?);
+        Ok(Self {
+            algo: sig.algorithm(),
+            sig,
+            signer: keypair.public_key(),
+            created_at,
+        })
+    }
+
+    /// Verifies the bundle is consistent and `sig` is the signer's over `payload`.
+    pub fn verify(&self, payload: &[u8]) -> Result<()> {
+        if self.algo != self.sig.algorithm() {
+            return Err(Error::SigningKeyTypeMismatch);
+        }
+        let bytes = Self::bytes_to_sign(payload, self.created_at)?;
+        self.sig.verify_strict(&self.signer, bytes)
+    }
+
+    /// Verifies the bundle as `verify` does, additionally checking
+    /// it was made by `signer` no later than `not_after`.
+    pub fn verify_by(&self, payload: &[u8], signer: &PublicKey, not_after: u64) -> Result<()> {
+        if &self.signer != signer || self.created_at > not_after {
+            return Err(Error::InvalidSignature);
+        }
+        self.verify(payload)
+    }
+
+    fn bytes_to_sign(payload: &[u8], created_at: u64) -> Result<Vec<u8>> {
+        utils::serialise(&(payload, created_at))
+    }
+}
+
 #[allow(clippy::derive_hash_xor_eq)]
 impl Hash for Signature {
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -92,6 +205,20 @@ impl Hash for Signature {
     }
 }
 
+impl Ord for Signature {
+    fn cmp(&self, other: &Signature) -> Ordering {
+        utils::serialise(&self)
+            .unwrap_or_default()
+            .cmp(&utils::serialise(other).unwrap_or_default())
+    }
+}
+
+impl PartialOrd for Signature {
+    fn partial_cmp(&self, other: &Signature) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Debug for Signature {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(formatter, "Signature::")?;
@@ -102,3 +229,106 @@ impl Debug for Signature {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Signature, SignatureAlgorithm, SignatureBundle, SignatureShare};
+    use crate::{Error, Keypair, PublicKey, Result};
+    use rand::rngs::OsRng;
+    use std::convert::TryFrom;
+    use threshold_crypto::SecretKeySet;
+
+    #[test]
+    fn signature_share_verified_against_key_set() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let payload = b"payload";
+        let share = sk_set.secret_key_share(1).sign(payload);
+
+        let sig_share = SignatureShare::new(1, share.clone(), &pk_set, payload)?;
+        assert_eq!(sig_share.index, 1);
+        sig_share.verify_against(&pk_set, payload)?;
+        assert_eq!(
+            sig_share.verify_against(&pk_set, b"other"),
+            Err(Error::InvalidSignature)
+        );
+
+        // Wrong index, or another key set
+        assert_eq!(
+            SignatureShare::new(0, share.clone(), &pk_set, payload),
+            Err(Error::InvalidSignature)
+        );
+        let other_set = SecretKeySet::random(1, &mut rng).public_keys();
+        assert_eq!(
+            SignatureShare::new(1, share, &other_set, payload),
+            Err(Error::InvalidSignature)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_strict_accepts_valid_signatures() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let payload = b"payload";
+        let signature = keypair.sign(payload);
+
+        signature.verify_strict(&keypair.public_key(), payload)?;
+        assert_eq!(
+            signature.verify_strict(&keypair.public_key(), b"other"),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn signature_bundle_verification() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let payload = b"receipt";
+        let bundle = SignatureBundle::new(&keypair, payload, 1_600_000_000)?;
+        assert_eq!(bundle.algo, SignatureAlgorithm::Ed25519);
+
+        bundle.verify(payload)?;
+        bundle.verify_by(payload, &keypair.public_key(), 1_600_000_000)?;
+        assert_eq!(bundle.verify(b"other"), Err(Error::InvalidSignature));
+        assert_eq!(
+            bundle.verify_by(payload, &keypair.public_key(), 1_599_999_999),
+            Err(Error::InvalidSignature)
+        );
+
+        // The creation time is covered by the signature
+        let mut backdated = bundle.clone();
+        backdated.created_at -= 1;
+        assert_eq!(backdated.verify(payload), Err(Error::InvalidSignature));
+
+        let mut mislabelled = bundle;
+        mislabelled.algo = SignatureAlgorithm::Bls;
+        assert_eq!(
+            mislabelled.verify(payload),
+            Err(Error::SigningKeyTypeMismatch)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn verify_strict_rejects_low_order_points() {
+        // The identity point as key and `R`, with a zero `s`, satisfies the
+        // verification equation for any payload.
+        let mut identity = [0; 32];
+        identity[0] = 1;
+        let public_key = PublicKey::Ed25519(
+            ed25519_dalek::PublicKey::from_bytes(&identity).expect("valid point encoding"),
+        );
+        let mut sig_bytes = [0; 64];
+        sig_bytes[..32].copy_from_slice(&identity);
+        let signature = Signature::Ed25519(
+            ed25519_dalek::Signature::try_from(&sig_bytes[..]).expect("valid signature encoding"),
+        );
+
+        assert_eq!(
+            signature.verify_strict(&public_key, b"any payload"),
+            Err(Error::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug, Formatter};
+use threshold_crypto::PublicKeySet;
+
+/// A BLS public key set along with its threshold, identifying a group
+/// (e.g. a section or a DAO) as the owner or user of some data.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ThresholdKey {
+    pk_set: PublicKeySet,
+}
+
+impl ThresholdKey {
+    /// Constructs a `ThresholdKey` from the group's public key set.
+    pub fn new(pk_set: PublicKeySet) -> Self {
+        Self { pk_set }
+    }
+
+    /// Returns the public key set of the group.
+    pub fn pk_set(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the threshold, i.e. one less than the number of signature
+    /// shares needed to produce a valid signature of the group.
+    pub fn threshold(&self) -> usize {
+        self.pk_set.threshold()
+    }
+
+    /// Returns the group's public key, which is what is set as the owner
+    /// or user in the policies of group-owned data.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::Bls(self.pk_set.public_key())
+    }
+
+    /// Returns `Ok(())` if `signature` is a valid signature of the group over `payload`,
+    /// i.e. it was combined from more than `threshold` shares.
+    pub fn verify_threshold_sig<T: AsRef<[u8]>>(
+        &self,
+        payload: T,
+        signature: &Signature,
+    ) -> Result<()> {
+        match signature {
+            Signature::Bls(sig) => {
+                if self.pk_set.public_key().verify(sig, payload) {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidSignature)
+                }
+            }
+            Signature::BlsShare(_) => Err(Error::InvalidSignature),
+            Signature::Ed25519(_) => Err(Error::SigningKeyTypeMismatch),
+        }
+    }
+
+    /// Returns `Ok(())` if the group is `owner` and `signature` is a valid signature of
+    /// the group over `payload`, for checks that some data is acted on by its group owner.
+    ///
+    /// Returns `Err(Error::AccessDenied)` if the group isn't `owner`.
+    pub fn verify_owner_sig<T: AsRef<[u8]>>(
+        &self,
+        owner: &PublicKey,
+        payload: T,
+        signature: &Signature,
+    ) -> Result<()> {
+        let key = self.public_key();
+        if *owner != key {
+            return Err(Error::AccessDenied(key));
+        }
+        self.verify_threshold_sig(payload, signature)
+    }
+}
+
+impl From<PublicKeySet> for ThresholdKey {
+    fn from(pk_set: PublicKeySet) -> Self {
+        Self::new(pk_set)
+    }
+}
+
+impl From<ThresholdKey> for PublicKey {
+    fn from(key: ThresholdKey) -> Self {
+        key.public_key()
+    }
+}
+
+impl Debug for ThresholdKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ThresholdKey {{ pk_set: PkSet {{ public_key: {:?} }}, threshold: {} }}",
+            self.pk_set.public_key(),
+            self.threshold()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use threshold_crypto::SecretKeySet;
+
+    #[test]
+    fn verify_threshold_sig() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let key = ThresholdKey::new(sk_set.public_keys());
+        assert_eq!(key.threshold(), 1);
+        assert_eq!(
+            key.public_key(),
+            PublicKey::Bls(sk_set.public_keys().public_key())
+        );
+
+        let payload = b"payload";
+        let shares: Vec<_> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).sign(payload)))
+            .collect();
+        let sig = key
+            .pk_set()
+            .combine_signatures(shares.iter().map(|(i, share)| (*i, share)))
+            .map_err(|_| Error::InvalidSignature)?;
+        key.verify_threshold_sig(payload, &Signature::Bls(sig.clone()))?;
+
+        assert_eq!(
+            key.verify_threshold_sig(b"other payload", &Signature::Bls(sig)),
+            Err(Error::InvalidSignature)
+        );
+        let share = sk_set.secret_key_share(0).sign(payload);
+        assert_eq!(
+            key.verify_threshold_sig(payload, &Signature::from((0, share))),
+            Err(Error::InvalidSignature)
+        );
+        Ok(())
+    }
+}
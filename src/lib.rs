@@ -29,45 +29,70 @@
 )]
 
 mod chunk;
+mod data_map;
 mod errors;
 mod keys;
 mod map;
+mod rebind;
 /// Register data type
 pub mod register;
 mod rewards;
 mod section;
 mod sequence;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
 mod token;
 mod transfer;
 mod utils;
 
 pub use chunk::{
-    Address as ChunkAddress, Chunk, Kind as ChunkKind, PrivateChunk, PublicChunk,
-    MAX_CHUNK_SIZE_IN_BYTES,
+    predict_address as predict_chunk_address, Address as ChunkAddress, Chunk, ContentEncoding,
+    InlineChunk, Kind as ChunkKind, MaybeChunk, PrivateChunk, PublicChunk, MAX_CHUNK_SIZE_IN_BYTES,
+    MAX_INLINE_SIZE_IN_BYTES,
 };
+pub use data_map::{ChainLink, ChunkChain, ChunkInfo, DataMap, DATA_MAP_VERSION};
 pub use errors::{Error, Result};
 pub use keys::{
-    BlsKeypairShare, Keypair, NodeKeypairs, OwnerType, PublicKey, SecretKey, Signature,
-    SignatureShare, Signing,
+    derive_shared_key, BlsKeypairShare, ClientKey, Keypair, NodeKey, NodeKeypairs, OwnerType,
+    PublicKey, SecretKey, SectionKey, Signature, SignatureAlgorithm, SignatureBundle,
+    SignatureShare, Signing, SymmetricKey, ThresholdKey, MAX_RETAINED_EPOCHS,
 };
 pub use map::{
-    Action as MapAction, Address as MapAddress, Data as Map, Entries as MapEntries,
-    EntryActions as MapEntryActions, Kind as MapKind, PermissionSet as MapPermissionSet,
-    SeqData as SeqMap, SeqEntries as MapSeqEntries, SeqEntryAction as MapSeqEntryAction,
+    Action as MapAction, Address as MapAddress, Bloom as MapKeyFilter, Data as Map,
+    Entries as MapEntries, EntryActions as MapEntryActions, Kind as MapKind, MapCache, MapView,
+    PermissionSet as MapPermissionSet, Schema as MapSchema, SeqData as SeqMap,
+    SeqEntries as MapSeqEntries, SeqEntryAction as MapSeqEntryAction,
     SeqEntryActions as MapSeqEntryActions, SeqValue as MapSeqValue, UnseqData as UnseqMap,
     UnseqEntries as MapUnseqEntries, UnseqEntryAction as MapUnseqEntryAction,
     UnseqEntryActions as MapUnseqEntryActions, Value as MapValue, Values as MapValues,
+    MAX_MAP_KEY_SIZE, MAX_MAP_VALUE_SIZE,
 };
+pub use rebind::Rebind;
 pub use register::Address as RegisterAddress;
-pub use rewards::{AccumulatingReward, NodeAge, RewardAccumulation, RewardProposal};
-pub use section::SectionElders;
+pub use rewards::{
+    AccumulatingReward, AgeProgression, ChurnEvent, NodeAge, RewardAccumulation, RewardProposal,
+};
+pub use section::{SectionElders, SectionProof};
+#[cfg(feature = "compression")]
+pub use sequence::{
+    compress_entry as compress_sequence_entry, decompress_entry as decompress_sequence_entry,
+    CompressedSequence, COMPRESSION_THRESHOLD as SEQUENCE_COMPRESSION_THRESHOLD,
+    MAX_COMPRESSED_ENTRY_SIZE as SEQUENCE_MAX_COMPRESSED_ENTRY_SIZE,
+};
 pub use sequence::{
-    Action as SequenceAction, Address as SequenceAddress, Data as Sequence, DataOp as SequenceOp,
-    Entries as SequenceEntries, Entry as SequenceEntry, Index as SequenceIndex,
-    Kind as SequenceKind, Permissions as SequencePermissions, Policy as SequencePolicy,
+    Action as SequenceAction, Address as SequenceAddress, AppendStats as SequenceAppendStats,
+    Commitment as SequenceCommitment, Cursor as SequenceCursor, Data as Sequence,
+    DataBatchOp as SequenceBatchOp, DataOp as SequenceOp, Entries as SequenceEntries,
+    Entry as SequenceEntry, EntryHash as SequenceEntryHash, EntryKind as SequenceEntryKind,
+    EntryMeta as SequenceEntryMeta, EntryProof as SequenceEntryProof,
+    EntryValidator as SequenceEntryValidator, Index as SequenceIndex, Kind as SequenceKind,
+    OpBuffer as SequenceOpBuffer, Page as SequencePage, Permissions as SequencePermissions,
+    Policy as SequencePolicy, PolicyTemplate as SequencePolicyTemplate,
     PrivatePermissions as SequencePrivatePermissions, PrivatePolicy as SequencePrivatePolicy,
     PrivateSeqData, PublicPermissions as SequencePublicPermissions,
-    PublicPolicy as SequencePublicPolicy, PublicSeqData, User as SequenceUser,
+    PublicPolicy as SequencePublicPolicy, PublicSeqData, Snapshot as SequenceSnapshot,
+    TypedSequence, User as SequenceUser,
+    MAX_BUFFERED_OPS_PER_SOURCE as SEQUENCE_MAX_BUFFERED_OPS_PER_SOURCE,
 };
 pub use token::Token;
 pub use transfer::*;
@@ -119,6 +144,31 @@ impl Data {
     pub fn is_private(&self) -> bool {
         !self.is_public()
     }
+
+    /// Dispatches to the `visitor` method matching the data variant.
+    pub fn accept<V: DataVisitor>(&self, visitor: &mut V) -> V::Output {
+        match self {
+            Self::Chunk(chunk) => visitor.visit_chunk(chunk),
+            Self::Map(map) => visitor.visit_map(map),
+            Self::Sequence(sequence) => visitor.visit_sequence(sequence),
+            Self::Register(register) => visitor.visit_register(register),
+        }
+    }
+}
+
+/// Processing of `Data` written once per concern (e.g. storage, validation, metrics),
+/// with one method per data variant, instead of matching on the enum at every call site.
+pub trait DataVisitor {
+    /// The result of visiting a data item.
+    type Output;
+    /// Visits a Chunk.
+    fn visit_chunk(&mut self, chunk: &Chunk) -> Self::Output;
+    /// Visits a Map.
+    fn visit_map(&mut self, map: &Map) -> Self::Output;
+    /// Visits a Sequence.
+    fn visit_sequence(&mut self, sequence: &Sequence) -> Self::Output;
+    /// Visits a Register.
+    fn visit_register(&mut self, register: &Register) -> Self::Output;
 }
 
 impl From<Chunk> for Data {
@@ -27,23 +27,90 @@
 //! does not have to pass version numbers for keys, but it still must pass the next version number
 //! while modifying the Map shell.
 
-use crate::{utils, Error, PublicKey, Result};
-use serde::{Deserialize, Serialize};
+use crate::{utils, Error, PublicKey, Result, Signature, ThresholdKey};
+use serde::{de, ser::SerializeTupleVariant, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     mem,
+    ops::Bound,
+    sync::Arc,
 };
+use tiny_keccak::{Hasher, Sha3};
 use xor_name::XorName;
 
+/// Maximum size in bytes of a Map entry key.
+pub const MAX_MAP_KEY_SIZE: usize = 1024;
+/// Maximum size in bytes of a Map entry value.
+pub const MAX_MAP_VALUE_SIZE: usize = 1024 * 1024;
+
+/// Number of filter bits allocated per key when exporting a `Bloom` of Map keys,
+/// giving a false positive rate of roughly 1%.
+const BLOOM_BITS_PER_KEY: usize = 10;
+/// Number of bit positions each key sets in a `Bloom`.
+const BLOOM_NUM_HASHES: u32 = 7;
+/// Smallest number of bits a `Bloom` is created with.
+const BLOOM_MIN_BITS: usize = 64;
+/// Largest number of bit positions a `Bloom` received from another replica can set per key.
+const BLOOM_MAX_HASHES: u32 = 32;
+
+/// Layouts a Map has been encoded with, the earliest first. Variants are only ever
+/// appended, for those encoded before to keep their tag.
+///
+/// Maps encoded before their layout was versioned start with their address, whose kind
+/// is encoded as the tag of the `Address`: the first variants mirror those of `Address`,
+/// for such a Map to be decoded as one of them. Maps without a schema are still encoded
+/// with that layout.
+#[derive(Deserialize)]
+enum MapEncoding<E> {
+    /// Unsequenced Map without a schema: name, tag, entries, permissions, version and owner.
+    UnseqV0(
+        XorName,
+        u64,
+        Arc<E>,
+        BTreeMap<PublicKey, PermissionSet>,
+        u64,
+        PublicKey,
+    ),
+    /// Sequenced Map without a schema: name, tag, entries, permissions, version and owner.
+    SeqV0(
+        XorName,
+        u64,
+        Arc<E>,
+        BTreeMap<PublicKey, PermissionSet>,
+        u64,
+        PublicKey,
+    ),
+    /// Unsequenced Map with the fields of `UnseqV0`, followed by its schema.
+    UnseqV1(
+        XorName,
+        u64,
+        Arc<E>,
+        BTreeMap<PublicKey, PermissionSet>,
+        u64,
+        PublicKey,
+        Schema,
+    ),
+    /// Sequenced Map with the fields of `SeqV0`, followed by its schema.
+    SeqV1(
+        XorName,
+        u64,
+        Arc<E>,
+        BTreeMap<PublicKey, PermissionSet>,
+        u64,
+        PublicKey,
+        Schema,
+    ),
+}
+
 /// Map that is unpublished on the network. This data can only be fetched by the owner or
 /// those in the permissions fields with `Permission::Read` access.
-#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct SeqData {
     /// Network address.
     address: Address,
-    /// Key-Value semantics.
-    data: SeqEntries,
+    /// Key-Value semantics, shared with the views taken of the Map.
+    data: Arc<SeqEntries>,
     /// Maps an application key to a list of allowed or forbidden actions.
     permissions: BTreeMap<PublicKey, PermissionSet>,
     /// Version should be increased for any changes to Map fields except for data.
@@ -52,6 +119,10 @@ pub struct SeqData {
     ///
     /// Data Handlers in nodes enforce that a mutation request has a valid signature of the owner.
     owner: PublicKey,
+    /// Structural constraints all inserted and updated entries must satisfy.
+    ///
+    /// Only hashed when set, and Maps without one are encoded with their earlier layout.
+    schema: Option<Schema>,
 }
 
 impl Debug for SeqData {
@@ -62,12 +133,12 @@ impl Debug for SeqData {
 
 /// Map that is unpublished on the network. This data can only be fetched by the owner or
 /// those in the permissions fields with `Permission::Read` access.
-#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct UnseqData {
     /// Network address.
     address: Address,
-    /// Key-Value semantics.
-    data: UnseqEntries,
+    /// Key-Value semantics, shared with the views taken of the Map.
+    data: Arc<UnseqEntries>,
     /// Maps an application key to a list of allowed or forbidden actions.
     permissions: BTreeMap<PublicKey, PermissionSet>,
     /// Version should be increased for any changes to Map fields except for data.
@@ -76,6 +147,10 @@ pub struct UnseqData {
     ///
     /// Data Handlers in nodes enforce that a mutation request has a valid signature of the owner.
     owner: PublicKey,
+    /// Structural constraints all inserted and updated entries must satisfy.
+    ///
+    /// Only hashed when set, and Maps without one are encoded with their earlier layout.
+    schema: Option<Schema>,
 }
 
 impl Debug for UnseqData {
@@ -188,8 +263,172 @@ pub enum Action {
     ManagePermissions,
 }
 
+/// Structural constraints on the entries of a Map, so a Map shared between
+/// several users keeps the shape its owner expects.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Schema {
+    /// Maximum length of a key in bytes.
+    pub max_key_len: Option<u64>,
+    /// Content-type tag every value must start with.
+    pub content_type: Option<u8>,
+    /// Prefixes of which every key must start with at least one, if not empty.
+    pub key_prefixes: BTreeSet<Vec<u8>>,
+}
+
+impl Schema {
+    /// Checks the entry satisfies the schema.
+    ///
+    /// Returns `Err(Error::SchemaViolation)` describing the first constraint not satisfied.
+    pub fn validate(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        if let Some(max_key_len) = self.max_key_len {
+            if key.len() as u64 > max_key_len {
+                return Err(Error::SchemaViolation(format!(
+                    "key length {} exceeds the maximum of {}",
+                    key.len(),
+                    max_key_len
+                )));
+            }
+        }
+
+        if let Some(content_type) = self.content_type {
+            if value.first() != Some(&content_type) {
+                return Err(Error::SchemaViolation(format!(
+                    "value is not tagged with content type {}",
+                    content_type
+                )));
+            }
+        }
+
+        if !self.key_prefixes.is_empty()
+            && !self
+                .key_prefixes
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+        {
+            return Err(Error::SchemaViolation(
+                "key does not start with any of the required prefixes".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 macro_rules! impl_map {
     ($flavour:ident) => {
+        impl std::hash::Hash for $flavour {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.address.hash(state);
+                self.data.hash(state);
+                self.permissions.hash(state);
+                self.version.hash(state);
+                self.owner.hash(state);
+                if let Some(schema) = &self.schema {
+                    schema.hash(state);
+                }
+            }
+        }
+
+        impl Serialize for $flavour {
+            fn serialize<S: Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                let (index, variant, len) = match (self.address.is_seq(), &self.schema) {
+                    (false, None) => (0, "UnseqV0", 6),
+                    (true, None) => (1, "SeqV0", 6),
+                    (false, Some(_)) => (2, "UnseqV1", 7),
+                    (true, Some(_)) => (3, "SeqV1", 7),
+                };
+                let mut variant =
+                    serializer.serialize_tuple_variant("MapEncoding", index, variant, len)?;
+                variant.serialize_field(self.address.name())?;
+                variant.serialize_field(&self.address.tag())?;
+                variant.serialize_field(&self.data)?;
+                variant.serialize_field(&self.permissions)?;
+                variant.serialize_field(&self.version)?;
+                variant.serialize_field(&self.owner)?;
+                if let Some(schema) = &self.schema {
+                    variant.serialize_field(schema)?;
+                }
+                variant.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $flavour {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                let (kind, name, tag, data, permissions, version, owner, schema) =
+                    match MapEncoding::deserialize(deserializer)? {
+                        MapEncoding::UnseqV0(name, tag, data, permissions, version, owner) => (
+                            Kind::Unseq,
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            None,
+                        ),
+                        MapEncoding::SeqV0(name, tag, data, permissions, version, owner) => (
+                            Kind::Seq,
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            None,
+                        ),
+                        MapEncoding::UnseqV1(
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            schema,
+                        ) => (
+                            Kind::Unseq,
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            Some(schema),
+                        ),
+                        MapEncoding::SeqV1(
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            schema,
+                        ) => (
+                            Kind::Seq,
+                            name,
+                            tag,
+                            data,
+                            permissions,
+                            version,
+                            owner,
+                            Some(schema),
+                        ),
+                    };
+                Ok($flavour {
+                    address: Address::from_kind(kind, name, tag),
+                    data,
+                    permissions,
+                    version,
+                    owner,
+                    schema,
+                })
+            }
+        }
+
         impl $flavour {
             /// Returns the address.
             pub fn address(&self) -> &Address {
@@ -226,14 +465,32 @@ macro_rules! impl_map {
                 self.data.keys().cloned().collect()
             }
 
+            /// Returns the keys in the data starting with `prefix`, without going
+            /// through the others.
+            pub fn keys_with_prefix(&self, prefix: &[u8]) -> BTreeSet<Vec<u8>> {
+                self.data
+                    .range(prefix.to_vec()..)
+                    .map(|(key, _)| key)
+                    .take_while(|key| key.starts_with(prefix))
+                    .cloned()
+                    .collect()
+            }
+
+            /// Returns a bloom filter of all the keys in the data, which another replica
+            /// can use to cheaply estimate which keys it is missing.
+            pub fn key_filter(&self) -> Bloom {
+                Bloom::from_keys(self.data.keys())
+            }
+
             /// Returns the shell of this Map (the fields without the data).
             pub fn shell(&self) -> Self {
                 Self {
                     address: self.address.clone(),
-                    data: BTreeMap::new(),
+                    data: Default::default(),
                     permissions: self.permissions.clone(),
                     version: self.version,
                     owner: self.owner,
+                    schema: self.schema.clone(),
                 }
             }
 
@@ -375,6 +632,39 @@ macro_rules! impl_map {
                     None => false,
                 }
             }
+
+            /// Returns the schema entries are validated against, if any.
+            pub fn schema(&self) -> Option<&Schema> {
+                self.schema.as_ref()
+            }
+
+            /// Sets or clears the schema entries are validated against.
+            /// Existing entries are not validated against the new schema.
+            ///
+            /// Requires the new `version` of the Map fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn set_schema(&mut self, schema: Option<Schema>, version: u64) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                self.schema = schema;
+                self.version = version;
+
+                Ok(())
+            }
+
+            // Checks the entry is within `MAX_MAP_KEY_SIZE` and `MAX_MAP_VALUE_SIZE`,
+            // and against the schema, if one is set.
+            fn validate_entry(&self, key: &[u8], value: &[u8]) -> Result<()> {
+                if key.len() > MAX_MAP_KEY_SIZE || value.len() > MAX_MAP_VALUE_SIZE {
+                    return Err(Error::ExceededSize);
+                }
+                match &self.schema {
+                    Some(schema) => schema.validate(key, value),
+                    None => Ok(()),
+                }
+            }
         }
     };
 }
@@ -391,6 +681,7 @@ impl UnseqData {
             permissions: Default::default(),
             version: 0,
             owner,
+            schema: None,
         }
     }
 
@@ -404,10 +695,11 @@ impl UnseqData {
     ) -> Self {
         Self {
             address: Address::Unseq { name, tag },
-            data,
+            data: Arc::new(data),
             permissions,
             version: 0,
             owner,
+            schema: None,
         }
     }
 
@@ -421,14 +713,29 @@ impl UnseqData {
         self.data.values().cloned().collect()
     }
 
+    /// Iterates over all entries, ordered by key, without cloning them.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+        self.data.iter()
+    }
+
     /// Returns all entries.
     pub fn entries(&self) -> &UnseqEntries {
         &self.data
     }
 
+    /// Returns at most `limit` entries, ordered by key, starting after the key `after`,
+    /// or from the first one if `None`.
+    pub fn entries_page(&self, after: Option<&[u8]>, limit: usize) -> UnseqEntries {
+        self.data
+            .range::<Vec<u8>, _>(page_start(after))
+            .take(limit)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
     /// Removes and returns all entries.
     pub fn take_entries(&mut self) -> UnseqEntries {
-        mem::replace(&mut self.data, BTreeMap::new())
+        Arc::try_unwrap(mem::take(&mut self.data)).unwrap_or_else(|data| (*data).clone())
     }
 
     /// Mutates entries based on `actions` for the provided user.
@@ -469,10 +776,14 @@ impl UnseqData {
             return Err(Error::AccessDenied(*requester));
         }
 
-        let mut new_data = self.data.clone();
+        let mut new_data = (*self.data).clone();
         let mut errors = BTreeMap::new();
 
         for (key, val) in insert {
+            if let Err(error) = self.validate_entry(&key, &val) {
+                let _ = errors.insert(key, error);
+                continue;
+            }
             match new_data.entry(key) {
                 Entry::Occupied(entry) => {
                     let _ = errors.insert(entry.key().clone(), Error::EntryExists(0));
@@ -484,6 +795,10 @@ impl UnseqData {
         }
 
         for (key, val) in update {
+            if let Err(error) = self.validate_entry(&key, &val) {
+                let _ = errors.insert(key, error);
+                continue;
+            }
             match new_data.entry(key) {
                 Entry::Occupied(mut entry) => {
                     let _ = entry.insert(val);
@@ -509,7 +824,7 @@ impl UnseqData {
             return Err(Error::InvalidEntryActions(errors));
         }
 
-        let _old_data = mem::replace(&mut self.data, new_data);
+        let _old_data = mem::replace(&mut self.data, Arc::new(new_data));
 
         Ok(())
     }
@@ -525,6 +840,7 @@ impl SeqData {
             permissions: Default::default(),
             version: 0,
             owner,
+            schema: None,
         }
     }
 
@@ -538,10 +854,11 @@ impl SeqData {
     ) -> Self {
         Self {
             address: Address::Seq { name, tag },
-            data,
+            data: Arc::new(data),
             permissions,
             version: 0,
             owner,
+            schema: None,
         }
     }
 
@@ -555,14 +872,29 @@ impl SeqData {
         self.data.values().cloned().collect()
     }
 
+    /// Iterates over all entries, ordered by key, without cloning them.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &SeqValue)> {
+        self.data.iter()
+    }
+
     /// Returns all entries
     pub fn entries(&self) -> &SeqEntries {
         &self.data
     }
 
+    /// Returns at most `limit` entries, ordered by key, starting after the key `after`,
+    /// or from the first one if `None`.
+    pub fn entries_page(&self, after: Option<&[u8]>, limit: usize) -> SeqEntries {
+        self.data
+            .range::<Vec<u8>, _>(page_start(after))
+            .take(limit)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
     /// Removes and returns all entries
     pub fn take_entries(&mut self) -> SeqEntries {
-        mem::replace(&mut self.data, BTreeMap::new())
+        Arc::try_unwrap(mem::take(&mut self.data)).unwrap_or_else(|data| (*data).clone())
     }
 
     /// Mutates entries (key + value pairs) in bulk.
@@ -600,10 +932,14 @@ impl SeqData {
             return Err(Error::AccessDenied(*requester));
         }
 
-        let mut new_data = self.data.clone();
+        let mut new_data = (*self.data).clone();
         let mut errors = BTreeMap::new();
 
         for (key, val) in insert {
+            if let Err(error) = self.validate_entry(&key, &val.data) {
+                let _ = errors.insert(key, error);
+                continue;
+            }
             match new_data.entry(key) {
                 Entry::Occupied(entry) => {
                     let _ = errors.insert(
@@ -618,6 +954,10 @@ impl SeqData {
         }
 
         for (key, val) in update {
+            if let Err(error) = self.validate_entry(&key, &val.data) {
+                let _ = errors.insert(key, error);
+                continue;
+            }
             match new_data.entry(key) {
                 Entry::Occupied(mut entry) => {
                     let current_version = entry.get().version;
@@ -659,7 +999,7 @@ impl SeqData {
             return Err(Error::InvalidEntryActions(errors));
         }
 
-        let _old_data = mem::replace(&mut self.data, new_data);
+        let _old_data = mem::replace(&mut self.data, Arc::new(new_data));
 
         Ok(())
     }
@@ -825,6 +1165,73 @@ impl Data {
         }
     }
 
+    /// Returns the values of all entries, if `requester` is allowed to read them.
+    pub fn values(&self, requester: &PublicKey) -> Result<Values> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(match self {
+            Data::Seq(data) => data.values().into(),
+            Data::Unseq(data) => data.values().into(),
+        })
+    }
+
+    /// Returns all entries, if `requester` is allowed to read them.
+    pub fn entries(&self, requester: &PublicKey) -> Result<Entries> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(match self {
+            Data::Seq(data) => data.entries().clone().into(),
+            Data::Unseq(data) => data.entries().clone().into(),
+        })
+    }
+
+    /// Returns a page of at most `limit` entries, ordered by key, starting after the key
+    /// `after`, or from the first one if `None`, if `requester` is allowed to read them.
+    ///
+    /// Passing the last key of a page as `after` fetches the next one, so large Maps can be
+    /// read in pages. A page with fewer than `limit` entries is the last one.
+    pub fn entries_page(
+        &self,
+        after: Option<&[u8]>,
+        limit: usize,
+        requester: &PublicKey,
+    ) -> Result<Entries> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(match self {
+            Data::Seq(data) => data.entries_page(after, limit).into(),
+            Data::Unseq(data) => data.entries_page(after, limit).into(),
+        })
+    }
+
+    /// Returns the keys in the data starting with `prefix`.
+    pub fn keys_with_prefix(&self, prefix: &[u8]) -> BTreeSet<Vec<u8>> {
+        match self {
+            Data::Seq(data) => data.keys_with_prefix(prefix),
+            Data::Unseq(data) => data.keys_with_prefix(prefix),
+        }
+    }
+
+    /// Iterates over all entries, ordered by key, cloning each value only as it's reached.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&Vec<u8>, Value)> + '_> {
+        match self {
+            Data::Seq(data) => {
+                Box::new(data.iter().map(|(key, value)| (key, value.clone().into())))
+            }
+            Data::Unseq(data) => {
+                Box::new(data.iter().map(|(key, value)| (key, value.clone().into())))
+            }
+        }
+    }
+
+    /// Returns a bloom filter of all the keys in the data.
+    pub fn key_filter(&self) -> Bloom {
+        match self {
+            Data::Seq(data) => data.key_filter(),
+            Data::Unseq(data) => data.key_filter(),
+        }
+    }
+
     /// Returns the shell of the data.
     pub fn shell(&self) -> Self {
         match self {
@@ -870,6 +1277,22 @@ impl Data {
         }
     }
 
+    /// Returns the schema entries are validated against, if any.
+    pub fn schema(&self) -> Option<&Schema> {
+        match self {
+            Data::Seq(data) => data.schema(),
+            Data::Unseq(data) => data.schema(),
+        }
+    }
+
+    /// Sets or clears the schema entries are validated against.
+    pub fn set_schema(&mut self, schema: Option<Schema>, version: u64) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.set_schema(schema, version),
+            Data::Unseq(data) => data.set_schema(schema, version),
+        }
+    }
+
     /// Checks permissions for given `action` for the provided user.
     pub fn check_permissions(&self, action: Action, requester: &PublicKey) -> Result<()> {
         match self {
@@ -894,6 +1317,17 @@ impl Data {
         }
     }
 
+    /// Checks `signature` over `payload` was combined by the group `key`, which has
+    /// to be the owner of the data.
+    pub fn check_threshold_owner<T: AsRef<[u8]>>(
+        &self,
+        key: &ThresholdKey,
+        payload: T,
+        signature: &Signature,
+    ) -> Result<()> {
+        key.verify_owner_sig(&self.owner(), payload, signature)
+    }
+
     /// Mutates entries (key + value pairs) in bulk.
     pub fn mutate_entries(&mut self, actions: EntryActions, requester: &PublicKey) -> Result<()> {
         match self {
@@ -911,6 +1345,12 @@ impl Data {
 
         Err(Error::InvalidOperation)
     }
+
+    /// Returns a read-only snapshot of the current state, for concurrent readers.
+    /// The entries aren't copied, the view sharing them with the Map until it's mutated.
+    pub fn view(&self) -> MapView {
+        self.clone().into()
+    }
 }
 
 impl From<SeqData> for Data {
@@ -925,6 +1365,278 @@ impl From<UnseqData> for Data {
     }
 }
 
+/// Read-only snapshot of a Map, which can be shared with concurrent readers
+/// while the Map it was taken from keeps being mutated.
+///
+/// Cloning a view is cheap, as the snapshot itself is reference counted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MapView {
+    data: Arc<Data>,
+}
+
+impl MapView {
+    /// Returns the address of the Map the view was taken from.
+    pub fn address(&self) -> &Address {
+        self.data.address()
+    }
+
+    /// Returns the version of the Map shell at the time the view was taken.
+    pub fn version(&self) -> u64 {
+        self.data.version()
+    }
+
+    /// Returns the value of `key`, if present.
+    pub fn get(&self, key: &[u8]) -> Option<Value> {
+        match &*self.data {
+            Data::Seq(data) => data.get(key).cloned().map(Value::from),
+            Data::Unseq(data) => data.get(key).cloned().map(Value::from),
+        }
+    }
+
+    /// Returns all the keys.
+    pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+        self.data.keys()
+    }
+
+    /// Returns all the entries.
+    pub fn entries(&self) -> Entries {
+        match &*self.data {
+            Data::Seq(data) => data.entries().clone().into(),
+            Data::Unseq(data) => data.entries().clone().into(),
+        }
+    }
+}
+
+impl From<Data> for MapView {
+    fn from(data: Data) -> Self {
+        Self {
+            data: Arc::new(data),
+        }
+    }
+}
+
+/// Bounded read-through cache of the entries of a Map, for clients which can't hold
+/// the whole Map in memory. Once full, the least recently read key is evicted.
+///
+/// Feeding it the entry actions applied to the Map keeps the cached values fresh.
+#[derive(Clone, Debug)]
+pub struct MapCache {
+    address: Address,
+    capacity: usize,
+    // Cached values, along with the tick they were last read at.
+    entries: BTreeMap<Vec<u8>, (Value, u64)>,
+    // Cached keys by the tick they were last read at, the least recently read first.
+    reads: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+}
+
+impl MapCache {
+    /// Constructs an empty cache of the Map at `address`, holding up to `capacity` keys.
+    pub fn new(address: Address, capacity: usize) -> Self {
+        Self {
+            address,
+            capacity,
+            entries: BTreeMap::new(),
+            reads: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the address of the cached Map.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the maximum number of keys cached.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of keys cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no key is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of `key`, loading it with `load` and caching it if it's not
+    /// cached yet. Keys without a value are not cached.
+    pub fn get(&mut self, key: &[u8], load: impl FnOnce(&[u8]) -> Option<Value>) -> Option<Value> {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((value, last_read)) = self.entries.get_mut(key) {
+            let _ = self.reads.remove(last_read);
+            *last_read = tick;
+            let _ = self.reads.insert(tick, key.to_vec());
+            return Some(value.clone());
+        }
+
+        let value = load(key)?;
+        if self.capacity == 0 {
+            return Some(value);
+        }
+        if self.entries.len() >= self.capacity {
+            self.evict();
+        }
+        let _ = self.entries.insert(key.to_vec(), (value.clone(), tick));
+        let _ = self.reads.insert(tick, key.to_vec());
+        Some(value)
+    }
+
+    /// Applies to the cached keys the entry actions applied to the Map. Keys not
+    /// cached are left to be loaded when read, and sequenced values are only
+    /// replaced by more recent versions.
+    pub fn apply_op(&mut self, actions: &EntryActions) {
+        match actions {
+            EntryActions::Seq(actions) => {
+                for (key, action) in actions.actions() {
+                    match action {
+                        SeqEntryAction::Ins(value) | SeqEntryAction::Update(value) => {
+                            self.update(key, |cached| match cached {
+                                Value::Seq(cached) if cached.version >= value.version => None,
+                                _ => Some(Value::Seq(value.clone())),
+                            })
+                        }
+                        SeqEntryAction::Del(_) => self.remove(key),
+                    }
+                }
+            }
+            EntryActions::Unseq(actions) => {
+                for (key, action) in actions.actions() {
+                    match action {
+                        UnseqEntryAction::Ins(value) | UnseqEntryAction::Update(value) => {
+                            self.update(key, |_| Some(Value::Unseq(value.clone())))
+                        }
+                        UnseqEntryAction::Del => self.remove(key),
+                    }
+                }
+            }
+        }
+    }
+
+    // Replaces the cached value of `key`, if any, with the one `new_value` returns for it.
+    fn update(&mut self, key: &[u8], new_value: impl FnOnce(&Value) -> Option<Value>) {
+        if let Some((cached, _)) = self.entries.get_mut(key) {
+            if let Some(value) = new_value(cached) {
+                *cached = value;
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if let Some((_, last_read)) = self.entries.remove(key) {
+            let _ = self.reads.remove(&last_read);
+        }
+    }
+
+    // Evicts the least recently read key.
+    fn evict(&mut self) {
+        let oldest = self.reads.keys().next().copied();
+        if let Some(key) = oldest.and_then(|tick| self.reads.remove(&tick)) {
+            let _ = self.entries.remove(&key);
+        }
+    }
+}
+
+/// Serialisable bloom filter over the keys of a Map, used by replicas to negotiate
+/// which entries need to be exchanged before running a full sync.
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Debug)]
+pub struct Bloom {
+    bits: Vec<u8>,
+    num_hashes: u32,
+}
+
+// Fields of a `Bloom`, checked before it's deserialised.
+#[derive(Deserialize)]
+struct BloomFields {
+    bits: Vec<u8>,
+    num_hashes: u32,
+}
+
+impl<'de> Deserialize<'de> for Bloom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let BloomFields { bits, num_hashes } = Deserialize::deserialize(deserializer)?;
+        // Keys are hashed modulo the number of bits, and each sets `num_hashes` of them.
+        if bits.is_empty() {
+            return Err(de::Error::custom("bloom filter without bits"));
+        }
+        if num_hashes == 0 || num_hashes > BLOOM_MAX_HASHES {
+            return Err(de::Error::custom(format!(
+                "bloom filter with {} hashes per key",
+                num_hashes
+            )));
+        }
+        Ok(Self { bits, num_hashes })
+    }
+}
+
+impl Bloom {
+    /// Creates a filter sized for, and containing, the provided keys.
+    pub fn from_keys<'a, I>(keys: I) -> Self
+    where
+        I: ExactSizeIterator<Item = &'a Vec<u8>>,
+    {
+        let num_bits = std::cmp::max(keys.len() * BLOOM_BITS_PER_KEY, BLOOM_MIN_BITS);
+        let mut bloom = Self {
+            bits: vec![0; num_bits.div_ceil(8)],
+            num_hashes: BLOOM_NUM_HASHES,
+        };
+        for key in keys {
+            bloom.insert(key);
+        }
+        bloom
+    }
+
+    /// Adds a key to the filter.
+    pub fn insert(&mut self, key: &[u8]) {
+        for bit in self.bit_indices(key) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Returns `false` if the key is definitely not in the filter, and `true` if it might be.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.bit_indices(key)
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Returns an estimate of the number of keys inserted in the filter,
+    /// derived from the proportion of bits set.
+    pub fn estimated_len(&self) -> u64 {
+        let num_bits = self.num_bits() as f64;
+        let bits_set = self.bits.iter().map(|byte| byte.count_ones()).sum::<u32>() as f64;
+        if bits_set >= num_bits {
+            return u64::MAX;
+        }
+        let estimate = -(num_bits / self.num_hashes as f64) * (1.0 - bits_set / num_bits).ln();
+        estimate.round() as u64
+    }
+
+    fn num_bits(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    // Derives the bit positions for a key by double hashing the halves of its SHA3 digest.
+    fn bit_indices(&self, key: &[u8]) -> impl Iterator<Item = usize> {
+        let mut hasher = Sha3::v256();
+        let mut digest = [0; 32];
+        hasher.update(key);
+        hasher.finalize(&mut digest);
+        let mut first = [0; 8];
+        let mut second = [0; 8];
+        first.copy_from_slice(&digest[..8]);
+        second.copy_from_slice(&digest[8..16]);
+        let first = u64::from_le_bytes(first);
+        let second = u64::from_le_bytes(second);
+        let num_bits = self.num_bits() as u64;
+        (0..u64::from(self.num_hashes))
+            .map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % num_bits) as usize)
+    }
+}
+
 /// Action for a sequenced Entry.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
 pub enum SeqEntryAction {
@@ -1138,6 +1850,15 @@ impl From<UnseqEntryActions> for EntryActions {
     }
 }
 
+// Range of the keys of a page starting after `after`, or from the first key if `None`.
+fn page_start(after: Option<&[u8]>) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    let start = match after {
+        Some(key) => Bound::Excluded(key.to_vec()),
+        None => Bound::Unbounded,
+    };
+    (start, Bound::Unbounded)
+}
+
 /// Sequenced entries (key-value pairs, with versioned values).
 pub type SeqEntries = BTreeMap<Vec<u8>, SeqValue>;
 /// Unsequenced entries (key-value pairs, without versioned values).
@@ -1166,8 +1887,16 @@ impl From<UnseqEntries> for Entries {
 
 #[cfg(test)]
 mod tests {
-    use super::{Address, XorName};
-    use crate::Result;
+    use super::{
+        Action, Address, Bloom, Data, Entries, MapCache, PermissionSet, Schema, SeqData,
+        SeqEntries, SeqEntryActions, SeqValue, UnseqData, UnseqEntries, UnseqEntryActions, Value,
+        Values, XorName, MAX_MAP_KEY_SIZE, MAX_MAP_VALUE_SIZE,
+    };
+    use crate::{utils, Error, Keypair, PublicKey, Result, Signature, ThresholdKey};
+    use rand::rngs::OsRng;
+    use serde::Serialize;
+    use std::{collections::BTreeMap, sync::Arc, thread};
+    use threshold_crypto::SecretKeySet;
 
     #[test]
     fn zbase32_encode_decode_map_address() -> Result<()> {
@@ -1178,4 +1907,419 @@ mod tests {
         assert_eq!(address, decoded);
         Ok(())
     }
+
+    #[test]
+    fn threshold_owner() -> Result<()> {
+        let sk_set = SecretKeySet::random(1, &mut OsRng);
+        let group = ThresholdKey::new(sk_set.public_keys());
+        let payload = b"request";
+        let shares: Vec<_> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).sign(payload)))
+            .collect();
+        let sig = group
+            .pk_set()
+            .combine_signatures(shares.iter().map(|(i, share)| (*i, share)))
+            .map_err(|_| Error::InvalidSignature)?;
+
+        let map = Data::from(UnseqData::new(XorName::random(), 15000, group.public_key()));
+        map.check_threshold_owner(&group, payload, &Signature::Bls(sig.clone()))?;
+        assert_eq!(
+            map.check_threshold_owner(&group, payload, &Signature::from(shares[0].clone())),
+            Err(Error::InvalidSignature)
+        );
+
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let map = Data::from(UnseqData::new(XorName::random(), 15000, owner));
+        assert_eq!(
+            map.check_threshold_owner(&group, payload, &Signature::Bls(sig)),
+            Err(Error::AccessDenied(group.public_key()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_contains_inserted_keys() {
+        let keys: Vec<Vec<u8>> = (0..500u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let bloom = Bloom::from_keys(keys.iter());
+
+        assert!(keys.iter().all(|key| bloom.contains(key)));
+
+        let false_positives = (500..1500u32)
+            .filter(|i| bloom.contains(&i.to_le_bytes()))
+            .count();
+        assert!(false_positives < 50);
+
+        let estimate = bloom.estimated_len();
+        assert!(estimate > 450 && estimate < 550);
+    }
+
+    #[test]
+    fn bloom_from_peer_is_validated() -> Result<()> {
+        let bloom = Bloom::from_keys([b"key".to_vec()].iter());
+        assert_eq!(
+            utils::deserialise::<Bloom>(&utils::serialise(&bloom)?)?,
+            bloom
+        );
+
+        let no_bits = utils::serialise(&(Vec::<u8>::new(), 7u32))?;
+        assert!(utils::deserialise::<Bloom>(&no_bits).is_err());
+        let no_hashes = utils::serialise(&(vec![0u8; 8], 0u32))?;
+        assert!(utils::deserialise::<Bloom>(&no_hashes).is_err());
+        let too_many_hashes = utils::serialise(&(vec![0u8; 8], u32::MAX))?;
+        assert!(utils::deserialise::<Bloom>(&too_many_hashes).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_entries_are_rejected() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = SeqData::new(XorName::random(), 15000, owner);
+
+        let actions = SeqEntryActions::new()
+            .ins(vec![0; MAX_MAP_KEY_SIZE], vec![0; MAX_MAP_VALUE_SIZE], 0)
+            .ins(vec![1; MAX_MAP_KEY_SIZE + 1], vec![], 0)
+            .ins(vec![2], vec![0; MAX_MAP_VALUE_SIZE + 1], 0);
+        match map.mutate_entries(actions, &owner) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.len(), 2);
+                assert!(errors
+                    .values()
+                    .all(|error| matches!(error, Error::ExceededSize)));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(map.entries().is_empty());
+
+        let key = vec![0; MAX_MAP_KEY_SIZE];
+        let actions = SeqEntryActions::new().ins(key.clone(), vec![0; MAX_MAP_VALUE_SIZE], 0);
+        map.mutate_entries(actions, &owner)?;
+        let actions = SeqEntryActions::new().update(key, vec![0; MAX_MAP_VALUE_SIZE + 1], 1);
+        assert!(matches!(
+            map.mutate_entries(actions, &owner),
+            Err(Error::InvalidEntryActions(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema_violations_are_rejected() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = UnseqData::new(XorName::random(), 15000, owner);
+
+        let schema = Schema {
+            max_key_len: Some(8),
+            content_type: Some(1),
+            key_prefixes: vec![b"app/".to_vec()].into_iter().collect(),
+        };
+        map.set_schema(Some(schema), 1)?;
+        assert_eq!(map.version(), 1);
+
+        let actions = UnseqEntryActions::new()
+            .ins(b"app/ok".to_vec(), vec![1, 42])
+            .ins(b"app/too-long".to_vec(), vec![1, 42])
+            .ins(b"app/tag".to_vec(), vec![2, 42])
+            .ins(b"other".to_vec(), vec![1, 42]);
+        match map.mutate_entries(actions, &owner) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.len(), 3);
+                assert!(errors
+                    .values()
+                    .all(|error| matches!(error, Error::SchemaViolation(_))));
+                assert!(!errors.contains_key(&b"app/ok"[..]));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(map.entries().is_empty());
+
+        let actions = UnseqEntryActions::new().ins(b"app/ok".to_vec(), vec![1, 42]);
+        map.mutate_entries(actions, &owner)?;
+        assert_eq!(map.get(b"app/ok"), Some(&vec![1, 42]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn maps_encoded_before_schemas_still_decode() -> Result<()> {
+        // Layout of a sequenced Map before schemas were added.
+        #[derive(Serialize)]
+        struct BaselineSeqData {
+            address: Address,
+            data: SeqEntries,
+            permissions: BTreeMap<PublicKey, PermissionSet>,
+            version: u64,
+            owner: PublicKey,
+        }
+
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut data = SeqEntries::new();
+        let _ = data.insert(
+            b"key".to_vec(),
+            SeqValue {
+                data: b"value".to_vec(),
+                version: 0,
+            },
+        );
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(owner, PermissionSet::new().allow(Action::Read));
+        let baseline = BaselineSeqData {
+            address: Address::Seq {
+                name: XorName::random(),
+                tag: 15000,
+            },
+            data,
+            permissions,
+            version: 2,
+            owner,
+        };
+        let encoded = utils::serialise(&baseline)?;
+
+        let mut map: SeqData = utils::deserialise(&encoded)?;
+        assert_eq!(map.address(), &baseline.address);
+        assert_eq!(map.entries(), &baseline.data);
+        assert_eq!(map.permissions(), baseline.permissions);
+        assert_eq!(map.version(), 2);
+        assert_eq!(map.owner(), &owner);
+        assert!(map.schema().is_none());
+        assert_eq!(utils::serialise(&map)?, encoded);
+
+        let schema = Schema {
+            max_key_len: Some(8),
+            ..Default::default()
+        };
+        map.set_schema(Some(schema.clone()), 3)?;
+        let encoded = utils::serialise(&map)?;
+        assert_eq!(encoded[..4], 3u32.to_le_bytes());
+        let decoded: SeqData = utils::deserialise(&encoded)?;
+        assert_eq!(decoded, map);
+        assert_eq!(decoded.version(), 3);
+        assert_eq!(decoded.schema(), Some(&schema));
+
+        Ok(())
+    }
+
+    #[test]
+    fn maps_decode_earliest_layout() -> Result<()> {
+        let key = |secret| -> Result<PublicKey> {
+            let secret = ed25519_dalek::SecretKey::from_bytes(&[secret; 32])
+                .map_err(|err| Error::Serialisation(err.to_string()))?;
+            Ok(PublicKey::Ed25519((&secret).into()))
+        };
+        let (owner, user) = (key(1)?, key(2)?);
+        let from_hex = |encoded: &str| {
+            hex::decode(encoded).map_err(|err| Error::Serialisation(err.to_string()))
+        };
+
+        // Maps as encoded before schemas were added
+        let encoded = from_hex(SEQ_MAP_V0)?;
+        let map: SeqData = utils::deserialise(&encoded)?;
+        let mut entries = SeqEntries::new();
+        let _ = entries.insert(
+            b"key".to_vec(),
+            SeqValue {
+                data: b"value".to_vec(),
+                version: 0,
+            },
+        );
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(user, PermissionSet::new().allow(Action::Read));
+        assert_eq!(
+            map,
+            SeqData::new_with_data(XorName([7; 32]), 17000, entries, permissions, owner)
+        );
+        assert_eq!(utils::serialise(&map)?, encoded);
+
+        let encoded = from_hex(UNSEQ_MAP_V0)?;
+        let map: UnseqData = utils::deserialise(&encoded)?;
+        let mut entries = UnseqEntries::new();
+        let _ = entries.insert(b"key".to_vec(), b"value".to_vec());
+        assert_eq!(
+            map,
+            UnseqData::new_with_data(XorName([8; 32]), 17001, entries, BTreeMap::new(), owner)
+        );
+        assert_eq!(utils::serialise(&map)?, encoded);
+        Ok(())
+    }
+
+    const SEQ_MAP_V0: &str = "\
+        01000000070707070707070707070707070707070707070707070707070707070707070768420000\
+        00000000010000000000000003000000000000006b6579050000000000000076616c756500000000\
+        0000000001000000000000000000000020000000000000008139770ea87d175f56a35466c34c7ecc\
+        cb8d8a91b4ee37a25df60f5b8fc9b394010000000000000000000000000000000000000000000000\
+        20000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c";
+
+    const UNSEQ_MAP_V0: &str = "\
+        00000000080808080808080808080808080808080808080808080808080808080808080869420000\
+        00000000010000000000000003000000000000006b6579050000000000000076616c756500000000\
+        0000000000000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72\
+        ca6709bf1d94121bf3748801b40f6f5c";
+
+    #[test]
+    fn map_values_and_entries_need_read_permission() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let reader = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = Data::from(SeqData::new(XorName::random(), 15000, owner));
+        let actions = SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0);
+        map.mutate_entries(actions.into(), &owner)?;
+
+        let value = SeqValue {
+            data: b"value".to_vec(),
+            version: 0,
+        };
+        assert_eq!(map.values(&owner)?, Values::Seq(vec![value.clone()]));
+        match map.entries(&owner)? {
+            Entries::Seq(entries) => assert_eq!(entries.get(&b"key"[..]), Some(&value)),
+            entries => panic!("Unexpected entries: {:?}", entries),
+        }
+
+        assert_eq!(map.values(&reader), Err(Error::AccessDenied(reader)));
+        map.set_user_permissions(reader, PermissionSet::new().allow(Action::Read), 1)?;
+        assert!(map.entries(&reader).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_keys_with_prefix_and_iter() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = Data::from(UnseqData::new(XorName::random(), 15000, owner));
+        let actions = UnseqEntryActions::new()
+            .ins(b"app/a".to_vec(), b"1".to_vec())
+            .ins(b"app/b".to_vec(), b"2".to_vec())
+            .ins(b"apq".to_vec(), b"3".to_vec())
+            .ins(b"ap".to_vec(), b"4".to_vec());
+        map.mutate_entries(actions.into(), &owner)?;
+
+        let keys: Vec<_> = map.keys_with_prefix(b"app/").into_iter().collect();
+        assert_eq!(keys, vec![b"app/a".to_vec(), b"app/b".to_vec()]);
+        assert_eq!(map.keys_with_prefix(b"ap").len(), 4);
+        assert!(map.keys_with_prefix(b"other").is_empty());
+
+        let entries: Vec<_> = map.iter().collect();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], (&b"ap".to_vec(), Value::Unseq(b"4".to_vec())));
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_entries_in_pages() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let stranger = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = Data::from(UnseqData::new(XorName::random(), 15000, owner));
+        let mut actions = UnseqEntryActions::new();
+        for i in 0..5u8 {
+            actions = actions.ins(vec![i], vec![i]);
+        }
+        map.mutate_entries(actions.into(), &owner)?;
+
+        let mut keys = vec![];
+        let mut after = None;
+        loop {
+            let page = match map.entries_page(after.as_deref(), 2, &owner)? {
+                Entries::Unseq(page) => page,
+                Entries::Seq(_) => panic!("unexpected sequenced entries"),
+            };
+            keys.extend(page.keys().cloned());
+            if page.len() < 2 {
+                break;
+            }
+            after = page.keys().next_back().cloned();
+        }
+        assert_eq!(keys, (0..5u8).map(|i| vec![i]).collect::<Vec<_>>());
+
+        assert!(matches!(
+            map.entries_page(None, 2, &stranger),
+            Err(Error::AccessDenied(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_view_is_unaffected_by_later_mutations() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut map = Data::from(UnseqData::new(XorName::random(), 15000, owner));
+        let actions = UnseqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec());
+        map.mutate_entries(actions.into(), &owner)?;
+
+        // The view shares the entries with the Map rather than copying them
+        let view = map.view();
+        assert!(matches!(
+            (&map, &*view.data),
+            (Data::Unseq(data), Data::Unseq(viewed)) if Arc::ptr_eq(&data.data, &viewed.data)
+        ));
+
+        let actions = UnseqEntryActions::new()
+            .update(b"key".to_vec(), b"new value".to_vec())
+            .ins(b"other".to_vec(), b"value".to_vec());
+        map.mutate_entries(actions.into(), &owner)?;
+        assert_eq!(map.keys().len(), 2);
+
+        let reader = {
+            let view = view.clone();
+            thread::spawn(move || (view.get(b"key"), view.keys().len()))
+        };
+        let (value, key_count) = reader.join().expect("reader thread panicked");
+        assert_eq!(value, Some(Value::Unseq(b"value".to_vec())));
+        assert_eq!(key_count, 1);
+        assert_eq!(view.get(b"other"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_cache_evicts_least_recently_read() {
+        let address = Address::Unseq {
+            name: XorName::random(),
+            tag: 15000,
+        };
+        let mut cache = MapCache::new(address, 2);
+        let load = |key: &[u8]| Some(Value::Unseq(key.to_vec()));
+
+        assert_eq!(cache.get(b"a", load), Some(Value::Unseq(b"a".to_vec())));
+        let _ = cache.get(b"b", load);
+        let _ = cache.get(b"a", |_| None);
+        let _ = cache.get(b"c", load);
+        assert_eq!(cache.len(), 2);
+        // "b" was read least recently, so it has been evicted
+        assert_eq!(cache.get(b"b", |_| None), None);
+        assert!(cache.get(b"a", |_| None).is_some());
+
+        let actions = UnseqEntryActions::new()
+            .update(b"a".to_vec(), b"new".to_vec())
+            .del(b"c".to_vec())
+            .ins(b"d".to_vec(), b"d".to_vec());
+        cache.apply_op(&actions.into());
+        assert_eq!(
+            cache.get(b"a", |_| None),
+            Some(Value::Unseq(b"new".to_vec()))
+        );
+        assert_eq!(cache.get(b"c", |_| None), None);
+        assert_eq!(cache.get(b"d", |_| None), None);
+    }
+
+    #[test]
+    fn map_cache_keeps_latest_seq_version() {
+        let address = Address::Seq {
+            name: XorName::random(),
+            tag: 15000,
+        };
+        let mut cache = MapCache::new(address, 1);
+        let value = |data: &[u8], version| SeqValue {
+            data: data.to_vec(),
+            version,
+        };
+        let _ = cache.get(b"key", |_| Some(Value::Seq(value(b"v1", 1))));
+
+        let stale = SeqEntryActions::new().update(b"key".to_vec(), b"v0".to_vec(), 0);
+        cache.apply_op(&stale.into());
+        let newer = SeqEntryActions::new().update(b"key".to_vec(), b"v2".to_vec(), 2);
+        cache.apply_op(&newer.into());
+        assert_eq!(
+            cache.get(b"key", |_| None),
+            Some(Value::Seq(value(b"v2", 2)))
+        );
+    }
 }
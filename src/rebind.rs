@@ -0,0 +1,111 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, DataAddress, Error, Keypair, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+
+/// A forwarding pointer, signed by the owner of some data, stating that
+/// the data once stored at `old_address` now lives at `new_address`
+/// (e.g. after being re-uploaded with a different tag).
+///
+/// Only mutable data (Map, Sequence and Register) can be rebound,
+/// as the address of a Chunk is derived from its content.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct Rebind {
+    /// Address the data was previously stored at.
+    pub old_address: DataAddress,
+    /// Address the data is now stored at.
+    pub new_address: DataAddress,
+    /// Owner of the data at both addresses.
+    pub owner: PublicKey,
+    /// Signature of the owner over the old and new addresses.
+    pub owner_sig: Signature,
+}
+
+impl Rebind {
+    /// Creates a rebind record from `old_address` to `new_address`, signed by the owner.
+    pub fn new(
+        old_address: DataAddress,
+        new_address: DataAddress,
+        owner_keypair: &Keypair,
+    ) -> Result<Self> {
+        let bytes = Self::bytes_to_sign(&old_address, &new_address)?;
+        Ok(Self {
+            old_address,
+            new_address,
+            owner: owner_keypair.public_key(),
+            owner_sig: owner_keypair.sign(&bytes),
+        })
+    }
+
+    /// Returns the bytes the owner signs to rebind `old_address` to `new_address`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if either address is not of mutable data,
+    /// or both addresses are the same.
+    pub fn bytes_to_sign(old_address: &DataAddress, new_address: &DataAddress) -> Result<Vec<u8>> {
+        let is_mutable = |address: &DataAddress| !matches!(address, DataAddress::Chunk(_));
+        if !is_mutable(old_address) || !is_mutable(new_address) || old_address == new_address {
+            return Err(Error::InvalidOperation);
+        }
+        utils::serialise(&(old_address, new_address))
+    }
+
+    /// Verifies the record is validly signed by its owner.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = Self::bytes_to_sign(&self.old_address, &self.new_address)?;
+        self.owner.verify(&self.owner_sig, &bytes)
+    }
+
+    /// Verifies the record is validly signed by `owner`, i.e. the known owner of the
+    /// data at the old address, so the forwarding pointer can be trusted and followed.
+    pub fn verify_owner(&self, owner: &PublicKey) -> Result<()> {
+        if &self.owner != owner {
+            return Err(Error::AccessDenied(self.owner));
+        }
+        self.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rebind;
+    use crate::{DataAddress, Error, Keypair, MapAddress, Result, SequenceAddress};
+    use rand::rngs::OsRng;
+    use xor_name::XorName;
+
+    #[test]
+    fn rebind_verifies_owner_signature() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let other = Keypair::new_ed25519(&mut OsRng).public_key();
+        let name = XorName::random();
+        let old_address = DataAddress::Sequence(SequenceAddress::Public { name, tag: 1 });
+        let new_address = DataAddress::Sequence(SequenceAddress::Public { name, tag: 2 });
+
+        let rebind = Rebind::new(old_address.clone(), new_address, &owner)?;
+        rebind.verify()?;
+        rebind.verify_owner(&owner.public_key())?;
+        assert_eq!(
+            rebind.verify_owner(&other),
+            Err(Error::AccessDenied(rebind.owner))
+        );
+
+        // Redirecting the pointer invalidates the signature
+        let mut tampered = rebind;
+        tampered.new_address = DataAddress::Map(MapAddress::Seq { name, tag: 2 });
+        assert_eq!(tampered.verify(), Err(Error::InvalidSignature));
+
+        // Rebinding to the same address is rejected
+        assert_eq!(
+            Rebind::new(old_address.clone(), old_address, &owner),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+}
@@ -7,9 +7,10 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+use super::EntryHash;
 use crate::{utils, Result, XorName};
 use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, hash::Hash};
+use std::{collections::BTreeMap, fmt::Debug, hash::Hash};
 
 /// An action on Register data type.
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -23,6 +24,30 @@ pub enum Action {
 /// An entry in a Register.
 pub type Entry = Vec<u8>;
 
+/// How to pick a single entry out of the concurrent current entries of a Register.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub enum MergeStrategy {
+    /// The entry with the latest timestamp its author signed, entries without
+    /// one being the oldest. Ties go to the lowest hash.
+    LastWriterWins,
+    /// The entry with the lowest hash.
+    MinHash,
+    /// An entry written by the owner or a co-owner if any, else any entry,
+    /// the lowest hash winning among them.
+    OwnerPreferred,
+}
+
+/// Shape of the DAG of entries in a Register, to monitor the growth of forks.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct BranchInfo {
+    /// Number of leaves, i.e. of concurrent current entries.
+    pub leaves: u64,
+    /// Number of entries in the longest chain from a leaf back to a root.
+    pub depth: u64,
+    /// Number of entries in the longest chain from each leaf back to a root.
+    pub lineages: BTreeMap<EntryHash, u64>,
+}
+
 /// Address of a Register.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Address {
@@ -11,24 +11,45 @@ mod metadata;
 mod policy;
 mod reg_crdt;
 
-use crate::{Error, PublicKey, Result};
-pub use metadata::{Action, Address, Entry, Kind};
+use crate::{utils, Error, Keypair, PublicKey, Result, Signature, ThresholdKey};
+use crdts::merkle_reg::Node;
+pub use metadata::{Action, Address, BranchInfo, Entry, Kind, MergeStrategy};
 pub use policy::{
-    Permissions, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
+    Permissions, Policy, PolicyId, PolicyOp, PrivatePermissions, PrivatePolicy, PublicPermissions,
+    PublicPolicy, ReadCap, User,
 };
 pub use reg_crdt::EntryHash;
-use reg_crdt::{CrdtOperation, RegisterCrdt};
-use serde::{Deserialize, Serialize};
+use reg_crdt::{
+    CrdtBatchOperation, CrdtDeleteOperation, CrdtOperation, CrdtSyncOperation, RegisterCrdt,
+    RegisterCrdtV0,
+};
+use serde::{
+    de::DeserializeOwned, ser::SerializeTupleVariant, Deserialize, Deserializer, Serialize,
+    Serializer,
+};
 use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     hash::Hash,
+    marker::PhantomData,
 };
 use xor_name::XorName;
 
+/// Maximum size in bytes of a Register entry.
+pub const MAX_REG_ENTRY_SIZE: usize = 1024;
+
 /// Register mutation operation to apply to Register.
 pub type RegisterOp<T> = CrdtOperation<T>;
 
+/// Register operation adding several entries at once, applied atomically.
+pub type RegisterBatchOp<T> = CrdtBatchOperation<T>;
+
+/// Register operation deleting an entry, only applicable to a private Register.
+pub type RegisterDeleteOp = CrdtDeleteOperation;
+
+/// Register operation adding one or several entries, as handed over by `ops_since`.
+pub type RegisterSyncOp<T> = CrdtSyncOperation<T>;
+
 impl Debug for RegisterCrdt {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(
@@ -44,12 +65,111 @@ impl Debug for RegisterCrdt {
     }
 }
 
+/// Summary of the storage used by a Register, signed by the node holding it,
+/// to be used when auditing storage payments.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct StorageProof {
+    /// Address of the Register.
+    pub address: Address,
+    /// Number of entries held in the Register.
+    pub count: u64,
+    /// Number of bytes taken by the Register's entries and DAG.
+    pub bytes: u64,
+    /// The PublicKey of the holder node.
+    pub holder: PublicKey,
+    /// The holder's signature over the address, count and bytes.
+    pub signature: Signature,
+}
+
+impl StorageProof {
+    /// Verifies the holder's signature over the summary, rejecting malleable ones.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = utils::serialise(&(self.address, self.count, self.bytes))?;
+        self.signature.verify_strict(&self.holder, &bytes)
+    }
+}
+
 /// Object storing the Register
-#[derive(Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+///
+/// Encoded as a `RegisterEncoding`, so Registers encoded with an earlier layout can
+/// still be decoded.
+#[derive(Clone, Eq, PartialEq, PartialOrd, Hash, Debug)]
 pub struct Register {
     authority: PublicKey,
     crdt: RegisterCrdt,
+    /// The current policy, i.e. the one of the latest version, or of those set
+    /// concurrently for it, the one with the lowest hash.
     policy: Policy,
+    policy_id: PolicyId,
+    /// Every policy held, current or not, for ops authorised under past policies
+    /// or under those replaced concurrently to be checked against them.
+    policies: BTreeMap<PolicyId, Policy>,
+}
+
+/// Layouts a `Register` has been encoded with, the earliest first. Variants are only
+/// ever appended, for those encoded before to keep their tag.
+///
+/// Registers encoded before their layout was versioned start with their authority,
+/// whose key kind is encoded as the tag of the `PublicKey`: the first variants
+/// mirror those of `PublicKey`, for such a Register to be decoded as one of them.
+#[derive(Deserialize)]
+enum RegisterEncoding {
+    /// Register of an Ed25519 authority, from before policies were versioned.
+    Ed25519V0(ed25519_dalek::PublicKey, RegisterCrdtV0, Policy),
+    /// Register of a BLS authority, from before policies were versioned.
+    BlsV0(threshold_crypto::PublicKey, RegisterCrdtV0, Policy),
+    /// Register of a BLS share authority, from before policies were versioned.
+    BlsShareV0(threshold_crypto::PublicKeyShare, RegisterCrdtV0, Policy),
+    /// Register with its authority, CRDT, current policy and its id, and every policy.
+    V1(
+        PublicKey,
+        RegisterCrdt,
+        Policy,
+        PolicyId,
+        BTreeMap<PolicyId, Policy>,
+    ),
+}
+
+impl Serialize for Register {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut variant = serializer.serialize_tuple_variant("RegisterEncoding", 3, "V1", 5)?;
+        variant.serialize_field(&self.authority)?;
+        variant.serialize_field(&self.crdt)?;
+        variant.serialize_field(&self.policy)?;
+        variant.serialize_field(&self.policy_id)?;
+        variant.serialize_field(&self.policies)?;
+        variant.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Register {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let (authority, crdt, policy) = match RegisterEncoding::deserialize(deserializer)? {
+            RegisterEncoding::Ed25519V0(key, crdt, policy) => {
+                (PublicKey::Ed25519(key), crdt, policy)
+            }
+            RegisterEncoding::BlsV0(key, crdt, policy) => (PublicKey::Bls(key), crdt, policy),
+            RegisterEncoding::BlsShareV0(key, crdt, policy) => {
+                (PublicKey::BlsShare(key), crdt, policy)
+            }
+            RegisterEncoding::V1(authority, crdt, policy, policy_id, policies) => {
+                return Ok(Self {
+                    authority,
+                    crdt,
+                    policy,
+                    policy_id,
+                    policies,
+                })
+            }
+        };
+
+        // Registers of the earliest layout only ever had their initial policy, and their
+        // entries are recorded as written by their authority.
+        let crdt = RegisterCrdt::from_v0(crdt, authority);
+        let mut register = Self::new(authority, *crdt.address(), policy);
+        register.crdt = crdt;
+        Ok(register)
+    }
 }
 
 impl Register {
@@ -67,13 +187,11 @@ impl Register {
         let policy = policy.unwrap_or(PublicPolicy {
             owner: authority,
             permissions: BTreeMap::new(),
+            max_entries: None,
+            co_owners: BTreeSet::new(),
         });
 
-        Self {
-            authority,
-            crdt: RegisterCrdt::new(Address::Public { name, tag }),
-            policy: policy.into(),
-        }
+        Self::new(authority, Address::Public { name, tag }, policy.into())
     }
 
     /// Construct a new Private Register.
@@ -90,12 +208,24 @@ impl Register {
         let policy = policy.unwrap_or(PrivatePolicy {
             owner: authority,
             permissions: BTreeMap::new(),
+            max_entries: None,
+            co_owners: BTreeSet::new(),
         });
 
+        Self::new(authority, Address::Private { name, tag }, policy.into())
+    }
+
+    fn new(authority: PublicKey, address: Address, policy: Policy) -> Self {
+        let policy_id = PolicyId::initial();
+        let mut policies = BTreeMap::new();
+        let _ = policies.insert(policy_id, policy.clone());
+
         Self {
             authority,
-            crdt: RegisterCrdt::new(Address::Private { name, tag }),
-            policy: policy.into(),
+            crdt: RegisterCrdt::new(address),
+            policy,
+            policy_id,
+            policies,
         }
     }
 
@@ -137,6 +267,30 @@ impl Register {
         Ok(self.crdt.size())
     }
 
+    /// Return the number of bytes taken by the entries held in the register,
+    /// including the DAG overhead, optionally verifying read permissions if a pk is provided
+    pub fn size_in_bytes(&self, requester: Option<PublicKey>) -> Result<u64> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.size_in_bytes())
+    }
+
+    /// Return a `StorageProof` of the register's current size, signed by the `holder` node.
+    pub fn storage_proof(&self, holder: &Keypair) -> Result<StorageProof> {
+        let address = *self.address();
+        let count = self.crdt.size();
+        let bytes = self.crdt.size_in_bytes();
+        let signature = holder.sign(&utils::serialise(&(address, count, bytes))?);
+
+        Ok(StorageProof {
+            address,
+            count,
+            bytes,
+            holder: holder.public_key(),
+            signature,
+        })
+    }
+
     /// Return true if the register is empty.
     pub fn is_empty(&self, requester: Option<PublicKey>) -> Result<bool> {
         Ok(self.size(requester)? == 0)
@@ -156,6 +310,85 @@ impl Register {
         Ok(self.crdt.read())
     }
 
+    /// Return a value corresponding to the provided 'hash', if present, to the
+    /// `requester` holding `cap` at time `now`, whether or not the policy lets them read.
+    pub fn get_with_cap(
+        &self,
+        hash: EntryHash,
+        cap: &ReadCap,
+        requester: PublicKey,
+        now: u64,
+    ) -> Result<Option<&Entry>> {
+        self.check_read_cap(cap, requester, now)?;
+
+        Ok(self.crdt.get(hash))
+    }
+
+    /// Read the last entry, or entries when there are branches, like `read`, to the
+    /// `requester` holding `cap` at time `now`, whether or not the policy lets them read.
+    pub fn read_with_cap(
+        &self,
+        cap: &ReadCap,
+        requester: PublicKey,
+        now: u64,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        self.check_read_cap(cap, requester, now)?;
+
+        Ok(self.crdt.read())
+    }
+
+    /// Return the number of ops which added entries to this replica so far,
+    /// i.e. the latest version `read_at` can read.
+    pub fn op_count(&self, requester: Option<PublicKey>) -> Result<u64> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.op_count())
+    }
+
+    /// Return the number of items held after the first `version` ops which added
+    /// entries to this replica were applied.
+    pub fn size_at(&self, version: u64, requester: Option<PublicKey>) -> Result<u64> {
+        self.check_permission(Action::Read, requester)?;
+
+        self.crdt.size_at(version).ok_or(Error::NoSuchEntry)
+    }
+
+    /// Read the last entry, or entries when there were branches, as they were after
+    /// the first `version` ops which added entries to this replica were applied.
+    pub fn read_at(
+        &self,
+        version: u64,
+        requester: Option<PublicKey>,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        self.check_permission(Action::Read, requester)?;
+
+        self.crdt.read_at(version).ok_or(Error::NoSuchEntry)
+    }
+
+    /// Render the DAG of entries in the DOT format, e.g. to debug forks,
+    /// optionally verifying read permissions if a pk is provided
+    pub fn to_dot(&self, requester: Option<PublicKey>) -> Result<String> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.to_dot())
+    }
+
+    /// Summarise the DAG of entries as text, one line per entry,
+    /// optionally verifying read permissions if a pk is provided
+    pub fn summary(&self, requester: Option<PublicKey>) -> Result<String> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.summary())
+    }
+
+    /// Return the number of leaves and the depth of the DAG of entries, along with
+    /// the lineage length of each leaf, e.g. to detect forks growing out of hand.
+    pub fn branch_info(&self, requester: Option<PublicKey>) -> Result<BranchInfo> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.branch_info())
+    }
+
     /// Write an entry to the Register, returning the generated unsigned
     /// CRDT operation so the caller can sign and broadcast it to other replicas,
     /// along with the hash of the entry just written.
@@ -165,17 +398,231 @@ impl Register {
         parents: BTreeSet<EntryHash>,
     ) -> Result<(EntryHash, RegisterOp<Entry>)> {
         self.check_permission(Action::Write, None)?;
+        self.check_entry_size(&entry)?;
+        self.check_room_for(1)?;
+
+        self.crdt
+            .write(entry, parents, self.authority, None, self.policy_id)
+    }
+
+    /// Write several entries to the Register, in order, each on top of its parents,
+    /// returning a single unsigned CRDT operation adding them all for the caller
+    /// to sign and broadcast to other replicas, along with the hashes of the entries.
+    pub fn write_batch(
+        &mut self,
+        entries: Vec<(Entry, BTreeSet<EntryHash>)>,
+    ) -> Result<(Vec<EntryHash>, RegisterBatchOp<Entry>)> {
+        if entries.is_empty() {
+            return Err(Error::InvalidOperation);
+        }
+        self.check_permission(Action::Write, None)?;
+        for (entry, _) in &entries {
+            self.check_entry_size(entry)?;
+        }
+        self.check_room_for(entries.len() as u64)?;
+
+        self.crdt
+            .write_batch(entries, self.authority, self.policy_id)
+    }
+
+    /// Write an entry to the Register like `write`, along with the time it's written
+    /// at, for `MergeStrategy::LastWriterWins` to resolve concurrent entries by.
+    pub fn write_at(
+        &mut self,
+        entry: Entry,
+        parents: BTreeSet<EntryHash>,
+        timestamp: u64,
+    ) -> Result<(EntryHash, RegisterOp<Entry>)> {
+        self.check_permission(Action::Write, None)?;
+        self.check_entry_size(&entry)?;
+        self.check_room_for(1)?;
+
+        self.crdt.write(
+            entry,
+            parents,
+            self.authority,
+            Some(timestamp),
+            self.policy_id,
+        )
+    }
+
+    /// Resolve the current entries into a single one according to `strategy`,
+    /// if the register is not empty.
+    pub fn resolve(
+        &self,
+        strategy: MergeStrategy,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<Entry>> {
+        self.check_permission(Action::Read, requester)?;
 
-        self.crdt.write(entry, parents, self.authority)
+        Ok(self.crdt.resolve(strategy, |key| self.policy.is_owner(key)))
     }
 
     /// Apply a signed data CRDT operation.
+    /// Unless allowed with `allow_missing_parents`, an op referencing parents not held
+    /// yet is rejected with `Error::MissingParents`, listing the entries to fetch and
+    /// apply before retrying it.
+    ///
+    /// The `max_entries` of the policy the op was authorised under bounds the entry's
+    /// causal past rather than the size of this replica, which would depend on the order
+    /// concurrent ops arrive in: an op whose entry would have more entries behind it,
+    /// itself included, is rejected with `Error::TooManyEntries` by every replica alike.
+    /// Under such a bound, an op referencing parents not held yet is always rejected with
+    /// `Error::MissingParents`, as its causal past can't be told.
+    ///
+    /// The op is checked against the policy it was authorised under rather than the
+    /// current one, so an op racing a policy change is accepted or rejected alike by
+    /// every replica. An op authorised under a policy not held yet is rejected with
+    /// `Error::OpNotCausallyReady`, to retry once the policy op is applied.
     pub fn apply_op(&mut self, op: RegisterOp<Entry>) -> Result<()> {
-        self.check_permission(Action::Write, Some(op.source))?;
+        self.check_permission_under(op.policy, Action::Write, op.source)?;
+        self.check_entry_size(&op.crdt_op.value)?;
+        self.check_causal_room(op.policy, std::slice::from_ref(&op.crdt_op))?;
 
         self.crdt.apply_op(op)
     }
 
+    /// Apply a signed CRDT operation adding several entries, either all or none of them.
+    /// Like `apply_op`, each entry's causal past is bound by the policy's `max_entries`.
+    pub fn apply_batch_op(&mut self, op: RegisterBatchOp<Entry>) -> Result<()> {
+        self.check_permission_under(op.policy, Action::Write, op.source)?;
+        for crdt_op in &op.crdt_ops {
+            self.check_entry_size(&crdt_op.value)?;
+        }
+        self.check_causal_room(op.policy, &op.crdt_ops)?;
+
+        self.crdt.apply_batch_op(op)
+    }
+
+    /// Apply a signed op handed over by another replica's `ops_since` or `missing_since`,
+    /// with `apply_op` or `apply_batch_op` depending on its kind.
+    pub fn apply_sync_op(&mut self, op: RegisterSyncOp<Entry>) -> Result<()> {
+        match op {
+            RegisterSyncOp::Single(op) => self.apply_op(op),
+            RegisterSyncOp::Batch(op) => self.apply_batch_op(op),
+        }
+    }
+
+    /// Delete the entry with the provided `hash`, returning the generated unsigned
+    /// CRDT operation so the caller can sign and broadcast it to other replicas.
+    /// The entry is no longer read, but remains in the DAG of entries for those
+    /// written on top of it. Only entries of a private Register can be deleted.
+    pub fn delete(&mut self, hash: EntryHash) -> Result<RegisterDeleteOp> {
+        self.check_can_delete(self.policy_id, self.authority)?;
+
+        self.crdt.delete(hash, self.authority, self.policy_id)
+    }
+
+    /// Apply a signed delete operation, checked against the policy it was authorised
+    /// under like `apply_op`.
+    pub fn apply_delete_op(&mut self, op: RegisterDeleteOp) -> Result<()> {
+        self.check_can_delete(op.policy, op.source)?;
+
+        self.crdt.apply_delete_op(op)
+    }
+
+    /// Return the authors of the entry with the provided `hash`, empty if it isn't held.
+    /// The same entry can be signed by several writers, none of which is told apart
+    /// as its only author.
+    pub fn entry_authors(
+        &self,
+        hash: EntryHash,
+        requester: Option<PublicKey>,
+    ) -> Result<BTreeSet<PublicKey>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.entry_authors(hash))
+    }
+
+    /// Verify the entry with the provided `hash` was signed by each of its authors, on
+    /// its own or in a batch, so its provenance can be checked after the fact and not
+    /// only when it was applied.
+    pub fn verify_entry(&self, hash: EntryHash, requester: Option<PublicKey>) -> Result<()> {
+        self.check_permission(Action::Read, requester)?;
+
+        self.crdt.verify_entry(hash)
+    }
+
+    /// Return the hashes of the entries held, for another replica to send back
+    /// the ops this one is missing with `ops_since`.
+    pub fn digest(&self, requester: Option<PublicKey>) -> Result<BTreeSet<EntryHash>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.digest())
+    }
+
+    /// Return the signed ops of the entries held which are not in the `digest` of
+    /// another replica, in an order that replica can apply them in.
+    pub fn ops_since(
+        &self,
+        digest: &BTreeSet<EntryHash>,
+        requester: Option<PublicKey>,
+    ) -> Result<Vec<RegisterSyncOp<Entry>>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.ops_since(digest))
+    }
+
+    /// Return the signed ops of the entries written after those `known`, i.e. the
+    /// entries held which are neither in `known` nor ancestors of those, in an
+    /// order they can be applied in. Unlike `ops_since`, `known` only needs to
+    /// hold the heads last read rather than every entry held.
+    pub fn missing_since(
+        &self,
+        known: &BTreeSet<EntryHash>,
+        requester: Option<PublicKey>,
+    ) -> Result<Vec<RegisterSyncOp<Entry>>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(self.crdt.missing_since(known))
+    }
+
+    /// Merge the state of `other`, a replica of the same Register with the same
+    /// policy, into this one, so a stale replica can catch up from a single message
+    /// instead of being sent every op it missed.
+    ///
+    /// The signed ops `other` holds are applied in order with `apply_sync_op` and
+    /// `apply_delete_op`, so they're checked like any other op. If any of them is
+    /// rejected, e.g. for an entry whose parents `other` doesn't hold, the error is
+    /// returned and this replica is left as it was. Entries `other` holds without
+    /// a signed op, e.g. written there but not applied back, can't be checked and
+    /// are left out.
+    pub fn merge(&mut self, other: Register) -> Result<()> {
+        if self.address() != other.address() {
+            return Err(Error::CrdtWrongAddress(*other.address()));
+        }
+        if self.policy != other.policy {
+            return Err(Error::InvalidOperation);
+        }
+
+        let mut merged = self.clone();
+        // Entries already held are handed over as well, as other may hold
+        // another signed copy of them.
+        for op in other.crdt.ops_since(&BTreeSet::new()) {
+            merged.apply_sync_op(op)?;
+        }
+        // Entries which couldn't be handed over, as their parents are missing.
+        if other
+            .crdt
+            .signed_hashes()
+            .any(|hash| !merged.crdt.holds(hash))
+        {
+            return Err(Error::OpNotCausallyReady);
+        }
+        for op in other.crdt.delete_ops() {
+            merged.apply_delete_op(op.clone())?;
+        }
+        *self = merged;
+
+        Ok(())
+    }
+
+    /// Sets whether ops referencing parents not held yet are applied, leaving them
+    /// dangling until the parents arrive, instead of being rejected.
+    pub fn allow_missing_parents(&mut self, allow: bool) {
+        self.crdt.allow_missing_parents(allow);
+    }
+
     /// Return user permissions, if applicable.
     pub fn permissions(&self, user: User, requester: Option<PublicKey>) -> Result<Permissions> {
         self.check_permission(Action::Read, requester)?;
@@ -190,6 +637,73 @@ impl Register {
         Ok(&self.policy)
     }
 
+    /// Return the version of the current policy, starting at 0 for the one the
+    /// Register was constructed with.
+    pub fn policy_version(&self) -> u64 {
+        self.policy_id.version
+    }
+
+    /// Return the id of the current policy, which writes are authorised under.
+    pub fn policy_id(&self) -> PolicyId {
+        self.policy_id
+    }
+
+    /// Return the policy of the given `version`, either the current or a past one,
+    /// or of those set concurrently for that version, the one with the lowest hash.
+    pub fn policy_at(&self, version: u64, requester: Option<PublicKey>) -> Result<&Policy> {
+        self.check_permission(Action::Read, requester)?;
+
+        let from = PolicyId {
+            version,
+            hash: [0; 32],
+        };
+        self.policies
+            .range(from..)
+            .next()
+            .filter(|(id, _)| id.version == version)
+            .map(|(_, policy)| policy)
+            .ok_or(Error::NoSuchEntry)
+    }
+
+    /// Replace the policy, returning the generated unsigned operation so the caller
+    /// can sign and broadcast it to other replicas. Only the owner and co-owners can
+    /// change the policy, and the new one has to be of the same kind as the Register.
+    pub fn set_policy(&mut self, policy: impl Into<Policy>) -> Result<PolicyOp> {
+        let op = PolicyOp {
+            address: *self.address(),
+            policy: policy.into(),
+            replaces: self.policy_id,
+            source: self.authority,
+            signature: None,
+        };
+        self.check_policy_op(&op)?;
+        self.insert_policy(op.id()?, op.policy.clone());
+
+        Ok(op)
+    }
+
+    /// Apply a signed policy operation. An op replacing a policy not held yet is
+    /// rejected with `Error::InvalidSuccessor`, to retry once that one is applied.
+    /// Owners can set policies concurrently: all of them are kept, and of those of
+    /// the latest version, every replica picks the one with the lowest hash.
+    pub fn apply_policy_op(&mut self, op: PolicyOp) -> Result<()> {
+        let signature = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        signature.verify_strict(&op.source, &op.bytes_to_sign()?)?;
+        if op.address != *self.address() {
+            return Err(Error::CrdtWrongAddress(op.address));
+        }
+
+        // An op delivered more than once is a no-op.
+        let id = op.id()?;
+        if self.policies.contains_key(&id) {
+            return Ok(());
+        }
+        self.check_policy_op(&op)?;
+        self.insert_policy(id, op.policy);
+
+        Ok(())
+    }
+
     /// Helper to check permissions for given `action`
     /// for the given requester's public key.
     ///
@@ -201,25 +715,235 @@ impl Register {
         self.policy.is_action_allowed(requester, action)
     }
 
+    /// Helper to check `cap` lets `requester` read this Register at time `now`, i.e.
+    /// it's granted to them for this Register, hasn't lapsed and is signed by the owner
+    /// or one of the co-owners.
+    fn check_read_cap(&self, cap: &ReadCap, requester: PublicKey, now: u64) -> Result<()> {
+        if cap.address != *self.address() || cap.grantee != requester || now > cap.expiry {
+            return Err(Error::AccessDenied(requester));
+        }
+
+        let bytes = cap.bytes_to_sign()?;
+        let is_signed_by_owner = std::iter::once(self.policy.owner())
+            .chain(self.policy.co_owners())
+            .any(|owner| cap.owner_sig.verify_strict(owner, &bytes).is_ok());
+        if is_signed_by_owner {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+
     /// Return the owner of the data.
     pub fn owner(&self) -> PublicKey {
         *self.policy.owner()
     }
 
+    /// Check `signature` over `payload` was combined by the group `key`, which has to be
+    /// the owner or one of the co-owners of the Register.
+    pub fn check_threshold_owner<T: AsRef<[u8]>>(
+        &self,
+        key: &ThresholdKey,
+        payload: T,
+        signature: &Signature,
+    ) -> Result<()> {
+        let is_owner = std::iter::once(self.policy.owner())
+            .chain(self.policy.co_owners())
+            .any(|owner| *owner == key.public_key());
+        if !is_owner {
+            return Err(Error::AccessDenied(key.public_key()));
+        }
+        key.verify_threshold_sig(payload, signature)
+    }
+
     /// Return the PK which the messages are expected to be signed with by this replica.
     pub fn replica_authority(&self) -> PublicKey {
         self.authority
     }
+
+    // Check `action` is allowed for `requester` under the policy with the id `policy`.
+    fn check_permission_under(
+        &self,
+        policy: PolicyId,
+        action: Action,
+        requester: PublicKey,
+    ) -> Result<()> {
+        self.policies
+            .get(&policy)
+            .ok_or(Error::OpNotCausallyReady)?
+            .is_action_allowed(requester, action)
+    }
+
+    // Check the policy op comes from an owner of the policy it replaces, which is
+    // held, and keeps the kind.
+    fn check_policy_op(&self, op: &PolicyOp) -> Result<()> {
+        let replaced = self
+            .policies
+            .get(&op.replaces)
+            .ok_or_else(|| Error::InvalidSuccessor(self.policy_version()))?;
+        if !replaced.is_owner(&op.source) {
+            return Err(Error::AccessDenied(op.source));
+        }
+        if matches!(op.policy, Policy::Public(_)) != self.is_public() {
+            return Err(Error::InvalidOperation);
+        }
+        Ok(())
+    }
+
+    // Keep the policy, making it the current one if it's of a later version, or of
+    // the same version with a lower hash, so replicas agree whatever the order.
+    fn insert_policy(&mut self, id: PolicyId, policy: Policy) {
+        let is_current = id.version > self.policy_id.version
+            || (id.version == self.policy_id.version && id.hash < self.policy_id.hash);
+        if is_current {
+            self.policy = policy.clone();
+            self.policy_id = id;
+        }
+        let _ = self.policies.insert(id, policy);
+    }
+
+    // Check the register is private and the requester can write to it under the
+    // policy with the id `policy`.
+    fn check_can_delete(&self, policy: PolicyId, requester: PublicKey) -> Result<()> {
+        if self.is_public() {
+            Err(Error::InvalidOperation)
+        } else {
+            self.check_permission_under(policy, Action::Write, requester)
+        }
+    }
+
+    // Check the entry is no larger than `MAX_REG_ENTRY_SIZE`.
+    fn check_entry_size(&self, entry: &[u8]) -> Result<()> {
+        if entry.len() > MAX_REG_ENTRY_SIZE {
+            Err(Error::ExceededSize)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Check the register can hold `count` more entries according to its policy,
+    // only when writing locally.
+    fn check_room_for(&self, count: u64) -> Result<()> {
+        match self.policy.max_entries() {
+            Some(max) if self.crdt.size().saturating_add(count) > max => {
+                Err(Error::DataFull { max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Check none of the entries added by `crdt_ops` would have more entries in its
+    // causal past than the `max_entries` of the policy they were authorised under.
+    // Ops already applied are let through, for the CRDT to ignore them.
+    fn check_causal_room(&self, policy: PolicyId, crdt_ops: &[Node<Entry>]) -> Result<()> {
+        let max = match self
+            .policies
+            .get(&policy)
+            .and_then(|policy| policy.max_entries())
+        {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        if crdt_ops
+            .iter()
+            .all(|crdt_op| self.crdt.holds(crdt_op.hash()))
+        {
+            return Ok(());
+        }
+        if self.crdt.causal_len(crdt_ops)? > max {
+            Err(Error::TooManyEntries { max })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A Register whose entries are values of type `T`, stored serialised with bincode.
+///
+/// Entries which can't be deserialised into a `T` are reported as `Error::Serialisation`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TypedRegister<T> {
+    register: Register,
+    _entries: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> TypedRegister<T> {
+    /// Wraps the given Register.
+    pub fn new(register: Register) -> Self {
+        Self {
+            register,
+            _entries: PhantomData,
+        }
+    }
+
+    /// Returns the underlying Register.
+    pub fn register(&self) -> &Register {
+        &self.register
+    }
+
+    /// Returns the underlying Register, unwrapping it.
+    pub fn into_register(self) -> Register {
+        self.register
+    }
+
+    /// Return the number of items held in the register
+    pub fn size(&self, requester: Option<PublicKey>) -> Result<u64> {
+        self.register.size(requester)
+    }
+
+    /// Return true if the register is empty.
+    pub fn is_empty(&self, requester: Option<PublicKey>) -> Result<bool> {
+        self.register.is_empty(requester)
+    }
+
+    /// Write the serialised value to the Register, returning the generated unsigned
+    /// CRDT operation along with the hash of the entry just written.
+    pub fn write(
+        &mut self,
+        value: &T,
+        parents: BTreeSet<EntryHash>,
+    ) -> Result<(EntryHash, RegisterOp<Entry>)> {
+        let entry = utils::serialise(value)?;
+        self.register.write(entry, parents)
+    }
+
+    /// Apply a signed data CRDT operation.
+    pub fn apply_op(&mut self, op: RegisterOp<Entry>) -> Result<()> {
+        self.register.apply_op(op)
+    }
+
+    /// Return the value corresponding to the provided 'hash', if present.
+    pub fn get(&self, hash: EntryHash, requester: Option<PublicKey>) -> Result<Option<T>> {
+        self.register
+            .get(hash, requester)?
+            .map(|entry| Self::parse(entry))
+            .transpose()
+    }
+
+    /// Read the last value, or values when there are branches, if the register is not empty.
+    pub fn read(&self, requester: Option<PublicKey>) -> Result<Vec<(EntryHash, T)>> {
+        self.register
+            .read(requester)?
+            .into_iter()
+            .map(|(hash, entry)| Ok((hash, Self::parse(&entry)?)))
+            .collect()
+    }
+
+    fn parse(entry: &[u8]) -> Result<T> {
+        utils::deserialise(entry)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         register::{
-            Address, Entry, EntryHash, Kind, Permissions, PrivatePermissions, PrivatePolicy,
-            PublicPermissions, PublicPolicy, Register, RegisterOp, User,
+            Address, BranchInfo, Entry, EntryHash, Kind, MergeStrategy, Permissions, Policy,
+            PolicyId, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, ReadCap,
+            Register, RegisterDeleteOp, RegisterOp, RegisterSyncOp, TypedRegister, User,
+            MAX_REG_ENTRY_SIZE,
         },
-        utils, Error, Keypair, Result,
+        utils, Error, Keypair, PublicKey, Result, Signature, ThresholdKey,
     };
     use anyhow::anyhow;
     use proptest::prelude::*;
@@ -228,6 +952,7 @@ mod tests {
         collections::{BTreeMap, BTreeSet},
         sync::Arc,
     };
+    use threshold_crypto::SecretKeySet;
     use xor_name::XorName;
 
     #[test]
@@ -296,6 +1021,8 @@ mod tests {
             Some(PublicPolicy {
                 owner: authority1,
                 permissions: perms.clone(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
         let mut replica2 = Register::new_public(
@@ -305,32 +1032,513 @@ mod tests {
             Some(PublicPolicy {
                 owner: authority1,
                 permissions: perms,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
-        // And let's write an item to replica1 with autority1
-        let item1 = b"item1";
-        let (_, op1) = replica1.write(item1.to_vec(), BTreeSet::new())?;
-        let signed_write_op1 = sign_register_op(op1, &authority_keypair1)?;
+        // And let's write an item to replica1 with autority1
+        let item1 = b"item1";
+        let (_, op1) = replica1.write(item1.to_vec(), BTreeSet::new())?;
+        let signed_write_op1 = sign_register_op(op1, &authority_keypair1)?;
+
+        // Let's assert current state on both replicas
+        assert_eq!(replica1.size(None)?, 1);
+        assert_eq!(replica2.size(None)?, 0);
+
+        // Concurrently write another item with authority2 on replica2
+        let item2 = b"item2";
+        let (_, op2) = replica2.write(item2.to_vec(), BTreeSet::new())?;
+        let signed_write_op2 = sign_register_op(op2, &authority_keypair2)?;
+
+        // Item should be writeed on replica2
+        assert_eq!(replica2.size(None)?, 1);
+
+        // Write operations are now broadcasted and applied to both replicas
+        replica1.apply_op(signed_write_op2)?;
+        replica2.apply_op(signed_write_op1)?;
+
+        // Let's assert data convergence on both replicas
+        verify_data_convergence(vec![replica1, replica2], 2)?;
+
+        Ok(())
+    }
+
+    // Public Register of an Ed25519 owner, which a user is allowed to write to,
+    // holding an entry and two entries forking off it, encoded before the layout of
+    // Registers was versioned.
+    const PUBLIC_REGISTER_V0: &str = "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c000000000303030303030303030303030303030303030303030303030303030303030303983a000000000000020000000000000085316503776a72e690cd9257dfaf3f109c0e447dc7413728e7dd6487a2ebdf73c4db4d6f12b480b6d3fe36facc8cb2516a65cd3e805fbae9c0eda85cfe5b8baa03000000000000005fdbf98ad6ebaca45ff0cc60361778b8b5eecfd76f44c11bcd8b558a5364b2ca00000000000000000500000000000000666972737485316503776a72e690cd9257dfaf3f109c0e447dc7413728e7dd6487a2ebdf7301000000000000005fdbf98ad6ebaca45ff0cc60361778b8b5eecfd76f44c11bcd8b558a5364b2ca0400000000000000666f726bc4db4d6f12b480b6d3fe36facc8cb2516a65cd3e805fbae9c0eda85cfe5b8baa01000000000000005fdbf98ad6ebaca45ff0cc60361778b8b5eecfd76f44c11bcd8b558a5364b2ca06000000000000007365636f6e640000000000000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c0100000000000000010000000000000020000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940101";
+    // Private Register of an Ed25519 owner, which a user is allowed to read, holding
+    // a single entry, encoded before the layout of Registers was versioned.
+    const PRIVATE_REGISTER_V0: &str = "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c010000000404040404040404040404040404040404040404040404040404040404040404993a00000000000001000000000000002032a7663effc6b47d3d2476625c0f085d89cdb9d1df44904fe558b65a703cb801000000000000002032a7663effc6b47d3d2476625c0f085d89cdb9d1df44904fe558b65a703cb800000000000000000700000000000000707269766174650000000000000000010000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c01000000000000000000000020000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940100";
+
+    #[test]
+    fn register_threshold_co_owner() -> anyhow::Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let sk_set = SecretKeySet::random(1, &mut OsRng);
+        let group = ThresholdKey::new(sk_set.public_keys());
+        let group_sign = |payload: &[u8]| -> anyhow::Result<Signature> {
+            let shares: Vec<_> = (0..2)
+                .map(|i| (i, sk_set.secret_key_share(i).sign(payload)))
+                .collect();
+            let sig = group
+                .pk_set()
+                .combine_signatures(shares.iter().map(|(i, share)| (*i, share)))
+                .map_err(|_| Error::InvalidSignature)?;
+            Ok(Signature::Bls(sig))
+        };
+
+        let name = XorName::random();
+        let policy = PublicPolicy {
+            owner,
+            permissions: BTreeMap::new(),
+            max_entries: None,
+            co_owners: vec![group.public_key()].into_iter().collect(),
+        };
+        let mut replica = Register::new_public(owner, name, 43_000, Some(policy.clone()));
+
+        // Only a signature combined by the group is accepted
+        let payload = b"request";
+        replica.check_threshold_owner(&group, payload, &group_sign(payload)?)?;
+        assert_eq!(
+            replica.check_threshold_owner(&group, b"other request", &group_sign(payload)?),
+            Err(Error::InvalidSignature)
+        );
+        let share = Signature::from((0, sk_set.secret_key_share(0).sign(payload)));
+        assert_eq!(
+            replica.check_threshold_owner(&group, payload, &share),
+            Err(Error::InvalidSignature)
+        );
+        let other = ThresholdKey::new(SecretKeySet::random(1, &mut OsRng).public_keys());
+        assert_eq!(
+            replica.check_threshold_owner(&other, payload, &group_sign(payload)?),
+            Err(Error::AccessDenied(other.public_key()))
+        );
+
+        // and the group can replace the policy as a co-owner
+        let mut group_replica =
+            Register::new_public(group.public_key(), name, 43_000, Some(policy.clone()));
+        let mut policy_op = group_replica.set_policy(PublicPolicy {
+            max_entries: Some(10),
+            ..policy
+        })?;
+        policy_op.signature = Some(share);
+        assert_eq!(
+            replica.apply_policy_op(policy_op.clone()),
+            Err(Error::SigningKeyTypeMismatch)
+        );
+        policy_op.signature = Some(group_sign(&policy_op.bytes_to_sign()?)?);
+        replica.apply_policy_op(policy_op)?;
+        assert_eq!(replica.policy_version(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn register_decodes_earliest_layout() -> anyhow::Result<()> {
+        let key = |byte| {
+            let secret = ed25519_dalek::SecretKey::from_bytes(&[byte; 32])?;
+            Ok::<_, anyhow::Error>(PublicKey::Ed25519((&secret).into()))
+        };
+        let owner = key(1)?;
+        let user = key(2)?;
+
+        let register: Register = utils::deserialise(&hex::decode(PUBLIC_REGISTER_V0)?)?;
+        assert_eq!(register.replica_authority(), owner);
+        assert_eq!(
+            *register.address(),
+            Address::Public {
+                name: XorName([3; 32]),
+                tag: 15_000
+            }
+        );
+        assert_eq!(register.policy_version(), 0);
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(User::Key(user), PublicPermissions::new(true));
+        assert_eq!(
+            register.policy(None)?,
+            &Policy::Public(PublicPolicy {
+                owner,
+                permissions,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
+            })
+        );
+        assert_eq!(register.size(None)?, 3);
+        let heads: BTreeSet<_> = register
+            .read(None)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        let expected: BTreeSet<_> = vec![b"second".to_vec(), b"fork".to_vec()]
+            .into_iter()
+            .collect();
+        assert_eq!(heads, expected);
+        // Entries are recorded parents first, as written by the authority
+        assert_eq!(register.op_count(None)?, 3);
+        assert_eq!(register.read_at(1, None)?.len(), 1);
+        for (hash, _) in register.read(None)? {
+            let authors: BTreeSet<_> = vec![owner].into_iter().collect();
+            assert_eq!(register.entry_authors(hash, None)?, authors);
+        }
+
+        // It's encoded with the latest layout from then on
+        let decoded: Register = utils::deserialise(&utils::serialise(&register)?)?;
+        assert_eq!(decoded, register);
+
+        let register: Register = utils::deserialise(&hex::decode(PRIVATE_REGISTER_V0)?)?;
+        assert_eq!(
+            *register.address(),
+            Address::Private {
+                name: XorName([4; 32]),
+                tag: 15_001
+            }
+        );
+        assert_eq!(register.size(Some(user))?, 1);
+        assert_eq!(
+            register.permissions(User::Key(user), Some(owner))?,
+            Permissions::Private(PrivatePermissions::new(true, false))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_max_entries_bounds_causal_past() -> anyhow::Result<()> {
+        let authority_keypair = Keypair::new_ed25519(&mut OsRng);
+        let policy = PublicPolicy {
+            owner: authority_keypair.public_key(),
+            permissions: BTreeMap::default(),
+            max_entries: Some(2),
+            co_owners: BTreeSet::new(),
+        };
+        let mut replicas = gen_pub_reg_replicas(
+            Some(authority_keypair.clone()),
+            XorName::random(),
+            43_000,
+            Some(policy),
+            2,
+        );
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        // Both replicas are filled up concurrently
+        let (hash1, op1) = replica1.write(b"value0".to_vec(), BTreeSet::new())?;
+        let parents = vec![hash1].into_iter().collect();
+        let (hash2, op2) = replica1.write(b"value1".to_vec(), parents)?;
+        assert_eq!(
+            replica1.write(b"value2".to_vec(), BTreeSet::new()),
+            Err(Error::DataFull { max: 2 })
+        );
+        let (hash3, op3) = replica2.write(b"other".to_vec(), BTreeSet::new())?;
+        let op1 = sign_register_op(op1, &authority_keypair)?;
+        let op2 = sign_register_op(op2, &authority_keypair)?;
+        let op3 = sign_register_op(op3, &authority_keypair)?;
+
+        // The ops are delivered in different orders, and all of them are applied as
+        // none has more than 2 entries in its causal past, so the replicas converge
+        replica1.apply_op(op3.clone())?;
+        replica1.apply_op(op1.clone())?;
+        replica1.apply_op(op2.clone())?;
+        replica2.apply_op(op1)?;
+        replica2.apply_op(op2)?;
+        replica2.apply_op(op3)?;
+        assert_eq!(replica1.read(None)?, replica2.read(None)?);
+
+        // Neither can be written to any further
+        assert_eq!(
+            replica2.write(b"value3".to_vec(), BTreeSet::new()),
+            Err(Error::DataFull { max: 2 })
+        );
+
+        // An op merging the branches, written bypassing the local check, has 4 entries
+        // in its causal past, so it's rejected whatever order the ops arrived in
+        let parents = vec![hash2, hash3].into_iter().collect();
+        let mut writer = replica1.clone();
+        let (_, op4) = writer.crdt.write(
+            b"merge".to_vec(),
+            parents,
+            writer.authority,
+            None,
+            writer.policy_id,
+        )?;
+        let op4 = sign_register_op(op4, &authority_keypair)?;
+        assert_eq!(
+            replica1.apply_op(op4.clone()),
+            Err(Error::TooManyEntries { max: 2 })
+        );
+        assert_eq!(
+            replica2.apply_op(op4),
+            Err(Error::TooManyEntries { max: 2 })
+        );
+
+        // So is a batch whose entries lengthen a branch past the bound
+        let parents: BTreeSet<_> = vec![hash2].into_iter().collect();
+        let (hashes, mut batch_op) = writer.crdt.write_batch(
+            vec![
+                (b"next".to_vec(), parents),
+                (b"other".to_vec(), BTreeSet::new()),
+            ],
+            writer.authority,
+            writer.policy_id,
+        )?;
+        assert_eq!(hashes.len(), 2);
+        batch_op.signature = Some(authority_keypair.sign(&batch_op.bytes_to_sign()?));
+        assert_eq!(
+            replica2.apply_batch_op(batch_op),
+            Err(Error::TooManyEntries { max: 2 })
+        );
+        verify_data_convergence(vec![replica1, replica2], 3)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_write_fails_when_entry_too_large() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let entry = vec![0; MAX_REG_ENTRY_SIZE];
+        let (_, op) = replica1.write(entry.clone(), BTreeSet::new())?;
+        replica2.apply_op(sign_register_op(op, &keypair1)?)?;
+
+        let mut large = entry;
+        large.push(0);
+        assert_eq!(
+            replica1.write(large.clone(), BTreeSet::new()),
+            Err(Error::ExceededSize)
+        );
+
+        // Nor is an oversized entry from another replica applied
+        let (_, mut op) = replica1.write(b"small".to_vec(), BTreeSet::new())?;
+        op.crdt_op.value = large;
+        assert_eq!(
+            replica2.apply_op(sign_register_op(op, &keypair1)?),
+            Err(Error::ExceededSize)
+        );
+        assert_eq!(replica2.size(None)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_duplicate_op_delivery() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let (hash1, op1) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        let op1 = sign_register_op(op1, &keypair1)?;
+        let parents = vec![hash1].into_iter().collect();
+        let (_, op2) = replica1.write(b"second".to_vec(), parents)?;
+        let op2 = sign_register_op(op2, &keypair1)?;
+
+        // Ops delivered out of order are rejected until their parents are applied
+        assert_eq!(
+            replica2.apply_op(op2.clone()),
+            Err(Error::MissingParents {
+                hashes: vec![hash1].into_iter().collect()
+            })
+        );
+
+        // and ops delivered more than once are only applied once
+        for op in [op1.clone(), op1.clone(), op2.clone(), op2] {
+            replica2.apply_op(op)?;
+        }
+        // including to the replica which wrote them
+        replica1.apply_op(op1)?;
+
+        verify_data_convergence(vec![replica1.clone(), replica2.clone()], 2)?;
+        assert_eq!(replica1.to_dot(None)?, replica2.to_dot(None)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_dag_export() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (keypair2, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let (hash1, op1) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        replica2.apply_op(sign_register_op(op1, &keypair1)?)?;
+        let parents = vec![hash1].into_iter().collect();
+        let (hash2, op2) = replica2.write(b"second".to_vec(), parents)?;
+        replica1.apply_op(sign_register_op(op2, &keypair2)?)?;
+
+        let dot = replica1.to_dot(None)?;
+        assert!(dot.starts_with("digraph Register {"));
+        assert!(dot.contains(&format!(
+            "\"{}\" -> \"{}\"",
+            hex::encode(hash2),
+            hex::encode(hash1)
+        )));
+        // Both replicas render the same DAG
+        assert_eq!(dot, replica2.to_dot(None)?);
+
+        let summary = replica1.summary(None)?;
+        assert_eq!(summary.lines().count(), 2);
+        assert_eq!(
+            summary
+                .lines()
+                .filter(|line| line.ends_with("(head)"))
+                .count(),
+            1
+        );
+
+        Ok(())
+    }
 
-        // Let's assert current state on both replicas
-        assert_eq!(replica1.size(None)?, 1);
-        assert_eq!(replica2.size(None)?, 0);
+    #[test]
+    fn register_branch_info() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (keypair2, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+        assert_eq!(replica1.branch_info(None)?, BranchInfo::default());
+
+        let (hash1, op1) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        replica2.apply_op(sign_register_op(op1, &keypair1)?)?;
+        let parents: BTreeSet<_> = vec![hash1].into_iter().collect();
+        let (hash2, op2) = replica1.write(b"second".to_vec(), parents.clone())?;
+        let (hash3, op3) = replica2.write(b"fork".to_vec(), parents)?;
+        let parents = vec![hash2].into_iter().collect();
+        let (hash4, op4) = replica1.write(b"third".to_vec(), parents)?;
+        replica2.apply_op(sign_register_op(op2, &keypair1)?)?;
+        replica2.apply_op(sign_register_op(op4, &keypair1)?)?;
+        replica1.apply_op(sign_register_op(op3, &keypair2)?)?;
+
+        let info = replica1.branch_info(None)?;
+        assert_eq!(info.leaves, 2);
+        assert_eq!(info.depth, 3);
+        assert_eq!(info.lineages.get(&hash4), Some(&3));
+        assert_eq!(info.lineages.get(&hash3), Some(&2));
+        assert_eq!(info, replica2.branch_info(None)?);
 
-        // Concurrently write another item with authority2 on replica2
-        let item2 = b"item2";
-        let (_, op2) = replica2.write(item2.to_vec(), BTreeSet::new())?;
-        let signed_write_op2 = sign_register_op(op2, &authority_keypair2)?;
+        Ok(())
+    }
 
-        // Item should be writeed on replica2
-        assert_eq!(replica2.size(None)?, 1);
+    #[test]
+    fn register_merge_replica_state() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(3);
+        let (_, replica3) = replicas.remove(2);
+        let (keypair2, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let (hash1, op1) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        let op1 = sign_register_op(op1, &keypair1)?;
+        replica1.apply_op(op1.clone())?;
+        let parents = vec![hash1].into_iter().collect();
+        let (_, op2) = replica1.write(b"second".to_vec(), parents)?;
+        replica1.apply_op(sign_register_op(op2, &keypair1)?)?;
+        replica2.apply_op(op1)?;
+        let (_, op3) = replica2.write(b"concurrent".to_vec(), BTreeSet::new())?;
+        replica2.apply_op(sign_register_op(op3, &keypair2)?)?;
+        // Written but not applied back signed, so it can't be merged
+        let _ = replica2.write(b"unsigned".to_vec(), BTreeSet::new())?;
+
+        let mut stale = replica3.clone();
+        stale.merge(replica1.clone())?;
+        stale.merge(replica2.clone())?;
+        replica1.merge(replica2.clone())?;
+        assert_eq!(stale.read(None)?, replica1.read(None)?);
+        assert_eq!(stale.size(None)?, 3);
+        assert_eq!(stale.read(None)?.len(), 2);
+
+        // The merged entries are not applied again
+        let (_, op) = replica1.write(b"third".to_vec(), BTreeSet::new())?;
+        let op = sign_register_op(op, &keypair1)?;
+        stale.apply_op(op.clone())?;
+        stale.apply_op(op)?;
+        assert_eq!(stale.size(None)?, 4);
+
+        let other = create_public_reg_replicas(1).remove(0).1;
+        assert!(matches!(
+            stale.merge(other),
+            Err(Error::CrdtWrongAddress(_))
+        ));
+
+        // Entries held with missing parents are rejected rather than left out
+        let mut orphaned = replica3.clone();
+        orphaned.allow_missing_parents(true);
+        let (_, op) = replica1.write(b"fourth".to_vec(), vec![[0; 32]].into_iter().collect())?;
+        orphaned.apply_op(sign_register_op(op, &keypair1)?)?;
+        let mut fresh = replica3;
+        let before = fresh.clone();
+        assert!(fresh.merge(orphaned).is_err());
+        assert_eq!(fresh, before);
 
-        // Write operations are now broadcasted and applied to both replicas
-        replica1.apply_op(signed_write_op2)?;
-        replica2.apply_op(signed_write_op1)?;
+        Ok(())
+    }
 
-        // Let's assert data convergence on both replicas
-        verify_data_convergence(vec![replica1, replica2], 2)?;
+    #[test]
+    fn register_missing_since_known_heads() -> anyhow::Result<()> {
+        let (keypair, mut replica) = create_public_reg_replicas(1).remove(0);
+        let mut write = |entry: &[u8], parents: Vec<EntryHash>| -> anyhow::Result<EntryHash> {
+            let parents = parents.into_iter().collect();
+            let (hash, op) = replica.write(entry.to_vec(), parents)?;
+            replica.apply_op(sign_register_op(op, &keypair)?)?;
+            Ok(hash)
+        };
+        let first = write(b"first", vec![])?;
+        let second = write(b"second", vec![first])?;
+        let third = write(b"third", vec![second])?;
+        let branch = write(b"branch", vec![first])?;
+
+        // Only the head read before is known, not its ancestors
+        let known = vec![second].into_iter().collect();
+        let missing: BTreeSet<_> = replica
+            .missing_since(&known, None)?
+            .iter()
+            .flat_map(|op| op.crdt_ops())
+            .map(|crdt_op| crdt_op.hash())
+            .collect();
+        assert_eq!(missing, vec![third, branch].into_iter().collect());
+
+        let heads = replica
+            .read(None)?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+        assert!(replica.missing_since(&heads, None)?.is_empty());
+        assert_eq!(replica.missing_since(&BTreeSet::new(), None)?.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_delta_sync() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(3);
+        let (_, mut replica3) = replicas.remove(2);
+        let (keypair2, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        // replica2 gets all of replica1's signed ops, replica3 only the first one
+        let mut parents = BTreeSet::new();
+        for (index, entry) in vec![&b"first"[..], b"second", b"third"]
+            .into_iter()
+            .enumerate()
+        {
+            let (hash, op) = replica1.write_at(entry.to_vec(), parents, index as u64)?;
+            let op = sign_register_op(op, &keypair1)?;
+            replica2.apply_op(op.clone())?;
+            if index == 0 {
+                replica3.apply_op(op)?;
+            }
+            parents = vec![hash].into_iter().collect();
+        }
+        let (_, op) = replica2.write(b"concurrent".to_vec(), BTreeSet::new())?;
+        // Not sent back signed, so replica2 can't hand this one over
+        let _ = sign_register_op(op, &keypair2)?;
+
+        let ops = replica2.ops_since(&replica3.digest(None)?, None)?;
+        assert_eq!(ops.len(), 2);
+        for op in ops {
+            replica3.apply_sync_op(op)?;
+        }
+        assert_eq!(replica3.read(None)?, replica1.read(None)?);
+        assert!(replica1
+            .ops_since(&replica3.digest(None)?, None)?
+            .is_empty());
 
         Ok(())
     }
@@ -372,6 +1580,28 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_storage_proof() -> anyhow::Result<()> {
+        let (_, register) = &mut create_public_reg_replicas(1)[0];
+        let empty_size = register.size_in_bytes(None)?;
+
+        let _ = register.write(b"value0".to_vec(), BTreeSet::new())?;
+        let _ = register.write(b"value1".to_vec(), BTreeSet::new())?;
+        assert!(register.size_in_bytes(None)? > empty_size + 12);
+
+        let holder = Keypair::new_ed25519(&mut OsRng);
+        let mut proof = register.storage_proof(&holder)?;
+        assert_eq!(proof.address, *register.address());
+        assert_eq!(proof.count, 2);
+        assert_eq!(proof.bytes, register.size_in_bytes(None)?);
+        proof.verify()?;
+
+        proof.count = 1;
+        assert_eq!(proof.verify(), Err(Error::InvalidSignature));
+
+        Ok(())
+    }
+
     #[test]
     fn register_query_public_policy() -> anyhow::Result<()> {
         let register_name = XorName::random();
@@ -389,6 +1619,8 @@ mod tests {
             Some(PublicPolicy {
                 owner: owner1,
                 permissions: perms1,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -404,6 +1636,8 @@ mod tests {
             Some(PublicPolicy {
                 owner: authority2,
                 permissions: perms2,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -459,6 +1693,8 @@ mod tests {
             Some(PrivatePolicy {
                 owner: authority1,
                 permissions: perms1,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -469,6 +1705,8 @@ mod tests {
             Some(PrivatePolicy {
                 owner: authority2,
                 permissions: perms2,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -522,6 +1760,8 @@ mod tests {
             Some(PublicPolicy {
                 owner: owner1,
                 permissions: perms1,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -537,6 +1777,8 @@ mod tests {
             Some(PublicPolicy {
                 owner: authority2,
                 permissions: perms2,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -581,6 +1823,8 @@ mod tests {
             Some(PrivatePolicy {
                 owner: authority1,
                 permissions: perms1,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -591,6 +1835,8 @@ mod tests {
             Some(PrivatePolicy {
                 owner: authority2,
                 permissions: perms2,
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             }),
         );
 
@@ -646,15 +1892,616 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_co_owners_can_write_to_private_register() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let co_owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let stranger_keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let policy = PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions: BTreeMap::default(),
+            max_entries: None,
+            co_owners: vec![co_owner_keypair.public_key()].into_iter().collect(),
+        };
+
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair),
+            Some(policy.clone()),
+        );
+        let mut replica2 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(co_owner_keypair.clone()),
+            Some(policy.clone()),
+        );
+        let mut replica3 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(stranger_keypair.clone()),
+            Some(policy),
+        );
+
+        // the co-owner's writes are accepted by the owner's replica
+        let (entry_hash, op) = replica2.write(b"item".to_vec(), BTreeSet::new())?;
+        replica1.apply_op(sign_register_op(op, &co_owner_keypair)?)?;
+        assert_eq!(
+            replica1.get(entry_hash, Some(co_owner_keypair.public_key()))?,
+            Some(&b"item".to_vec())
+        );
+
+        // but not those from a key which is neither owner nor co-owner
+        check_op_not_allowed_failure(replica3.write(b"other".to_vec(), BTreeSet::new()))?;
+        check_op_not_allowed_failure(replica1.read(Some(stranger_keypair.public_key())))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_verify_entry_provenance() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (keypair2, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let (hash, op) = replica1.write_at(b"entry".to_vec(), BTreeSet::new(), 42)?;
+        // Not known signed until the signed op is applied
+        assert_eq!(
+            replica1.verify_entry(hash, None),
+            Err(Error::CrdtMissingOpSignature)
+        );
+
+        // The same entry re-signed by another writer, without a timestamp, is another op:
+        // whatever the order they arrive in, both replicas report both authors
+        let mut other_op = op.clone();
+        other_op.source = keypair2.public_key();
+        other_op.timestamp = None;
+        let other_op = sign_register_op(other_op, &keypair2)?;
+        let op = sign_register_op(op, &keypair1)?;
+        replica1.apply_op(other_op.clone())?;
+        replica1.apply_op(op.clone())?;
+        replica2.apply_op(op)?;
+        replica2.apply_op(other_op)?;
+
+        let authors: BTreeSet<_> = vec![keypair1.public_key(), keypair2.public_key()]
+            .into_iter()
+            .collect();
+        for replica in [&replica1, &replica2] {
+            assert_eq!(replica.entry_authors(hash, None)?, authors);
+            replica.verify_entry(hash, None)?;
+            assert_eq!(replica.ops_since(&BTreeSet::new(), None)?.len(), 2);
+        }
+
+        let missing = [0; 32];
+        assert!(replica2.entry_authors(missing, None)?.is_empty());
+        assert_eq!(
+            replica2.verify_entry(missing, None),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_read_at_version() -> anyhow::Result<()> {
+        let (_, mut replica) = create_public_reg_replicas(1).remove(0);
+        assert!(replica.read_at(0, None)?.is_empty());
+
+        let (first, _) = replica.write(b"first".to_vec(), BTreeSet::new())?;
+        let parents: BTreeSet<_> = vec![first].into_iter().collect();
+        let (hashes, _) = replica.write_batch(vec![
+            (b"second".to_vec(), parents.clone()),
+            (b"third".to_vec(), parents),
+        ])?;
+        let parents = hashes.iter().copied().collect();
+        let (fourth, _) = replica.write(b"fourth".to_vec(), parents)?;
+        assert_eq!(replica.op_count(None)?, 3);
+
+        let first_only: BTreeSet<_> = vec![(first, b"first".to_vec())].into_iter().collect();
+        assert_eq!(replica.read_at(1, None)?, first_only);
+        assert_eq!(replica.size_at(1, None)?, 1);
+        let branches: BTreeSet<_> = replica
+            .read_at(2, None)?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+        assert_eq!(branches, hashes.into_iter().collect());
+        assert_eq!(replica.size_at(2, None)?, 3);
+        assert_eq!(replica.read_at(3, None)?, replica.read(None)?);
+        assert!(replica.read(None)?.iter().any(|(hash, _)| *hash == fourth));
+
+        assert_eq!(replica.read_at(4, None), Err(Error::NoSuchEntry));
+        assert_eq!(replica.size_at(4, None), Err(Error::NoSuchEntry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_write_batch() -> anyhow::Result<()> {
+        let mut replicas = create_public_reg_replicas(2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (keypair1, mut replica1) = replicas.remove(0);
+
+        let (first, op) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        let first_op = sign_register_op(op, &keypair1)?;
+        let parents: BTreeSet<_> = vec![first].into_iter().collect();
+        let (hashes, op) = replica1.write_batch(vec![
+            (b"second".to_vec(), parents.clone()),
+            (b"third".to_vec(), parents),
+        ])?;
+        assert_eq!(hashes.len(), 2);
+        let mut batch_op = op;
+        batch_op.signature = Some(keypair1.sign(&batch_op.bytes_to_sign()?));
+
+        // None of the entries is added while the parent is missing
+        match replica2.apply_batch_op(batch_op.clone()) {
+            Err(Error::MissingParents { hashes }) => assert!(hashes.contains(&first)),
+            other => return Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+        assert_eq!(replica2.size(None)?, 0);
+
+        replica2.apply_op(first_op)?;
+        replica2.apply_batch_op(batch_op.clone())?;
+        // Applying it again is a no-op
+        replica2.apply_batch_op(batch_op.clone())?;
+        assert_eq!(replica2.size(None)?, 3);
+        assert_eq!(replica2.read(None)?, replica1.read(None)?);
+        assert_eq!(replica2.read(None)?.len(), 2);
+
+        // The entries are verified against the batch signature, and the batch is
+        // handed over whole to a replica missing them
+        for hash in &hashes {
+            replica2.verify_entry(*hash, None)?;
+        }
+        let digest = vec![first].into_iter().collect();
+        assert_eq!(
+            replica2.ops_since(&digest, None)?,
+            vec![RegisterSyncOp::Batch(batch_op)]
+        );
+
+        assert_eq!(replica1.write_batch(vec![]), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_register_entries() -> anyhow::Result<()> {
+        let (keypair, replica) = create_public_reg_replicas(1).remove(0);
+        let mut register = TypedRegister::<(String, u64)>::new(replica);
+
+        let (first, op) = register.write(&("a".to_string(), 1), BTreeSet::new())?;
+        register.apply_op(sign_register_op(op, &keypair)?)?;
+        let parents = vec![first].into_iter().collect();
+        let (second, op) = register.write(&("b".to_string(), 2), parents)?;
+        register.apply_op(sign_register_op(op, &keypair)?)?;
+
+        assert_eq!(register.size(None)?, 2);
+        assert_eq!(register.get(first, None)?, Some(("a".to_string(), 1)));
+        assert_eq!(register.read(None)?, vec![(second, ("b".to_string(), 2))]);
+
+        // Entries of another type can't be read as such
+        let register = TypedRegister::<(u64, u64, u64)>::new(register.into_register());
+        match register.get(first, None) {
+            Err(Error::Serialisation(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn register_read_with_cap() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let grantee = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut replica = create_private_reg_replica_with(
+            XorName::random(),
+            43_000,
+            Some(owner_keypair.clone()),
+            None,
+        );
+        let (hash, _) = replica.write(b"entry".to_vec(), BTreeSet::new())?;
+        check_op_not_allowed_failure(replica.read(Some(grantee)))?;
+
+        let cap = ReadCap::new(*replica.address(), grantee, 100, &owner_keypair)?;
+        assert_eq!(
+            replica.read_with_cap(&cap, grantee, 50)?,
+            replica.read(None)?
+        );
+        assert_eq!(
+            replica.get_with_cap(hash, &cap, grantee, 100)?,
+            Some(&b"entry".to_vec())
+        );
+
+        // Lapsed, presented by another key, or for another register
+        check_op_not_allowed_failure(replica.read_with_cap(&cap, grantee, 101))?;
+        check_op_not_allowed_failure(replica.read_with_cap(&cap, owner_keypair.public_key(), 50))?;
+        let other =
+            create_private_reg_replica_with(XorName::random(), 43_000, Some(owner_keypair), None);
+        check_op_not_allowed_failure(other.read_with_cap(&cap, grantee, 50))?;
+
+        // Not granted by the owner
+        let forged = ReadCap::new(
+            *replica.address(),
+            grantee,
+            100,
+            &Keypair::new_ed25519(&mut OsRng),
+        )?;
+        assert_eq!(
+            replica.read_with_cap(&forged, grantee, 50),
+            Err(Error::InvalidSignature)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_read_with_cap_granted_by_co_owner() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let co_owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let grantee = Keypair::new_ed25519(&mut OsRng).public_key();
+        let policy = PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions: BTreeMap::default(),
+            max_entries: None,
+            co_owners: vec![co_owner_keypair.public_key()].into_iter().collect(),
+        };
+        let mut replica = create_private_reg_replica_with(
+            XorName::random(),
+            43_000,
+            Some(owner_keypair),
+            Some(policy),
+        );
+        let _ = replica.write(b"entry".to_vec(), BTreeSet::new())?;
+
+        let cap = ReadCap::new(*replica.address(), grantee, 100, &co_owner_keypair)?;
+        assert_eq!(
+            replica.read_with_cap(&cap, grantee, 50)?,
+            replica.read(None)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_policy_updates() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            None,
+        );
+        let mut replica2 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(user_keypair.clone()),
+            Some(PrivatePolicy {
+                owner: owner_keypair.public_key(),
+                permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
+            }),
+        );
+        check_op_not_allowed_failure(replica2.write(b"entry".to_vec(), BTreeSet::new()))?;
+
+        // the owner grants write permission to the user
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            user_keypair.public_key(),
+            PrivatePermissions::new(true, true),
+        );
+        let policy = PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions,
+            max_entries: None,
+            co_owners: BTreeSet::new(),
+        };
+        let mut op = replica1.set_policy(policy)?;
+        op.signature = Some(owner_keypair.sign(&op.bytes_to_sign()?));
+        replica2.apply_policy_op(op.clone())?;
+        replica2.apply_policy_op(op.clone())?;
+        assert_eq!(replica2.policy_version(), 1);
+        assert_eq!(replica2.policy(None)?, replica1.policy(None)?);
+        let (_, write_op) = replica2.write(b"entry".to_vec(), BTreeSet::new())?;
+        replica1.apply_op(sign_register_op(write_op, &user_keypair)?)?;
+
+        // the previous policy is kept
+        assert_ne!(replica2.policy_at(0, None)?, replica2.policy(None)?);
+        assert_eq!(replica2.policy_at(2, None), Err(Error::NoSuchEntry));
+
+        // only owners can change the policy, and only replacing one held
+        assert_eq!(
+            replica2.set_policy(PrivatePolicy {
+                owner: user_keypair.public_key(),
+                permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
+            }),
+            Err(Error::AccessDenied(user_keypair.public_key()))
+        );
+        op.replaces = PolicyId {
+            version: 2,
+            hash: [0; 32],
+        };
+        op.signature = Some(owner_keypair.sign(&op.bytes_to_sign()?));
+        assert_eq!(
+            replica2.apply_policy_op(op),
+            Err(Error::InvalidSuccessor(1))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_concurrent_policy_updates() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let co_owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            user_keypair.public_key(),
+            PrivatePermissions::new(true, true),
+        );
+        let policy = PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions,
+            max_entries: None,
+            co_owners: vec![co_owner_keypair.public_key()].into_iter().collect(),
+        };
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            Some(policy.clone()),
+        );
+        let mut replica2 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(co_owner_keypair.clone()),
+            Some(policy.clone()),
+        );
+        let mut replica3 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(user_keypair.clone()),
+            Some(policy.clone()),
+        );
+
+        // the user writes while the owner revokes their permission
+        let (_, write_op) = replica3.write(b"entry".to_vec(), BTreeSet::new())?;
+        let write_op = sign_register_op(write_op, &user_keypair)?;
+        let mut revoked = policy.clone();
+        revoked.permissions = BTreeMap::new();
+        let mut op1 = replica1.set_policy(revoked)?;
+        op1.signature = Some(owner_keypair.sign(&op1.bytes_to_sign()?));
+
+        // the co-owner concurrently sets another policy of the same version
+        let mut other = policy;
+        other.max_entries = Some(10);
+        let mut op2 = replica2.set_policy(other)?;
+        op2.signature = Some(co_owner_keypair.sign(&op2.bytes_to_sign()?));
+
+        // the replicas agree on the current policy whatever the order
+        replica1.apply_policy_op(op2.clone())?;
+        replica2.apply_policy_op(op1.clone())?;
+        replica3.apply_policy_op(op2)?;
+        replica3.apply_policy_op(op1)?;
+        assert_eq!(replica1.policy_version(), 1);
+        assert_eq!(replica1.policy_id(), replica2.policy_id());
+        assert_eq!(replica1.policy_id(), replica3.policy_id());
+        assert_eq!(replica1.policy(None)?, replica2.policy(None)?);
+
+        // the write authorised before the change is accepted by every replica
+        replica1.apply_op(write_op.clone())?;
+        replica2.apply_op(write_op)?;
+        assert_eq!(replica1.size(None)?, 1);
+        assert_eq!(replica2.size(None)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_op_under_unknown_policy() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            None,
+        );
+        let mut replica2 = replica1.clone();
+
+        let mut policy_op = replica1.set_policy(PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions: BTreeMap::default(),
+            max_entries: Some(5),
+            co_owners: BTreeSet::new(),
+        })?;
+        policy_op.signature = Some(owner_keypair.sign(&policy_op.bytes_to_sign()?));
+        let (_, op) = replica1.write(b"entry".to_vec(), BTreeSet::new())?;
+        let op = sign_register_op(op, &owner_keypair)?;
+
+        // the write is only applied once the policy it was authorised under is
+        assert_eq!(
+            replica2.apply_op(op.clone()),
+            Err(Error::OpNotCausallyReady)
+        );
+        replica2.apply_policy_op(policy_op)?;
+        replica2.apply_op(op)?;
+        assert_eq!(replica2.size(None)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_resolve_concurrent_entries() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            user_keypair.public_key(),
+            PrivatePermissions::new(true, true),
+        );
+        let policy = PrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions,
+            max_entries: None,
+            co_owners: BTreeSet::new(),
+        };
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            Some(policy.clone()),
+        );
+        let mut replica2 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(user_keypair.clone()),
+            Some(policy),
+        );
+        assert_eq!(replica1.resolve(MergeStrategy::MinHash, None)?, None);
+
+        let (hash1, op1) = replica1.write_at(b"owner".to_vec(), BTreeSet::new(), 10)?;
+        let (hash2, op2) = replica2.write_at(b"user".to_vec(), BTreeSet::new(), 20)?;
+        let op2 = sign_register_op(op2, &user_keypair)?;
+        replica2.apply_op(sign_register_op(op1.clone(), &owner_keypair)?)?;
+        replica1.apply_op(op2.clone())?;
+
+        for replica in &[&replica1, &replica2] {
+            assert_eq!(
+                replica.resolve(MergeStrategy::LastWriterWins, None)?,
+                Some(b"user".to_vec())
+            );
+            assert_eq!(
+                replica.resolve(MergeStrategy::OwnerPreferred, None)?,
+                Some(b"owner".to_vec())
+            );
+            let min = if hash1 < hash2 { "owner" } else { "user" };
+            assert_eq!(
+                replica.resolve(MergeStrategy::MinHash, None)?,
+                Some(min.as_bytes().to_vec())
+            );
+        }
+
+        // the user signs its entry again with an earlier timestamp, and the owner's
+        // one without any: whatever the order the copies arrive in, both replicas
+        // keep all of them, so neither entry loses its latest time nor its author
+        let mut equivocated = op2.clone();
+        equivocated.timestamp = Some(5);
+        let equivocated = sign_register_op(equivocated, &user_keypair)?;
+        let mut resigned = op1.clone();
+        resigned.source = user_keypair.public_key();
+        resigned.timestamp = None;
+        let resigned = sign_register_op(resigned, &user_keypair)?;
+        replica1.apply_op(equivocated.clone())?;
+        replica1.apply_op(resigned.clone())?;
+        replica2.apply_op(resigned)?;
+        replica2.apply_op(equivocated)?;
+        replica2.apply_op(op2)?;
+        for replica in &[&replica1, &replica2] {
+            assert_eq!(
+                replica.resolve(MergeStrategy::LastWriterWins, None)?,
+                Some(b"user".to_vec())
+            );
+            assert_eq!(
+                replica.resolve(MergeStrategy::OwnerPreferred, None)?,
+                Some(b"owner".to_vec())
+            );
+            assert_eq!(replica.entry_authors(hash1, None)?.len(), 2);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_delete_entries() -> anyhow::Result<()> {
+        let register_name = XorName::random();
+        let register_tag = 43_666;
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replica1 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            None,
+        );
+        let mut replica2 = create_private_reg_replica_with(
+            register_name,
+            register_tag,
+            Some(owner_keypair.clone()),
+            None,
+        );
+
+        let (hash1, op1) = replica1.write(b"first".to_vec(), BTreeSet::new())?;
+        let (hash2, op2) = replica1.write(b"second".to_vec(), BTreeSet::new())?;
+        let delete_op = sign_register_delete_op(replica1.delete(hash2)?, &owner_keypair)?;
+        assert_eq!(replica1.get(hash2, None)?, None);
+        assert_eq!(
+            replica1.read(None)?,
+            vec![(hash1, b"first".to_vec())].into_iter().collect()
+        );
+
+        // the delete can't be replayed against another register of the same owner
+        let mut other_replica = create_private_reg_replica_with(
+            XorName::random(),
+            register_tag,
+            Some(owner_keypair.clone()),
+            None,
+        );
+        let mut replayed_op = delete_op.clone();
+        replayed_op.address = *other_replica.address();
+        assert_eq!(
+            other_replica.apply_delete_op(replayed_op),
+            Err(Error::InvalidSignature)
+        );
+
+        // the delete can arrive before the entry it deletes
+        replica2.apply_delete_op(delete_op.clone())?;
+        replica2.apply_delete_op(delete_op)?;
+        replica2.apply_op(sign_register_op(op2, &owner_keypair)?)?;
+        replica2.apply_op(sign_register_op(op1, &owner_keypair)?)?;
+        assert_eq!(replica1.read(None)?, replica2.read(None)?);
+        assert_eq!(replica2.size(None)?, 2);
+
+        assert_eq!(replica1.delete(hash2), Err(Error::NoSuchEntry));
+
+        // entries of public registers can't be deleted
+        let mut replicas = create_public_reg_replicas(1);
+        let (_, mut public_replica) = replicas.remove(0);
+        let (hash, _) = public_replica.write(b"public".to_vec(), BTreeSet::new())?;
+        assert_eq!(public_replica.delete(hash), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
     // Helpers for tests
 
     fn sign_register_op(mut op: RegisterOp<Entry>, keypair: &Keypair) -> Result<RegisterOp<Entry>> {
-        let bytes = utils::serialise(&op.crdt_op)?;
+        let bytes = op.bytes_to_sign()?;
         let signature = keypair.sign(&bytes);
         op.signature = Some(signature);
         Ok(op)
     }
 
+    fn sign_register_delete_op(
+        mut op: RegisterDeleteOp,
+        keypair: &Keypair,
+    ) -> Result<RegisterDeleteOp> {
+        let bytes = op.bytes_to_sign()?;
+        op.signature = Some(keypair.sign(&bytes));
+        Ok(op)
+    }
+
     fn gen_pub_reg_replicas(
         authority_keypair: Option<Keypair>,
         name: XorName,
@@ -699,11 +2546,20 @@ mod tests {
         replicas
     }
 
+    // Replicas with their own authority each, all allowed to write by a shared policy.
     fn create_public_reg_replicas(count: usize) -> Vec<(Keypair, Register)> {
         let register_name = XorName::random();
         let register_tag = 43_000;
+        let mut permissions = BTreeMap::default();
+        let _ = permissions.insert(User::Anyone, PublicPermissions::new(true));
+        let policy = PublicPolicy {
+            owner: Keypair::new_ed25519(&mut OsRng).public_key(),
+            permissions,
+            max_entries: None,
+            co_owners: BTreeSet::new(),
+        };
 
-        gen_pub_reg_replicas(None, register_name, register_tag, None, count)
+        gen_pub_reg_replicas(None, register_name, register_tag, Some(policy), count)
     }
 
     fn create_public_reg_replica_with(
@@ -769,12 +2625,16 @@ mod tests {
         let policy = PublicPolicy {
             owner,
             permissions: BTreeMap::default(),
+            max_entries: None,
+            co_owners: BTreeSet::new(),
         };
 
         (1..max_quantity + 1).prop_map(move |quantity| {
             let mut replicas = Vec::with_capacity(quantity);
             for _ in 0..quantity {
-                let replica = Register::new_public(owner, xorname, tag, Some(policy.clone()));
+                let mut replica = Register::new_public(owner, xorname, tag, Some(policy.clone()));
+                // ops are delivered to these replicas in any order
+                replica.allow_missing_parents(true);
 
                 replicas.push(replica);
             }
@@ -813,6 +2673,8 @@ mod tests {
             let policy = PublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             };
 
             let mut replicas = gen_pub_reg_replicas(
@@ -843,6 +2705,8 @@ mod tests {
             let policy = PublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             };
 
             // Instantiate the same Register on two replicas
@@ -882,6 +2746,8 @@ mod tests {
             let policy = PublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             };
 
             // Instantiate the same Register on two replicas
@@ -1022,6 +2888,8 @@ mod tests {
             let policy = PublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                max_entries: None,
+                co_owners: BTreeSet::new(),
             };
 
             // Instantiate the same Register on two replicas
@@ -1033,6 +2901,8 @@ mod tests {
                 2);
             let (_, mut replica1) = replicas.remove(0);
             let (_, mut replica2) = replicas.remove(0);
+            // some ops are dropped, so later ones may reference missing parents
+            replica2.allow_missing_parents(true);
 
             let dataset_length = dataset.len() as u64;
 
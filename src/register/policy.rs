@@ -7,13 +7,22 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::Action;
-use crate::{Error, PublicKey, Result};
+use super::{Action, Address};
+use crate::{utils, Error, Keypair, PublicKey, Result, Signature};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Debug, hash::Hash};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    hash::Hash,
+};
+use xor_name::XorName;
 
 /// Wrapper type for permissions, which can be public or private.
+///
+/// Encoded as a `PolicyEncoding`, so policies encoded with an earlier layout can
+/// still be decoded.
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
+#[serde(from = "PolicyEncoding", into = "PolicyEncoding")]
 pub enum Policy {
     /// Public permissions.
     Public(PublicPolicy),
@@ -21,6 +30,66 @@ pub enum Policy {
     Private(PrivatePolicy),
 }
 
+/// Layouts a `Policy` has been encoded with, the earliest first. Variants are only
+/// ever appended, for those encoded before to keep their tag.
+#[derive(Serialize, Deserialize)]
+enum PolicyEncoding {
+    /// Public policy from before `max_entries` and `co_owners` were added.
+    Public(PublicPolicyV0),
+    /// Private policy from before `max_entries` and `co_owners` were added.
+    Private(PrivatePolicyV0),
+    /// Public policy.
+    PublicV1(PublicPolicy),
+    /// Private policy.
+    PrivateV1(PrivatePolicy),
+}
+
+#[derive(Serialize, Deserialize)]
+struct PublicPolicyV0 {
+    owner: PublicKey,
+    permissions: BTreeMap<User, PublicPermissions>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PrivatePolicyV0 {
+    owner: PublicKey,
+    permissions: BTreeMap<PublicKey, PrivatePermissions>,
+}
+
+impl From<PolicyEncoding> for Policy {
+    fn from(encoding: PolicyEncoding) -> Self {
+        match encoding {
+            PolicyEncoding::Public(PublicPolicyV0 { owner, permissions }) => {
+                Self::Public(PublicPolicy {
+                    owner,
+                    permissions,
+                    max_entries: None,
+                    co_owners: BTreeSet::new(),
+                })
+            }
+            PolicyEncoding::Private(PrivatePolicyV0 { owner, permissions }) => {
+                Self::Private(PrivatePolicy {
+                    owner,
+                    permissions,
+                    max_entries: None,
+                    co_owners: BTreeSet::new(),
+                })
+            }
+            PolicyEncoding::PublicV1(policy) => Self::Public(policy),
+            PolicyEncoding::PrivateV1(policy) => Self::Private(policy),
+        }
+    }
+}
+
+impl From<Policy> for PolicyEncoding {
+    fn from(policy: Policy) -> Self {
+        match policy {
+            Policy::Public(policy) => Self::PublicV1(policy),
+            Policy::Private(policy) => Self::PrivateV1(policy),
+        }
+    }
+}
+
 impl Policy {
     /// Returns true if `action` is allowed for the provided user.
     pub fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
@@ -45,6 +114,112 @@ impl Policy {
             Policy::Private(policy) => policy.owner(),
         }
     }
+
+    /// Returns the keys given the same authority as the owner.
+    pub fn co_owners(&self) -> &BTreeSet<PublicKey> {
+        match self {
+            Policy::Public(policy) => &policy.co_owners,
+            Policy::Private(policy) => &policy.co_owners,
+        }
+    }
+
+    /// Returns true if `key` is the owner or one of the co-owners.
+    pub fn is_owner(&self, key: &PublicKey) -> bool {
+        self.owner() == key || self.co_owners().contains(key)
+    }
+
+    /// Returns the maximum number of entries, if bounded.
+    pub fn max_entries(&self) -> Option<u64> {
+        match self {
+            Policy::Public(policy) => policy.max_entries,
+            Policy::Private(policy) => policy.max_entries,
+        }
+    }
+}
+
+/// Identifies a policy of a Register: its version, and a hash telling it apart from
+/// the policies other owners set concurrently for the same version.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct PolicyId {
+    /// Version of the policy, 0 for the one the Register was constructed with.
+    pub version: u64,
+    /// Hash of the Register's address, the policy and the id of the one it replaced,
+    /// all zeros for the one the Register was constructed with.
+    pub hash: [u8; 32],
+}
+
+impl PolicyId {
+    /// Returns the id of the policy a Register is constructed with.
+    pub fn initial() -> Self {
+        Self {
+            version: 0,
+            hash: [0; 32],
+        }
+    }
+}
+
+/// Operation replacing a policy of a Register, applicable to other replicas.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct PolicyOp {
+    /// Address of a Register object on the network.
+    pub address: Address,
+    /// The new policy.
+    pub policy: Policy,
+    /// Id of the policy replaced, the new one being the next version.
+    pub replaces: PolicyId,
+    /// The PublicKey of the entity that generated the operation
+    pub source: PublicKey,
+    /// The signature of source on the address, policy and replaced id, required to apply the op
+    pub signature: Option<Signature>,
+}
+
+impl PolicyOp {
+    /// Returns the bytes the source is expected to sign.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&(&self.address, &self.policy, &self.replaces))
+    }
+
+    /// Returns the id of the new policy.
+    pub fn id(&self) -> Result<PolicyId> {
+        Ok(PolicyId {
+            version: self.replaces.version + 1,
+            hash: XorName::from_content(&[&self.bytes_to_sign()?]).0,
+        })
+    }
+}
+
+/// Capability granted by the owner or a co-owner of a private Register for a key
+/// to read it until some time, without adding that key to the policy.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct ReadCap {
+    /// Address of the Register the capability is for.
+    pub address: Address,
+    /// The key allowed to read the Register.
+    pub grantee: PublicKey,
+    /// Time after which the capability has lapsed.
+    pub expiry: u64,
+    /// The signature of the owner or a co-owner on the address, grantee and expiry.
+    pub owner_sig: Signature,
+}
+
+impl ReadCap {
+    /// Grants `grantee` to read the Register at `address` until `expiry`, signing the
+    /// capability with the keypair of the `owner`, or of one of the co-owners.
+    pub fn new(address: Address, grantee: PublicKey, expiry: u64, owner: &Keypair) -> Result<Self> {
+        let owner_sig = owner.sign(&utils::serialise(&(&address, &grantee, expiry))?);
+
+        Ok(Self {
+            address,
+            grantee,
+            expiry,
+            owner_sig,
+        })
+    }
+
+    /// Returns the bytes the owner or co-owner is expected to sign.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&(&self.address, &self.grantee, self.expiry))
+    }
 }
 
 impl From<PrivatePolicy> for Policy {
@@ -159,6 +334,12 @@ pub struct PublicPolicy {
     pub owner: PublicKey,
     /// Map of users to their public permission set.
     pub permissions: BTreeMap<User, PublicPermissions>,
+    /// Maximum number of entries that can be written to the Register, if bounded.
+    /// Ops from other replicas are applied as long as the entry they add has no more
+    /// entries in its causal past, itself included.
+    pub max_entries: Option<u64>,
+    /// Keys given the same authority as the owner.
+    pub co_owners: BTreeSet<PublicKey>,
 }
 
 impl PublicPolicy {
@@ -173,8 +354,9 @@ impl PublicPolicy {
     /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
     /// this action is not permitted.
     pub fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
-        // First checks if the requester is the owner.
-        if action == Action::Read || requester == self.owner {
+        // First checks if the requester is the owner or a co-owner.
+        if action == Action::Read || requester == self.owner || self.co_owners.contains(&requester)
+        {
             Ok(())
         } else {
             match self
@@ -207,14 +389,20 @@ pub struct PrivatePolicy {
     pub owner: PublicKey,
     /// Map of users to their private permission set.
     pub permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    /// Maximum number of entries that can be written to the Register, if bounded.
+    /// Ops from other replicas are applied as long as the entry they add has no more
+    /// entries in its causal past, itself included.
+    pub max_entries: Option<u64>,
+    /// Keys given the same authority as the owner.
+    pub co_owners: BTreeSet<PublicKey>,
 }
 
 impl PrivatePolicy {
     /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
     /// this action is not permitted.
     pub fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
-        // First checks if the requester is the owner.
-        if requester == self.owner {
+        // First checks if the requester is the owner or a co-owner.
+        if requester == self.owner || self.co_owners.contains(&requester) {
             Ok(())
         } else {
             match self.permissions.get(&requester) {
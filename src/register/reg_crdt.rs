@@ -7,7 +7,8 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::metadata::{Address, Entry};
+use super::metadata::{Address, BranchInfo, Entry, MergeStrategy};
+use super::policy::PolicyId;
 use crate::{
     Signature, {utils, Error, PublicKey, Result},
 };
@@ -18,10 +19,12 @@ use crdts::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display},
-    hash::Hash,
+    hash::{Hash, Hasher},
 };
+use xor_name::XorName;
 
 /// CRDT Data operation applicable to other Register replica.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -34,15 +37,186 @@ pub struct CrdtOperation<T> {
     pub source: PublicKey,
     /// The signature of source on the crdt_top, required to apply the op
     pub signature: Option<Signature>,
+    /// Time the source claims to have written the entry at, if it provided one.
+    pub timestamp: Option<u64>,
+    /// Id of the policy the write was authorised under.
+    pub policy: PolicyId,
+}
+
+impl<T: Serialize> CrdtOperation<T> {
+    /// Returns the bytes the source is expected to sign, i.e. the `address`, `crdt_op`,
+    /// `timestamp` and `policy`.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        let bytes = utils::serialise(&(&self.address, &self.crdt_op, self.timestamp, &self.policy));
+        bytes.map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT operation to verify signature: {}",
+                err
+            ))
+        })
+    }
+}
+
+/// CRDT operation adding several entries to a Register at once, applicable
+/// atomically to other replicas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrdtBatchOperation<T> {
+    /// Address of a Register object on the network.
+    pub address: Address,
+    /// The data operations to apply, in order.
+    pub crdt_ops: Vec<Node<T>>,
+    /// The PublicKey of the entity that generated the operation
+    pub source: PublicKey,
+    /// The signature of source on the crdt_ops, required to apply the op
+    pub signature: Option<Signature>,
+    /// Id of the policy the writes were authorised under.
+    pub policy: PolicyId,
+}
+
+impl<T: Serialize> CrdtBatchOperation<T> {
+    /// Returns the bytes the source is expected to sign, i.e. the `address`,
+    /// all the `crdt_ops` and the `policy`.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&(&self.address, &self.crdt_ops, &self.policy)).map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT operation to verify signature: {}",
+                err
+            ))
+        })
+    }
+
+    /// Returns the id of the batch, which the entries it adds are recorded with.
+    pub fn id(&self) -> Result<[u8; 32]> {
+        Ok(XorName::from_content(&[&self.bytes_to_sign()?]).0)
+    }
+}
+
+/// Signed CRDT operation bringing entries to a Register replica, either on its own
+/// or as part of a batch, as handed over to bring another replica up to date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CrdtSyncOperation<T> {
+    /// An op adding a single entry.
+    Single(CrdtOperation<T>),
+    /// An op adding several entries at once.
+    Batch(CrdtBatchOperation<T>),
+}
+
+impl<T> CrdtSyncOperation<T> {
+    /// Returns the data operations of the entries the op adds.
+    pub fn crdt_ops(&self) -> Vec<&Node<T>> {
+        match self {
+            Self::Single(op) => vec![&op.crdt_op],
+            Self::Batch(op) => op.crdt_ops.iter().collect(),
+        }
+    }
+}
+
+/// CRDT operation tombstoning an entry, applicable to other Register replicas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct CrdtDeleteOperation {
+    /// Address of a Register object on the network.
+    pub address: Address,
+    /// The hash of the entry to tombstone.
+    pub hash: EntryHash,
+    /// The PublicKey of the entity that generated the operation
+    pub source: PublicKey,
+    /// The signature of source on the address, hash and policy, required to apply the op
+    pub signature: Option<Signature>,
+    /// Id of the policy the delete was authorised under.
+    pub policy: PolicyId,
+}
+
+impl CrdtDeleteOperation {
+    /// Returns the bytes the source is expected to sign, i.e. the `address`, `hash` and
+    /// `policy`, so the op can't be replayed against another Register of the same owner.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&(&self.address, &self.hash, &self.policy)).map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT operation to verify signature: {}",
+                err
+            ))
+        })
+    }
+}
+
+/// Author of an entry, along with the timestamp and signature of an op which brought it,
+/// kept together so the signed op can be rebuilt and verified.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct Provenance {
+    author: PublicKey,
+    timestamp: Option<u64>,
+    /// The signature of an entry brought on its own, `None` for one brought in a batch
+    /// or written here whose signed op hasn't been applied back yet.
+    signature: Option<Signature>,
+    policy: PolicyId,
+    /// The id of the signed batch the entry was brought in, if it was.
+    batch: Option<[u8; 32]>,
+}
+
+impl Provenance {
+    fn is_signed(&self) -> bool {
+        self.signature.is_some() || self.batch.is_some()
+    }
+}
+
+/// Signed batch of entries, kept for the entries brought in it to be handed over
+/// to other replicas and verified.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+struct Batch {
+    source: PublicKey,
+    signature: Signature,
+    policy: PolicyId,
+    /// The hashes of the entries of the batch, in order.
+    hashes: Vec<EntryHash>,
 }
 
 /// Register data type as a CRDT with Access Control
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RegisterCrdt {
     /// Address on the network of this piece of data
     address: Address,
     /// CRDT to store the actual data, i.e. the items of the Register.
     data: MerkleReg<Entry>,
+    /// The provenance of each entry, from every signed op which brought it to this
+    /// replica, or from its unsigned op if it was written here and its signed op hasn't
+    /// been applied back yet. Also used to tell the entries already held.
+    provenance: BTreeMap<EntryHash, BTreeSet<Provenance>>,
+    /// The signed batches the provenance of some entry refers to, by id.
+    batches: BTreeMap<[u8; 32], Batch>,
+    /// Whether ops referencing parents not held yet are applied, leaving them dangling
+    /// until the parents arrive, instead of being rejected.
+    allow_missing_parents: bool,
+    /// Entries deleted, which remain in the DAG for their descendants
+    /// but are no longer read.
+    tombstones: BTreeSet<EntryHash>,
+    /// The signed op of the first delete applied for each entry deleted, to hand the
+    /// deletes over to other replicas.
+    delete_ops: BTreeMap<EntryHash, CrdtDeleteOperation>,
+    /// Hashes of the entries added by each op applied, in order, to read the Register
+    /// as it was after a given number of them. Deletes aren't recorded.
+    history: Vec<Vec<EntryHash>>,
+}
+
+// Replicas are compared on the entries they hold and those deleted, leaving out
+// how and in which order the entries were brought to each of them.
+impl PartialEq for RegisterCrdt {
+    fn eq(&self, other: &Self) -> bool {
+        self.state() == other.state()
+    }
+}
+
+impl Eq for RegisterCrdt {}
+
+impl Hash for RegisterCrdt {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.state().hash(state)
+    }
+}
+
+impl PartialOrd for RegisterCrdt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.state().partial_cmp(&other.state())
+    }
 }
 
 impl Display for RegisterCrdt {
@@ -58,13 +232,69 @@ impl Display for RegisterCrdt {
     }
 }
 
+/// Layout of a `RegisterCrdt` encoded before the provenance of its entries was kept.
+#[derive(Deserialize)]
+pub(super) struct RegisterCrdtV0 {
+    address: Address,
+    data: MerkleReg<Entry>,
+}
+
 impl RegisterCrdt {
     /// Constructs a new 'RegisterCrdt'.
     pub fn new(address: Address) -> Self {
         Self {
             address,
             data: MerkleReg::new(),
+            provenance: BTreeMap::new(),
+            batches: BTreeMap::new(),
+            allow_missing_parents: false,
+            tombstones: BTreeSet::new(),
+            delete_ops: BTreeMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Constructs a 'RegisterCrdt' from one decoded with its earliest layout, recording
+    /// each of its entries as written by `author` under the initial policy, parents first.
+    /// Entries whose parents are missing stay in the DAG, but are only recorded once
+    /// their op is applied again.
+    pub(super) fn from_v0(legacy: RegisterCrdtV0, author: PublicKey) -> Self {
+        let mut crdt = Self::new(legacy.address);
+        crdt.data = legacy.data;
+
+        // Walk the DAG down from its heads, recording each entry once its parents are.
+        let mut visited = BTreeSet::new();
+        let mut pending: Vec<_> = crdt.heads().into_iter().map(|hash| (hash, false)).collect();
+        while let Some((hash, parents_recorded)) = pending.pop() {
+            if parents_recorded {
+                crdt.add_provenance(
+                    hash,
+                    Provenance {
+                        author,
+                        timestamp: None,
+                        signature: None,
+                        policy: PolicyId::initial(),
+                        batch: None,
+                    },
+                );
+                crdt.history.push(vec![hash]);
+                continue;
+            }
+            if !visited.insert(hash) {
+                continue;
+            }
+            if let Some(node) = crdt.data.node(hash) {
+                pending.push((hash, true));
+                pending.extend(
+                    node.parents
+                        .iter()
+                        .filter(|parent| !visited.contains(*parent))
+                        .map(|parent| (*parent, false)),
+                );
+            }
         }
+
+        crdt
     }
 
     /// Returns the address.
@@ -72,24 +302,50 @@ impl RegisterCrdt {
         &self.address
     }
 
+    /// Sets whether ops referencing parents not held yet are applied, e.g. when ops
+    /// are known to be delivered out of order, or rejected with `Error::MissingParents`.
+    pub fn allow_missing_parents(&mut self, allow: bool) {
+        self.allow_missing_parents = allow;
+    }
+
     /// Returns total number of items in the register.
     pub fn size(&self) -> u64 {
         (self.data.num_nodes() + self.data.num_orphans()) as u64
     }
 
-    /// Write a new entry to the RegisterCrdt, returning the hash
-    /// of the entry and the CRDT operation without a signature
+    /// Returns the number of bytes taken by all the entries in the register,
+    /// including the overhead of the DAG structure linking them.
+    pub fn size_in_bytes(&self) -> u64 {
+        bincode::serialized_size(&self.data).unwrap_or(u64::MAX)
+    }
+
+    /// Write a new entry to the RegisterCrdt, optionally timestamped, authorised
+    /// under the policy with the id `policy`, returning the hash of the entry and
+    /// the CRDT operation without a signature
     pub fn write(
         &mut self,
         entry: Entry,
         parents: BTreeSet<EntryHash>,
         source: PublicKey,
+        timestamp: Option<u64>,
+        policy: PolicyId,
     ) -> Result<(EntryHash, CrdtOperation<Entry>)> {
         let address = *self.address();
 
         let crdt_op = self.data.write(entry, parents);
         self.data.apply(crdt_op.clone());
         let hash = crdt_op.hash();
+        self.add_provenance(
+            hash,
+            Provenance {
+                author: source,
+                timestamp,
+                signature: None,
+                policy,
+                batch: None,
+            },
+        );
+        self.history.push(vec![hash]);
 
         // We return the operation as it may need to be broadcasted to other replicas
         let op = CrdtOperation {
@@ -97,46 +353,694 @@ impl RegisterCrdt {
             crdt_op,
             source,
             signature: None,
+            timestamp,
+            policy,
         };
 
         Ok((hash, op))
     }
 
+    /// Write several entries to the RegisterCrdt, in order, authorised under the policy
+    /// with the id `policy`, returning their hashes and the single CRDT operation adding
+    /// them all, without a signature.
+    pub fn write_batch(
+        &mut self,
+        entries: Vec<(Entry, BTreeSet<EntryHash>)>,
+        source: PublicKey,
+        policy: PolicyId,
+    ) -> Result<(Vec<EntryHash>, CrdtBatchOperation<Entry>)> {
+        let mut hashes = Vec::with_capacity(entries.len());
+        let mut crdt_ops = Vec::with_capacity(entries.len());
+        for (entry, parents) in entries {
+            let crdt_op = self.data.write(entry, parents);
+            self.data.apply(crdt_op.clone());
+            let hash = crdt_op.hash();
+            self.add_provenance(
+                hash,
+                Provenance {
+                    author: source,
+                    timestamp: None,
+                    signature: None,
+                    policy,
+                    batch: None,
+                },
+            );
+            hashes.push(hash);
+            crdt_ops.push(crdt_op);
+        }
+        self.history.push(hashes.clone());
+
+        let op = CrdtBatchOperation {
+            address: self.address,
+            crdt_ops,
+            source,
+            signature: None,
+            policy,
+        };
+
+        Ok((hashes, op))
+    }
+
+    /// Apply a remote CRDT operation adding several entries to this replica of
+    /// the RegisterCrdt, either all of them or none if it fails.
+    /// The batch is kept along with the entries, so `ops_since` can hand it over
+    /// and `verify_entry` check them against its signature.
+    pub fn apply_batch_op(&mut self, op: CrdtBatchOperation<Entry>) -> Result<()> {
+        let sig = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        sig.verify_strict(&op.source, &op.bytes_to_sign()?)?;
+        let id = op.id()?;
+
+        if self.address != op.address {
+            return Err(Error::CrdtWrongAddress(op.address));
+        }
+
+        // The parents of an entry may be added by the entries before it in the batch.
+        if !self.allow_missing_parents {
+            let mut batch = BTreeSet::new();
+            let mut hashes = BTreeSet::new();
+            for crdt_op in &op.crdt_ops {
+                hashes.extend(
+                    crdt_op
+                        .parents
+                        .iter()
+                        .filter(|parent| {
+                            self.data.node(**parent).is_none() && !batch.contains(*parent)
+                        })
+                        .copied(),
+                );
+                let _ = batch.insert(crdt_op.hash());
+            }
+            if !hashes.is_empty() {
+                return Err(Error::MissingParents { hashes });
+            }
+        }
+
+        let batch = Batch {
+            source: op.source,
+            signature: sig.clone(),
+            policy: op.policy,
+            hashes: op.crdt_ops.iter().map(|crdt_op| crdt_op.hash()).collect(),
+        };
+        let mut added = vec![];
+        for crdt_op in op.crdt_ops {
+            let hash = crdt_op.hash();
+            let provenance = Provenance {
+                author: op.source,
+                timestamp: None,
+                signature: None,
+                policy: op.policy,
+                batch: Some(id),
+            };
+            // Entries already held, e.g. brought by another op, are also
+            // recorded as brought in the batch.
+            let held = self.provenance.contains_key(&hash);
+            self.add_provenance(hash, provenance);
+            if !held {
+                self.data.apply(crdt_op);
+                added.push(hash);
+            }
+        }
+        if !added.is_empty() {
+            self.history.push(added);
+        }
+        let _ = self.batches.insert(id, batch);
+
+        Ok(())
+    }
+
     /// Apply a remote data CRDT operation to this replica of the RegisterCrdt.
+    /// The same entry can come in ops signed by different authors or with different
+    /// timestamps: the provenance of each of them is kept, so none of them can take
+    /// the place of another, and replicas which applied the same ops agree on it.
     pub fn apply_op(&mut self, op: CrdtOperation<Entry>) -> Result<()> {
         // Let's first check the op is validly signed.
         // Note: Perms for the op are checked at the upper Register layer.
+        let bytes = op.bytes_to_sign()?;
         let sig = op.signature.ok_or(Error::CrdtMissingOpSignature)?;
-        let bytes_to_verify = utils::serialise(&op.crdt_op).map_err(|err| {
-            Error::Serialisation(format!(
-                "Could not serialise CRDT operation to verify signature: {}",
-                err
-            ))
-        })?;
-        op.source.verify(&sig, &bytes_to_verify)?;
+        sig.verify_strict(&op.source, &bytes)?;
 
         // Check the targetting address is correct
         if self.address != op.address {
             return Err(Error::CrdtWrongAddress(op.address));
         }
 
+        // An op delivered more than once is a no-op, so callers with at-least-once
+        // delivery don't need to dedupe. The entries held are the set of ops seen,
+        // which is bounded by the Register's size.
+        let hash = op.crdt_op.hash();
+        let provenance = Provenance {
+            author: op.source,
+            timestamp: op.timestamp,
+            signature: Some(sig),
+            policy: op.policy,
+            batch: None,
+        };
+        if self.provenance.contains_key(&hash) {
+            // E.g. the signed op of an entry written here, only known once it comes back.
+            self.add_provenance(hash, provenance);
+            return Ok(());
+        }
+
+        if !self.allow_missing_parents {
+            let hashes: BTreeSet<_> = op
+                .crdt_op
+                .parents
+                .iter()
+                .filter(|parent| self.data.node(**parent).is_none())
+                .copied()
+                .collect();
+            if !hashes.is_empty() {
+                return Err(Error::MissingParents { hashes });
+            }
+        }
+
         // Apply the CRDT operation to the Register
         self.data.apply(op.crdt_op);
+        self.add_provenance(hash, provenance);
+        self.history.push(vec![hash]);
+
+        Ok(())
+    }
+
+    /// Returns the hashes of the entries held, for another replica to work out
+    /// with `ops_since` the ops this one is missing.
+    pub fn digest(&self) -> BTreeSet<EntryHash> {
+        self.nodes().map(|(hash, _)| hash).collect()
+    }
+
+    /// Returns the signed ops of the entries held which are not in `digest`, every op
+    /// which brought each of them, ops adding parents before those adding their children.
+    /// A batch is handed over whole if any of the entries brought in it is missing.
+    /// Entries whose signed op this replica hasn't seen, e.g. written here but not
+    /// applied back once signed, are left out.
+    pub fn ops_since(&self, digest: &BTreeSet<EntryHash>) -> Vec<CrdtSyncOperation<Entry>> {
+        let mut ops = vec![];
+        // The indices in `ops` of the ops handed over for each entry, and of each batch.
+        let mut op_index: BTreeMap<EntryHash, Vec<usize>> = BTreeMap::new();
+        let mut batch_index = BTreeMap::new();
+        for (hash, node) in self.nodes().filter(|(hash, _)| !digest.contains(hash)) {
+            for provenance in self.provenance.get(&hash).into_iter().flatten() {
+                let index = match provenance.batch {
+                    Some(id) => match batch_index.get(&id) {
+                        Some(index) => *index,
+                        None => match self.signed_batch_op(id) {
+                            Some(op) => {
+                                ops.push(CrdtSyncOperation::Batch(op));
+                                let _ = batch_index.insert(id, ops.len() - 1);
+                                ops.len() - 1
+                            }
+                            None => continue,
+                        },
+                    },
+                    None => match self.signed_op(provenance, node) {
+                        Some(op) => {
+                            ops.push(CrdtSyncOperation::Single(op));
+                            ops.len() - 1
+                        }
+                        None => continue,
+                    },
+                };
+                op_index.entry(hash).or_default().push(index);
+            }
+        }
+
+        // Order the ops topologically, each after those adding the parents of its entries.
+        let mut dependants = vec![BTreeSet::new(); ops.len()];
+        let mut pending = vec![0; ops.len()];
+        for (index, op) in ops.iter().enumerate() {
+            let dependencies: BTreeSet<usize> = op
+                .crdt_ops()
+                .into_iter()
+                .flat_map(|crdt_op| crdt_op.parents.iter())
+                .filter_map(|parent| op_index.get(parent))
+                .flatten()
+                .copied()
+                .filter(|dependency| *dependency != index)
+                .collect();
+            pending[index] = dependencies.len();
+            for dependency in dependencies {
+                let _ = dependants[dependency].insert(index);
+            }
+        }
+        let mut ready: BTreeSet<usize> = (0..ops.len()).filter(|i| pending[*i] == 0).collect();
+        let mut order = Vec::with_capacity(ops.len());
+        while let Some(index) = ready.iter().next().copied() {
+            let _ = ready.remove(&index);
+            order.push(index);
+            for dependant in &dependants[index] {
+                pending[*dependant] -= 1;
+                if pending[*dependant] == 0 {
+                    let _ = ready.insert(*dependant);
+                }
+            }
+        }
+        // Ops depending on each other, which can't be ordered, are left for last.
+        order.extend((0..ops.len()).filter(|index| pending[*index] > 0));
+
+        let mut ops: Vec<_> = ops.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|index| ops[index].take())
+            .collect()
+    }
+
+    /// Returns the signed ops of the entries held which are neither in `known` nor
+    /// ancestors of those, parents before their children, e.g. for a client which
+    /// only kept the heads it last read to catch up on what was written since.
+    pub fn missing_since(&self, known: &BTreeSet<EntryHash>) -> Vec<CrdtSyncOperation<Entry>> {
+        let mut dominated = BTreeSet::new();
+        let mut stack: Vec<EntryHash> = known.iter().copied().collect();
+        while let Some(hash) = stack.pop() {
+            if !dominated.insert(hash) {
+                continue;
+            }
+            if let Some(node) = self.data.node(hash) {
+                stack.extend(node.parents.iter().copied());
+            }
+        }
+
+        self.ops_since(&dominated)
+    }
+
+    /// Returns the authors of the entry with the provided `hash`, i.e. the sources of
+    /// all the ops which brought it to this replica, empty if it isn't held.
+    pub fn entry_authors(&self, hash: EntryHash) -> BTreeSet<PublicKey> {
+        self.provenance
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .map(|provenance| provenance.author)
+            .collect()
+    }
+
+    /// Verifies the entry with the provided `hash` against the signatures of its authors
+    /// on each op or batch which brought it, failing with `Error::CrdtMissingOpSignature`
+    /// if this replica hasn't seen it signed, e.g. written here but not applied back.
+    pub fn verify_entry(&self, hash: EntryHash) -> Result<()> {
+        let node = self.data.node(hash).ok_or(Error::NoSuchEntry)?;
+        let provenances = self.provenance.get(&hash).ok_or(Error::NoSuchEntry)?;
+        for provenance in provenances {
+            let (source, sig, bytes) = match provenance.batch {
+                Some(id) => {
+                    let op = self
+                        .signed_batch_op(id)
+                        .ok_or(Error::CrdtMissingOpSignature)?;
+                    let bytes = op.bytes_to_sign()?;
+                    (op.source, op.signature, bytes)
+                }
+                None => {
+                    let op = self
+                        .signed_op(provenance, node)
+                        .ok_or(Error::CrdtMissingOpSignature)?;
+                    let bytes = op.bytes_to_sign()?;
+                    (op.source, op.signature, bytes)
+                }
+            };
+            let sig = sig.ok_or(Error::CrdtMissingOpSignature)?;
+            sig.verify_strict(&source, &bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tombstone the entry with the provided `hash`, authorised under the policy with
+    /// the id `policy`, returning the CRDT operation without a signature.
+    pub fn delete(
+        &mut self,
+        hash: EntryHash,
+        source: PublicKey,
+        policy: PolicyId,
+    ) -> Result<CrdtDeleteOperation> {
+        if self.get(hash).is_none() {
+            return Err(Error::NoSuchEntry);
+        }
+        let _ = self.tombstones.insert(hash);
+
+        Ok(CrdtDeleteOperation {
+            address: self.address,
+            hash,
+            source,
+            signature: None,
+            policy,
+        })
+    }
+
+    /// Apply a remote delete operation to this replica of the RegisterCrdt.
+    /// Tombstones only ever accumulate, so the op can be applied before the entry
+    /// it deletes arrives, and applying it more than once is a no-op.
+    pub fn apply_delete_op(&mut self, op: CrdtDeleteOperation) -> Result<()> {
+        let sig = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        sig.verify_strict(&op.source, &op.bytes_to_sign()?)?;
+
+        if self.address != op.address {
+            return Err(Error::CrdtWrongAddress(op.address));
+        }
+
+        let _ = self.tombstones.insert(op.hash);
+        let _ = self.delete_ops.entry(op.hash).or_insert(op);
 
         Ok(())
     }
 
-    /// Get the entry corresponding to the provided `hash` if it exists.
+    /// Returns the signed delete ops applied to this replica, one per entry deleted.
+    pub fn delete_ops(&self) -> impl Iterator<Item = &CrdtDeleteOperation> {
+        self.delete_ops.values()
+    }
+
+    /// Returns the hashes of the entries held along with a signed op or batch,
+    /// i.e. those `ops_since` can hand over unless their parents are missing.
+    pub fn signed_hashes(&self) -> impl Iterator<Item = EntryHash> + '_ {
+        self.provenance
+            .iter()
+            .filter(|(_, provenances)| provenances.iter().any(Provenance::is_signed))
+            .map(|(hash, _)| *hash)
+    }
+
+    /// Returns whether the entry with the provided `hash` is held, even if deleted.
+    pub fn holds(&self, hash: EntryHash) -> bool {
+        self.provenance.contains_key(&hash)
+    }
+
+    /// Returns the largest number of entries any of the provided `crdt_ops` would have
+    /// in its causal past once applied, i.e. itself along with all of its ancestors,
+    /// those added by the ops before it counted in. Unlike the size of the replica, it
+    /// doesn't depend on the order concurrent ops are applied in.
+    /// Ancestors not held are reported with `Error::MissingParents`.
+    pub fn causal_len(&self, crdt_ops: &[Node<Entry>]) -> Result<u64> {
+        let batch: BTreeMap<EntryHash, &Node<Entry>> = crdt_ops
+            .iter()
+            .map(|crdt_op| (crdt_op.hash(), crdt_op))
+            .collect();
+
+        let mut len = 0;
+        let mut missing = BTreeSet::new();
+        for crdt_op in crdt_ops {
+            let mut seen = BTreeSet::new();
+            let mut pending: Vec<EntryHash> = crdt_op.parents.iter().copied().collect();
+            while let Some(hash) = pending.pop() {
+                if !seen.insert(hash) {
+                    continue;
+                }
+                match self.data.node(hash).or_else(|| batch.get(&hash).copied()) {
+                    Some(node) => pending.extend(node.parents.iter().copied()),
+                    None => {
+                        let _ = missing.insert(hash);
+                    }
+                }
+            }
+            let ancestors = seen.iter().filter(|hash| !missing.contains(*hash)).count();
+            len = len.max(ancestors as u64 + 1);
+        }
+
+        if missing.is_empty() {
+            Ok(len)
+        } else {
+            Err(Error::MissingParents { hashes: missing })
+        }
+    }
+
+    /// Get the entry corresponding to the provided `hash` if it exists and wasn't deleted.
     pub fn get(&self, hash: EntryHash) -> Option<&Entry> {
+        if self.tombstones.contains(&hash) {
+            return None;
+        }
         self.data.node(hash).map(|node| &node.value)
     }
 
-    /// Read the last entry, or entries if there are branches.
+    /// Read the last entry, or entries if there are branches, leaving out deleted ones.
     pub fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.data
             .read()
             .hashes_and_nodes()
+            .filter(|(hash, _)| !self.tombstones.contains(hash))
             .map(|(hash, node)| (hash, node.value.clone()))
             .collect()
     }
+
+    /// Returns the number of ops which added entries to this replica so far.
+    pub fn op_count(&self) -> u64 {
+        self.history.len() as u64
+    }
+
+    /// Returns the number of entries held after the first `version` ops were applied,
+    /// if this replica applied that many.
+    pub fn size_at(&self, version: u64) -> Option<u64> {
+        let ops = self.history.get(..version as usize)?;
+        Some(ops.iter().map(Vec::len).sum::<usize>() as u64)
+    }
+
+    /// Read the last entries as they were after the first `version` ops were applied,
+    /// if this replica applied that many. Deletes aren't versioned, so the entries
+    /// deleted since are read as well.
+    pub fn read_at(&self, version: u64) -> Option<BTreeSet<(EntryHash, Entry)>> {
+        let ops = self.history.get(..version as usize)?;
+        let nodes: Vec<_> = ops
+            .iter()
+            .flatten()
+            .filter_map(|hash| self.data.node(*hash).map(|node| (*hash, node)))
+            .collect();
+        // The heads are the entries none of the others were written on top of.
+        let parents: BTreeSet<_> = nodes
+            .iter()
+            .flat_map(|(_, node)| node.parents.iter().copied())
+            .collect();
+
+        Some(
+            nodes
+                .into_iter()
+                .filter(|(hash, _)| !parents.contains(hash))
+                .map(|(hash, node)| (hash, node.value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Picks a single entry out of the current ones according to `strategy`,
+    /// `is_owner` telling the keys `MergeStrategy::OwnerPreferred` prefers.
+    /// Ties are broken by the lowest hash, so replicas holding the same entries
+    /// pick the same one.
+    pub fn resolve(
+        &self,
+        strategy: MergeStrategy,
+        is_owner: impl Fn(&PublicKey) -> bool,
+    ) -> Option<Entry> {
+        // Heads are ordered by hash, so the first one found has the lowest hash.
+        let mut heads = self.read().into_iter();
+        let head = match strategy {
+            MergeStrategy::MinHash => heads.next(),
+            MergeStrategy::LastWriterWins => {
+                heads.max_by_key(|(hash, _)| (self.timestamp(*hash), Reverse(*hash)))
+            }
+            MergeStrategy::OwnerPreferred => {
+                let all: Vec<_> = heads.collect();
+                all.iter()
+                    .find(|(hash, _)| self.entry_authors(*hash).iter().any(&is_owner))
+                    .or_else(|| all.first())
+                    .cloned()
+            }
+        };
+        head.map(|(_, entry)| entry)
+    }
+
+    /// Counts the leaves of the DAG of entries and the length of their lineages.
+    pub fn branch_info(&self) -> BranchInfo {
+        let mut lengths = BTreeMap::new();
+        let lineages: BTreeMap<_, _> = self
+            .heads()
+            .into_iter()
+            .map(|head| (head, self.lineage_length(head, &mut lengths)))
+            .collect();
+
+        BranchInfo {
+            leaves: lineages.len() as u64,
+            depth: lineages.values().copied().max().unwrap_or(0),
+            lineages,
+        }
+    }
+
+    /// Renders the DAG of entries in the DOT format, with the hash, author and value of
+    /// each entry, and an edge from each entry to each of its parents. Current heads are
+    /// drawn with a double border. The output only depends on the entries held.
+    pub fn to_dot(&self) -> String {
+        let heads = self.heads();
+        let mut dot = "digraph Register {\n".to_string();
+        for (hash, node) in self.nodes() {
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\\nauthor: {}\\n{}\"{}];\n",
+                hex::encode(hash),
+                short_hex(&hash),
+                self.author(hash),
+                String::from_utf8_lossy(&node.value).escape_default(),
+                if heads.contains(&hash) {
+                    ", peripheries=2"
+                } else {
+                    ""
+                }
+            ));
+            for parent in &node.parents {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    hex::encode(hash),
+                    hex::encode(parent)
+                ));
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Summarises the DAG of entries, one line per entry with its hash,
+    /// author and parents, marking the current heads.
+    pub fn summary(&self) -> String {
+        let heads = self.heads();
+        self.nodes()
+            .map(|(hash, node)| {
+                let parents: Vec<_> = node.parents.iter().map(|p| short_hex(p)).collect();
+                format!(
+                    "{} by {} <- [{}]{}",
+                    short_hex(&hash),
+                    self.author(hash),
+                    parents.join(", "),
+                    if heads.contains(&hash) { " (head)" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // The state replicas converge on, which they're compared on.
+    fn state(&self) -> (&Address, &MerkleReg<Entry>, &BTreeSet<EntryHash>) {
+        (&self.address, &self.data, &self.tombstones)
+    }
+
+    // Entries held, ordered by hash.
+    fn nodes(&self) -> impl Iterator<Item = (EntryHash, &Node<Entry>)> {
+        self.provenance
+            .keys()
+            .filter_map(move |hash| self.data.node(*hash).map(|node| (*hash, node)))
+    }
+
+    fn heads(&self) -> BTreeSet<EntryHash> {
+        self.data
+            .read()
+            .hashes_and_nodes()
+            .map(|(hash, _)| hash)
+            .collect()
+    }
+
+    // Rebuilds the signed op with the given `provenance` which brought the entry `node`,
+    // if its signature is known.
+    fn signed_op(
+        &self,
+        provenance: &Provenance,
+        node: &Node<Entry>,
+    ) -> Option<CrdtOperation<Entry>> {
+        Some(CrdtOperation {
+            address: self.address,
+            crdt_op: node.clone(),
+            source: provenance.author,
+            signature: Some(provenance.signature.clone()?),
+            timestamp: provenance.timestamp,
+            policy: provenance.policy,
+        })
+    }
+
+    // Rebuilds the signed batch op with the given `id`, if it's held.
+    fn signed_batch_op(&self, id: [u8; 32]) -> Option<CrdtBatchOperation<Entry>> {
+        let batch = self.batches.get(&id)?;
+        let crdt_ops = batch
+            .hashes
+            .iter()
+            .map(|hash| self.data.node(*hash).cloned())
+            .collect::<Option<_>>()?;
+        Some(CrdtBatchOperation {
+            address: self.address,
+            crdt_ops,
+            source: batch.source,
+            signature: Some(batch.signature.clone()),
+            policy: batch.policy,
+        })
+    }
+
+    // Keeps `provenance` along with the others of the entry with the given `hash`.
+    // The unsigned provenance of an entry written here is dropped once its author's
+    // signed op is applied, and isn't kept along with a signed one of the same author.
+    fn add_provenance(&mut self, hash: EntryHash, provenance: Provenance) {
+        let provenances = self.provenance.entry(hash).or_default();
+        if provenance.is_signed() {
+            provenances.retain(|held| held.is_signed() || held.author != provenance.author);
+        } else if provenances
+            .iter()
+            .any(|held| held.is_signed() && held.author == provenance.author)
+        {
+            return;
+        }
+        let _ = provenances.insert(provenance);
+    }
+
+    // Number of entries in the longest chain from `hash` back to a root, not counting
+    // missing parents. The lengths computed on the way are kept in `lengths`.
+    fn lineage_length(&self, hash: EntryHash, lengths: &mut BTreeMap<EntryHash, u64>) -> u64 {
+        let mut stack = vec![hash];
+        while let Some(&current) = stack.last() {
+            if lengths.contains_key(&current) {
+                let _ = stack.pop();
+                continue;
+            }
+            let parents: Vec<EntryHash> = self
+                .data
+                .node(current)
+                .map(|node| node.parents.iter().copied().collect())
+                .unwrap_or_default();
+            let pending: Vec<EntryHash> = parents
+                .iter()
+                .filter(|parent| {
+                    self.data.node(**parent).is_some() && !lengths.contains_key(*parent)
+                })
+                .copied()
+                .collect();
+            if pending.is_empty() {
+                let longest = parents
+                    .iter()
+                    .filter_map(|parent| lengths.get(parent))
+                    .max()
+                    .copied()
+                    .unwrap_or(0);
+                let _ = lengths.insert(current, longest + 1);
+                let _ = stack.pop();
+            } else {
+                stack.extend(pending);
+            }
+        }
+        lengths.get(&hash).copied().unwrap_or(0)
+    }
+
+    fn author(&self, hash: EntryHash) -> String {
+        let authors: Vec<_> = self
+            .entry_authors(hash)
+            .iter()
+            .map(|author| short_hex(&author.to_bytes()))
+            .collect();
+        if authors.is_empty() {
+            "unknown".to_string()
+        } else {
+            authors.join(", ")
+        }
+    }
+
+    // Latest time the authors of the entry claim to have written it at, if any of them
+    // provided one.
+    fn timestamp(&self, hash: EntryHash) -> Option<u64> {
+        self.provenance
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|provenance| provenance.timestamp)
+            .max()
+    }
+}
+
+// Private helpers
+
+fn short_hex(bytes: &[u8]) -> String {
+    hex::encode(&bytes[..std::cmp::min(4, bytes.len())])
 }
@@ -14,6 +14,35 @@ use serde::{Deserialize, Serialize};
 /// it has been relocated between sections.
 pub type NodeAge = u8;
 
+/// A churn event affecting the age of a node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum ChurnEvent {
+    /// The node was relocated to another section.
+    Relocated,
+    /// The section of the node split in two.
+    SectionSplit,
+    /// The node was penalised for misbehaving.
+    Penalty,
+}
+
+/// Progression of a node's age through churn events.
+pub trait AgeProgression {
+    /// Returns the age of the node after the churn event.
+    fn progress(self, on: ChurnEvent) -> NodeAge;
+}
+
+impl AgeProgression for NodeAge {
+    /// A relocation ages the node by one (saturating at the maximum age),
+    /// a split leaves the age untouched, and a penalty halves it.
+    fn progress(self, on: ChurnEvent) -> NodeAge {
+        match on {
+            ChurnEvent::Relocated => self.saturating_add(1),
+            ChurnEvent::SectionSplit => self,
+            ChurnEvent::Penalty => self / 2,
+        }
+    }
+}
+
 /// Proposed credits resulting from a churn.
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RewardProposal {
@@ -47,3 +76,27 @@ impl AccumulatingReward {
         self.signed_credit.id()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AgeProgression, ChurnEvent, NodeAge};
+
+    #[test]
+    fn node_age_progression() {
+        let age: NodeAge = 5;
+        assert_eq!(6, age.progress(ChurnEvent::Relocated));
+        assert_eq!(5, age.progress(ChurnEvent::SectionSplit));
+        assert_eq!(2, age.progress(ChurnEvent::Penalty));
+        assert_eq!(NodeAge::MAX, NodeAge::MAX.progress(ChurnEvent::Relocated));
+
+        let events = [
+            ChurnEvent::Relocated,
+            ChurnEvent::Relocated,
+            ChurnEvent::SectionSplit,
+            ChurnEvent::Penalty,
+            ChurnEvent::Relocated,
+        ];
+        let age = events.iter().fold(age, |age, event| age.progress(*event));
+        assert_eq!(4, age);
+    }
+}
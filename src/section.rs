@@ -12,7 +12,7 @@ use std::collections::BTreeSet;
 use threshold_crypto::PublicKeySet;
 use xor_name::{Prefix, XorName};
 
-use crate::PublicKey;
+use crate::{utils, Error, PublicKey, Result, SectionKey, Signature};
 
 ///
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -41,3 +41,103 @@ impl SectionElders {
         self.prefix.name()
     }
 }
+
+/// Proof that a section key descends from a trusted one, as a chain of section keys
+/// each signed by the one before it. Attached to responses, it lets clients check
+/// they come from the legitimate section for the queried address.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SectionProof {
+    /// Section keys, from the oldest to the key of the responding section.
+    pub chain: Vec<SectionKey>,
+    /// Signature of each key of the chain but the first, by the key before it.
+    pub signatures: Vec<Signature>,
+}
+
+impl SectionProof {
+    /// Constructs a proof starting at the `root` key.
+    pub fn new(root: SectionKey) -> Self {
+        Self {
+            chain: vec![root],
+            signatures: vec![],
+        }
+    }
+
+    /// Appends the next section key, signed by the current last key of the chain.
+    pub fn push(&mut self, key: SectionKey, signature: Signature) {
+        self.chain.push(key);
+        self.signatures.push(signature);
+    }
+
+    /// The key of the responding section, i.e. the last key of the chain.
+    pub fn last_key(&self) -> Option<&SectionKey> {
+        self.chain.last()
+    }
+
+    /// Returns the bytes each key of the chain is signed over.
+    pub fn bytes_to_sign(key: &SectionKey) -> Result<Vec<u8>> {
+        utils::serialise(key)
+    }
+
+    /// Verifies the chain descends from `trusted_root`, which may be any of its keys,
+    /// returning the key of the responding section.
+    pub fn verify(&self, trusted_root: &SectionKey) -> Result<SectionKey> {
+        if self.signatures.len() + 1 != self.chain.len() {
+            return Err(Error::InvalidSectionProof(format!(
+                "{} signatures for a chain of {} keys",
+                self.signatures.len(),
+                self.chain.len()
+            )));
+        }
+        let start = self
+            .chain
+            .iter()
+            .position(|key| key == trusted_root)
+            .ok_or_else(|| {
+                Error::InvalidSectionProof("trusted root key not in the chain".to_string())
+            })?;
+
+        for (index, signature) in self.signatures.iter().enumerate().skip(start) {
+            let signer = &self.chain[index];
+            let key = &self.chain[index + 1];
+            signer.verify(signature, &Self::bytes_to_sign(key)?)?;
+        }
+
+        Ok(self.chain[self.chain.len() - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SectionProof;
+    use crate::{Error, PublicKey, Result, SectionKey, Signature};
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn section_proof_verifies_from_trusted_root() -> Result<()> {
+        let keys: Vec<_> = (0..4).map(|_| SecretKey::random()).collect();
+        let public = |index: usize| SectionKey::new(PublicKey::Bls(keys[index].public_key()));
+
+        let mut proof = SectionProof::new(public(0));
+        for index in 1..keys.len() {
+            let bytes = SectionProof::bytes_to_sign(&public(index))?;
+            proof.push(public(index), Signature::Bls(keys[index - 1].sign(&bytes)));
+        }
+
+        assert_eq!(proof.verify(&public(0))?, public(3));
+        assert_eq!(proof.verify(&public(2))?, public(3));
+
+        let stranger = SectionKey::new(PublicKey::Bls(SecretKey::random().public_key()));
+        assert!(matches!(
+            proof.verify(&stranger),
+            Err(Error::InvalidSectionProof(_))
+        ));
+
+        // A link not signed by the previous key breaks the chain
+        let bytes = SectionProof::bytes_to_sign(&public(2))?;
+        proof.signatures[1] = Signature::Bls(keys[0].sign(&bytes));
+        assert_eq!(proof.verify(&public(0)), Err(Error::InvalidSignature));
+        assert_eq!(proof.verify(&public(2))?, public(3));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{metadata::EntryHash, seq_crdt::hash_entry, Entry};
+use serde::{Deserialize, Serialize};
+
+// Prefixes keeping the hashes of entries apart from those of inner nodes,
+// so an inner node can't be passed off as an entry.
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Merkle root over the entries of a Sequence, for third parties to verify
+/// an entry is at a given index with an `EntryProof`, without the whole Sequence.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct Commitment {
+    root: EntryHash,
+    len: u64,
+}
+
+impl Commitment {
+    /// Returns the Merkle root, or all zeros if the Sequence is empty.
+    pub fn root(&self) -> EntryHash {
+        self.root
+    }
+
+    /// Returns the number of entries committed to.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if committing to no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if `proof` shows its entry is at its index among the entries committed to.
+    pub fn verify(&self, proof: &EntryProof) -> bool {
+        if proof.index >= self.len {
+            return false;
+        }
+        let mut hash = hash_leaf(&proof.entry);
+        let mut siblings = proof.siblings.iter();
+        let mut index = proof.index;
+        let mut width = self.len;
+        while width > 1 {
+            // The last node of a level with an odd width is carried up as is.
+            if index != width - 1 || width.is_multiple_of(2) {
+                let sibling = match siblings.next() {
+                    Some(sibling) => sibling,
+                    None => return false,
+                };
+                hash = if index.is_multiple_of(2) {
+                    hash_node(&hash, sibling)
+                } else {
+                    hash_node(sibling, &hash)
+                };
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+
+        siblings.next().is_none() && hash == self.root
+    }
+}
+
+/// Proof that an entry is at a given index among the entries of a `Commitment`.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct EntryProof {
+    index: u64,
+    entry: Entry,
+    siblings: Vec<EntryHash>,
+}
+
+impl EntryProof {
+    /// Returns the index of the entry.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the entry.
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+}
+
+// Commits to `entries`.
+pub(super) fn commit(entries: &[Entry]) -> Commitment {
+    let mut level: Vec<_> = entries.iter().map(|entry| hash_leaf(entry)).collect();
+    let root = if level.is_empty() {
+        [0; 32]
+    } else {
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level[0]
+    };
+
+    Commitment {
+        root,
+        len: entries.len() as u64,
+    }
+}
+
+// Builds the proof of the entry at `index` among `entries`, if any.
+pub(super) fn open(entries: &[Entry], index: usize) -> Option<EntryProof> {
+    let entry = entries.get(index)?.clone();
+    let mut level: Vec<_> = entries.iter().map(|entry| hash_leaf(entry)).collect();
+    let mut siblings = vec![];
+    let mut position = index;
+    while level.len() > 1 {
+        let sibling = if position.is_multiple_of(2) {
+            position + 1
+        } else {
+            position - 1
+        };
+        if let Some(hash) = level.get(sibling) {
+            siblings.push(*hash);
+        }
+        level = next_level(&level);
+        position /= 2;
+    }
+
+    Some(EntryProof {
+        index: index as u64,
+        entry,
+        siblings,
+    })
+}
+
+fn next_level(level: &[EntryHash]) -> Vec<EntryHash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_node(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks are of one or two nodes"),
+        })
+        .collect()
+}
+
+fn hash_leaf(entry: &[u8]) -> EntryHash {
+    let mut bytes = Vec::with_capacity(entry.len() + 1);
+    bytes.push(LEAF_PREFIX);
+    bytes.extend_from_slice(entry);
+    hash_entry(&bytes)
+}
+
+fn hash_node(left: &EntryHash, right: &EntryHash) -> EntryHash {
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(NODE_PREFIX);
+    bytes.extend_from_slice(left);
+    bytes.extend_from_slice(right);
+    hash_entry(&bytes)
+}
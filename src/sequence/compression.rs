@@ -0,0 +1,221 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    metadata::{Entries, Entry, Index},
+    seq_crdt::Op,
+    Data, DataOp,
+};
+use crate::{Error, PublicKey, Result};
+use std::io::Read;
+
+/// Size in bytes above which an entry is compressed.
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Largest size in bytes of an entry compressed, which decompressing an entry
+/// is bounded by, whatever its compressed size.
+pub const MAX_COMPRESSED_ENTRY_SIZE: usize = 1024 * 1024;
+
+// Flag byte prefixed to entries stored as they are.
+const FLAG_RAW: u8 = 0;
+// Flag byte prefixed to entries compressed with zstd.
+const FLAG_ZSTD: u8 = 1;
+// zstd compression level used for entries.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Encodes an entry to be stored in a Sequence, compressing it if it's larger than
+/// `COMPRESSION_THRESHOLD`. The encoding is prefixed with a flag byte so that every
+/// replica decodes it the same way. Entries larger than `MAX_COMPRESSED_ENTRY_SIZE`
+/// are rejected with `Error::ExceededSize`.
+pub fn compress_entry(entry: &[u8]) -> Result<Entry> {
+    if entry.len() > MAX_COMPRESSED_ENTRY_SIZE {
+        return Err(Error::ExceededSize);
+    }
+    if entry.len() <= COMPRESSION_THRESHOLD {
+        let mut encoded = Vec::with_capacity(entry.len() + 1);
+        encoded.push(FLAG_RAW);
+        encoded.extend_from_slice(entry);
+        return Ok(encoded);
+    }
+
+    let compressed = zstd::encode_all(entry, ZSTD_LEVEL)
+        .map_err(|err| Error::Serialisation(format!("Could not compress entry: {}", err)))?;
+    let mut encoded = Vec::with_capacity(compressed.len() + 1);
+    encoded.push(FLAG_ZSTD);
+    encoded.extend(compressed);
+    Ok(encoded)
+}
+
+/// Decodes an entry encoded with `compress_entry`, failing with `Error::ExceededSize`
+/// rather than decompressing it beyond `MAX_COMPRESSED_ENTRY_SIZE`.
+pub fn decompress_entry(encoded: &[u8]) -> Result<Entry> {
+    match encoded.split_first() {
+        Some((&FLAG_RAW, entry)) => Ok(entry.to_vec()),
+        Some((&FLAG_ZSTD, compressed)) => {
+            let to_error =
+                |err| Error::Serialisation(format!("Could not decompress entry: {}", err));
+            let decoder = zstd::stream::read::Decoder::new(compressed).map_err(to_error)?;
+            let mut entry = Vec::new();
+            let _ = decoder
+                .take(MAX_COMPRESSED_ENTRY_SIZE as u64 + 1)
+                .read_to_end(&mut entry)
+                .map_err(to_error)?;
+            if entry.len() > MAX_COMPRESSED_ENTRY_SIZE {
+                return Err(Error::ExceededSize);
+            }
+            Ok(entry)
+        }
+        _ => Err(Error::Serialisation(
+            "Entry is not prefixed with a known compression flag".to_string(),
+        )),
+    }
+}
+
+// Checks the entry is prefixed with a known flag, without decompressing it.
+fn check_flag(encoded: &[u8]) -> Result<()> {
+    match encoded.first() {
+        Some(&FLAG_RAW) | Some(&FLAG_ZSTD) => Ok(()),
+        _ => Err(Error::Serialisation(
+            "Entry is not prefixed with a known compression flag".to_string(),
+        )),
+    }
+}
+
+/// A Sequence whose entries are compressed when appended, if larger than
+/// `COMPRESSION_THRESHOLD`, and decompressed when read, so its users only
+/// ever deal with the entries as appended.
+///
+/// Every entry of the underlying Sequence is expected to be encoded with
+/// `compress_entry`, those which aren't being reported as `Error::Serialisation`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CompressedSequence {
+    data: Data,
+}
+
+impl CompressedSequence {
+    /// Wraps the given Sequence.
+    pub fn new(data: Data) -> Self {
+        Self { data }
+    }
+
+    /// Returns the underlying Sequence, whose entries are read as stored.
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
+
+    /// Returns the underlying Sequence, unwrapping it.
+    pub fn into_data(self) -> Data {
+        self.data
+    }
+
+    /// Returns the length of the sequence, optionally
+    /// verifying read permissions if a pk is provided
+    pub fn len(&self, requester: Option<PublicKey>) -> Result<u64> {
+        self.data.len(requester)
+    }
+
+    /// Returns true if the sequence is empty.
+    pub fn is_empty(&self, requester: Option<PublicKey>) -> Result<bool> {
+        self.data.is_empty(requester)
+    }
+
+    /// Generate unsigned crdt op, adding the entry compressed if it's larger than
+    /// `COMPRESSION_THRESHOLD`.
+    pub fn append(&mut self, entry: &[u8]) -> Result<DataOp<Entry>> {
+        self.data.create_unsigned_append_op(compress_entry(entry)?)
+    }
+
+    /// Apply a signed data CRDT operation, rejecting entries appended which
+    /// aren't prefixed with a known compression flag with `Error::Serialisation`.
+    pub fn apply_op(&mut self, op: DataOp<Entry>) -> Result<()> {
+        if let Op::Insert { val, .. } = &op.crdt_op {
+            check_flag(val)?;
+        }
+        self.data.apply_op(op)
+    }
+
+    /// Returns the entry at 'index', decompressed, if present.
+    pub fn get(&self, index: Index, requester: Option<PublicKey>) -> Result<Option<Entry>> {
+        self.data
+            .get(index, requester)?
+            .map(|entry| decompress_entry(entry))
+            .transpose()
+    }
+
+    /// Returns the last entry, decompressed, if it's not empty.
+    pub fn last_entry(&self, requester: Option<PublicKey>) -> Result<Option<Entry>> {
+        self.data
+            .last_entry(requester)?
+            .map(|entry| decompress_entry(entry))
+            .transpose()
+    }
+
+    /// Gets a list of entries, decompressed, which are within the given indices.
+    /// Note the range of items is [start, end), i.e. the end index is not inclusive.
+    pub fn in_range(
+        &self,
+        start: Index,
+        end: Index,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<Entries>> {
+        self.data
+            .in_range(start, end, requester)?
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| decompress_entry(entry))
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compress_entry, decompress_entry, COMPRESSION_THRESHOLD, FLAG_ZSTD,
+        MAX_COMPRESSED_ENTRY_SIZE, ZSTD_LEVEL,
+    };
+    use crate::{Error, Result};
+
+    #[test]
+    fn compression_roundtrip() -> Result<()> {
+        let small = b"small entry".to_vec();
+        let encoded = compress_entry(&small)?;
+        assert_eq!(encoded.len(), small.len() + 1);
+        assert_eq!(decompress_entry(&encoded)?, small);
+
+        let large = b"log line\n".repeat(COMPRESSION_THRESHOLD);
+        let encoded = compress_entry(&large)?;
+        assert!(encoded.len() < large.len());
+        assert_eq!(decompress_entry(&encoded)?, large);
+
+        assert!(decompress_entry(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn decompression_is_bounded() -> Result<()> {
+        let oversized = vec![0; MAX_COMPRESSED_ENTRY_SIZE + 1];
+        assert_eq!(compress_entry(&oversized), Err(Error::ExceededSize));
+
+        // A small payload decompressing beyond the bound, as another client could append
+        let mut bomb = vec![FLAG_ZSTD];
+        bomb.extend(
+            zstd::encode_all(&oversized[..], ZSTD_LEVEL)
+                .map_err(|err| Error::Serialisation(err.to_string()))?,
+        );
+        assert!(bomb.len() < COMPRESSION_THRESHOLD);
+        assert_eq!(decompress_entry(&bomb), Err(Error::ExceededSize));
+
+        let largest = vec![0; MAX_COMPRESSED_ENTRY_SIZE];
+        assert_eq!(decompress_entry(&compress_entry(&largest)?)?, largest);
+        Ok(())
+    }
+}
@@ -18,6 +18,8 @@ pub enum Action {
     Read,
     /// Append to the data.
     Append,
+    /// Remove entries from the data, only possible on private data.
+    Delete,
 }
 
 /// List of entries.
@@ -26,6 +28,124 @@ pub type Entries = Vec<Entry>;
 /// An entry in a Sequence.
 pub type Entry = Vec<u8>;
 
+/// Hash of an entry in a Sequence.
+pub type EntryHash = [u8; 32];
+
+/// Author and time of the append of an entry in a Sequence.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct EntryMeta {
+    /// The key which appended the entry.
+    pub author: PublicKey,
+    /// Time the author claims to have appended the entry at, if it provided one.
+    pub timestamp: Option<u64>,
+}
+
+/// Kind of content of a typed entry, stored as the first byte of the entry so that
+/// feeds mixing content types are parsed the same way by every app.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum EntryKind {
+    /// UTF-8 text.
+    Text,
+    /// Reference to other data, e.g. its serialised address.
+    Ref,
+    /// Marker of an earlier entry being withdrawn.
+    Tombstone,
+    /// Application-defined kind, from `EntryKind::MIN_CUSTOM` on, as
+    /// the bytes below it are reserved for kinds defined here.
+    Custom(u8),
+}
+
+impl EntryKind {
+    /// Smallest byte of an application-defined kind.
+    pub const MIN_CUSTOM: u8 = 16;
+
+    /// Returns the byte the kind is stored as, failing for a custom kind
+    /// using a reserved byte.
+    pub fn to_byte(self) -> Result<u8> {
+        match self {
+            Self::Text => Ok(0),
+            Self::Ref => Ok(1),
+            Self::Tombstone => Ok(2),
+            Self::Custom(byte) if byte >= Self::MIN_CUSTOM => Ok(byte),
+            Self::Custom(byte) => Err(Error::FailedToParse(format!(
+                "Entry kind {} is reserved",
+                byte
+            ))),
+        }
+    }
+
+    /// Returns the kind stored as `byte`, failing for a reserved byte
+    /// not assigned to a kind yet.
+    pub fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(Self::Text),
+            1 => Ok(Self::Ref),
+            2 => Ok(Self::Tombstone),
+            byte if byte >= Self::MIN_CUSTOM => Ok(Self::Custom(byte)),
+            byte => Err(Error::FailedToParse(format!("Unknown entry kind {}", byte))),
+        }
+    }
+}
+
+/// Application-defined check of the entries appended to a Sequence, e.g. to
+/// enforce the schema of a feed.
+///
+/// Validation must be a pure function of the entry and its metadata, so every
+/// replica configured with the same validator accepts and rejects the same appends.
+pub trait EntryValidator {
+    /// Returns the hash of the validator configuration, which the policy of a
+    /// Sequence records so replicas can't apply its appends with another one.
+    fn config_hash(&self) -> [u8; 32];
+    /// Returns `Ok(())` if the entry can be appended and `Err(SchemaViolation)` otherwise.
+    fn validate(&self, entry: &[u8], meta: &EntryMeta) -> Result<()>;
+}
+
+/// Appends made to a Sequence by a single key.
+#[derive(
+    Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug,
+)]
+pub struct AppendStats {
+    /// Number of entries appended.
+    pub appends: u64,
+    /// Total size in bytes of the entries appended.
+    pub bytes: u64,
+}
+
+/// Position to resume reading a Sequence from, e.g. in a later read request.
+#[derive(
+    Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug,
+)]
+pub struct Cursor {
+    index: u64,
+}
+
+impl Cursor {
+    /// Cursor pointing at the first entry.
+    pub fn start() -> Self {
+        Self::default()
+    }
+
+    /// Index of the entry the cursor points at.
+    /// Note this shifts if entries before it are removed from a private Sequence.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub(crate) fn new(index: u64) -> Self {
+        Self { index }
+    }
+}
+
+/// Entries read from a Sequence, together with where to continue reading from.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct Page {
+    /// The entries read.
+    pub entries: Entries,
+    /// Cursor pointing at the entry following the page, `None` if the page reached
+    /// the end of the Sequence.
+    pub next: Option<Cursor>,
+}
+
 /// Address of a Sequence.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Address {
@@ -140,13 +260,16 @@ pub struct PublicPermissions {
     /// `Some(false)` explicitly denies this permission (even if `Anyone` has permissions).
     /// Use permissions for `Anyone` if `None`.
     append: Option<bool>,
+    /// Time after which the permissions lapse, if they do.
+    expires_at: Option<u64>,
 }
 
 impl PublicPermissions {
-    /// Constructs a new public permission set.
+    /// Constructs a new public permission set, which doesn't lapse.
     pub fn new(append: impl Into<Option<bool>>) -> Self {
         Self {
             append: append.into(),
+            expires_at: None,
         }
     }
 
@@ -155,12 +278,49 @@ impl PublicPermissions {
         self.append = append.into();
     }
 
+    /// Sets the time after which the permissions lapse, `None` for them not to lapse.
+    pub fn set_expires_at(&mut self, expires_at: Option<u64>) {
+        self.expires_at = expires_at;
+    }
+
+    /// Returns the time after which the permissions lapse, if they do.
+    pub fn expires_at(self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Returns `Some(true)` if `action` is allowed at time `now` and `Some(false)` if it's not
+    /// permitted. `None` means that default permissions should be applied, as is the case
+    /// once the permissions have lapsed. Expiry isn't checked if `now` is unknown.
+    pub fn is_allowed_at(self, action: Action, now: Option<u64>) -> Option<bool> {
+        if has_lapsed(self.expires_at, now) {
+            None
+        } else {
+            self.is_allowed(action)
+        }
+    }
+
     /// Returns `Some(true)` if `action` is allowed and `Some(false)` if it's not permitted.
     /// `None` means that default permissions should be applied.
     pub fn is_allowed(self, action: Action) -> Option<bool> {
         match action {
             Action::Read => Some(true), // It's public data, so it's always allowed to read it.
             Action::Append => self.append,
+            Action::Delete => Some(false), // It's public data, so it can never be removed.
+        }
+    }
+}
+
+/// Layout of `PublicPermissions` encoded before they could expire.
+#[derive(Deserialize)]
+pub(super) struct PublicPermissionsV0 {
+    append: Option<bool>,
+}
+
+impl From<PublicPermissionsV0> for PublicPermissions {
+    fn from(permissions: PublicPermissionsV0) -> Self {
+        Self {
+            append: permissions.append,
+            expires_at: None,
         }
     }
 }
@@ -172,12 +332,57 @@ pub struct PrivatePermissions {
     read: bool,
     /// `true` if the user can append.
     append: bool,
+    /// `true` if the user can remove entries.
+    delete: bool,
+    /// Time after which the permissions lapse, if they do.
+    expires_at: Option<u64>,
+}
+
+/// Layout of `PrivatePermissions` encoded before they could allow removing entries
+/// or expire.
+#[derive(Deserialize)]
+pub(super) struct PrivatePermissionsV0 {
+    read: bool,
+    append: bool,
+}
+
+impl From<PrivatePermissionsV0> for PrivatePermissions {
+    fn from(permissions: PrivatePermissionsV0) -> Self {
+        Self {
+            read: permissions.read,
+            append: permissions.append,
+            delete: false,
+            expires_at: None,
+        }
+    }
 }
 
 impl PrivatePermissions {
-    /// Constructs a new private permission set.
+    /// Constructs a new private permission set, without the permission to remove entries,
+    /// which doesn't lapse.
     pub fn new(read: bool, append: bool) -> Self {
-        Self { read, append }
+        Self {
+            read,
+            append,
+            delete: false,
+            expires_at: None,
+        }
+    }
+
+    /// Sets the time after which the permissions lapse, `None` for them not to lapse.
+    pub fn set_expires_at(&mut self, expires_at: Option<u64>) {
+        self.expires_at = expires_at;
+    }
+
+    /// Returns the time after which the permissions lapse, if they do.
+    pub fn expires_at(self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Returns `true` if `action` is allowed at time `now`, i.e. it's allowed and the
+    /// permissions haven't lapsed. Expiry isn't checked if `now` is unknown.
+    pub fn is_allowed_at(self, action: Action, now: Option<u64>) -> bool {
+        !has_lapsed(self.expires_at, now) && self.is_allowed(action)
     }
 
     /// Sets permissions.
@@ -186,11 +391,17 @@ impl PrivatePermissions {
         self.append = append;
     }
 
+    /// Sets the permission to remove entries.
+    pub fn set_delete(&mut self, delete: bool) {
+        self.delete = delete;
+    }
+
     /// Returns `true` if `action` is allowed.
     pub fn is_allowed(self, action: Action) -> bool {
         match action {
             Action::Read => self.read,
             Action::Append => self.append,
+            Action::Delete => self.delete,
         }
     }
 }
@@ -212,15 +423,22 @@ pub struct PublicPolicy {
     pub owner: PublicKey,
     /// Map of users to their public permission set.
     pub permissions: BTreeMap<User, PublicPermissions>,
+    /// Config hash of the `EntryValidator` appends must pass, if any.
+    pub validator: Option<[u8; 32]>,
 }
 
 impl PublicPolicy {
     /// Returns `Some(true)` if `action` is allowed for the provided user and `Some(false)` if it's
     /// not permitted. `None` means that default permissions should be applied.
-    fn is_action_allowed_by_user(&self, user: &User, action: Action) -> Option<bool> {
+    fn is_action_allowed_by_user(
+        &self,
+        user: &User,
+        action: Action,
+        now: Option<u64>,
+    ) -> Option<bool> {
         self.permissions
             .get(user)
-            .and_then(|perms| perms.is_allowed(action))
+            .and_then(|perms| perms.is_allowed_at(action, now))
     }
 }
 
@@ -232,11 +450,65 @@ pub struct PrivatePolicy {
     pub owner: PublicKey,
     /// Map of users to their private permission set.
     pub permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    /// Config hash of the `EntryValidator` appends must pass, if any.
+    pub validator: Option<[u8; 32]>,
+}
+
+/// Layout of `PublicPolicy` encoded before it could require an `EntryValidator`.
+#[derive(Deserialize)]
+pub(super) struct PublicPolicyV0 {
+    owner: PublicKey,
+    permissions: BTreeMap<User, PublicPermissionsV0>,
+}
+
+impl From<PublicPolicyV0> for PublicPolicy {
+    fn from(policy: PublicPolicyV0) -> Self {
+        Self {
+            owner: policy.owner,
+            permissions: policy
+                .permissions
+                .into_iter()
+                .map(|(user, permissions)| (user, permissions.into()))
+                .collect(),
+            validator: None,
+        }
+    }
+}
+
+/// Layout of `PrivatePolicy` encoded before it could require an `EntryValidator`.
+#[derive(Deserialize)]
+pub(super) struct PrivatePolicyV0 {
+    owner: PublicKey,
+    permissions: BTreeMap<PublicKey, PrivatePermissionsV0>,
+}
+
+impl From<PrivatePolicyV0> for PrivatePolicy {
+    fn from(policy: PrivatePolicyV0) -> Self {
+        Self {
+            owner: policy.owner,
+            permissions: policy
+                .permissions
+                .into_iter()
+                .map(|(key, permissions)| (key, permissions.into()))
+                .collect(),
+            validator: None,
+        }
+    }
 }
 
 pub trait Perm {
     /// Returns true if `action` is allowed for the provided user.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()>;
+    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+        self.is_action_allowed_at(requester, action, None)
+    }
+    /// Returns true if `action` is allowed for the provided user at time `now`,
+    /// taking into account the expiry of permissions if `now` is known.
+    fn is_action_allowed_at(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        now: Option<u64>,
+    ) -> Result<()>;
     /// Gets the permissions for a user if applicable.
     fn permissions(&self, user: User) -> Option<Permissions>;
     /// Returns the owner.
@@ -244,16 +516,21 @@ pub trait Perm {
 }
 
 impl Perm for PublicPolicy {
-    /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
-    /// this action is not permitted.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+    /// Returns `Ok(())` if `action` is allowed for the provided user at time `now` and
+    /// `Err(AccessDenied)` if this action is not permitted.
+    fn is_action_allowed_at(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        now: Option<u64>,
+    ) -> Result<()> {
         // First checks if the requester is the owner.
         if action == Action::Read || requester == self.owner {
             Ok(())
         } else {
             match self
-                .is_action_allowed_by_user(&User::Key(requester), action)
-                .or_else(|| self.is_action_allowed_by_user(&User::Anyone, action))
+                .is_action_allowed_by_user(&User::Key(requester), action, now)
+                .or_else(|| self.is_action_allowed_by_user(&User::Anyone, action, now))
             {
                 Some(true) => Ok(()),
                 Some(false) => Err(Error::AccessDenied(requester)),
@@ -274,16 +551,21 @@ impl Perm for PublicPolicy {
 }
 
 impl Perm for PrivatePolicy {
-    /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
-    /// this action is not permitted.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+    /// Returns `Ok(())` if `action` is allowed for the provided user at time `now` and
+    /// `Err(AccessDenied)` if this action is not permitted.
+    fn is_action_allowed_at(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        now: Option<u64>,
+    ) -> Result<()> {
         // First checks if the requester is the owner.
         if requester == self.owner {
             Ok(())
         } else {
             match self.permissions.get(&requester) {
                 Some(perms) => {
-                    if perms.is_allowed(action) {
+                    if perms.is_allowed_at(action, now) {
                         Ok(())
                     } else {
                         Err(Error::AccessDenied(requester))
@@ -329,6 +611,48 @@ impl From<PublicPolicy> for Policy {
     }
 }
 
+/// Ready-made policies for the common uses of a Sequence.
+pub struct PolicyTemplate;
+
+impl PolicyTemplate {
+    /// Public Sequence anyone can read but only `owner` can append to.
+    pub fn public_feed(owner: PublicKey) -> PublicPolicy {
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(User::Anyone, PublicPermissions::new(false));
+        PublicPolicy {
+            owner,
+            permissions,
+            validator: None,
+        }
+    }
+
+    /// Private Sequence `members` can read and append to, but not remove entries from.
+    pub fn group_append(
+        owner: PublicKey,
+        members: impl IntoIterator<Item = PublicKey>,
+    ) -> PrivatePolicy {
+        let permissions = members
+            .into_iter()
+            .filter(|member| *member != owner)
+            .map(|member| (member, PrivatePermissions::new(true, true)))
+            .collect();
+        PrivatePolicy {
+            owner,
+            permissions,
+            validator: None,
+        }
+    }
+
+    /// Private Sequence only `owner` can read and append to.
+    pub fn owner_only(owner: PublicKey) -> PrivatePolicy {
+        PrivatePolicy {
+            owner,
+            permissions: BTreeMap::new(),
+            validator: None,
+        }
+    }
+}
+
 /// Wrapper type for permissions set, which can be public or private.
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
 pub enum Permissions {
@@ -349,3 +673,8 @@ impl From<PublicPermissions> for Permissions {
         Permissions::Public(permission_set)
     }
 }
+
+// Returns true if permissions expiring at `expires_at` have lapsed by `now`.
+fn has_lapsed(expires_at: Option<u64>, now: Option<u64>) -> bool {
+    matches!((expires_at, now), (Some(expires_at), Some(now)) if now > expires_at)
+}
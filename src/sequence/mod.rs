@@ -7,18 +7,33 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod commitment;
+#[cfg(feature = "compression")]
+mod compression;
 mod metadata;
+mod op_buffer;
 mod seq_crdt;
 
-use crate::{Error, PublicKey, Result};
+use crate::{utils, Error, PublicKey, Result, Signature, ThresholdKey};
+pub use commitment::{Commitment, EntryProof};
+#[cfg(feature = "compression")]
+pub use compression::{
+    compress_entry, decompress_entry, CompressedSequence, COMPRESSION_THRESHOLD,
+    MAX_COMPRESSED_ENTRY_SIZE,
+};
 pub use metadata::{
-    Action, Address, Entries, Entry, Index, Kind, Perm, Permissions, Policy, PrivatePermissions,
-    PrivatePolicy, PublicPermissions, PublicPolicy, User,
+    Action, Address, AppendStats, Cursor, Entries, Entry, EntryHash, EntryKind, EntryMeta,
+    EntryValidator, Index, Kind, Page, Perm, Permissions, Policy, PolicyTemplate,
+    PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
+};
+use metadata::{PrivatePolicyV0, PublicPolicyV0};
+pub use op_buffer::{OpBuffer, MAX_BUFFERED_OPS_PER_SOURCE};
+use seq_crdt::{
+    hash_entry, CrdtBatchOperation, CrdtOperation, CrdtState, Op, SequenceCrdt, SequenceCrdtV0,
 };
-use seq_crdt::{CrdtOperation, SequenceCrdt};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
-use std::{fmt::Debug, hash::Hash};
+use std::{fmt::Debug, hash::Hash, marker::PhantomData};
 use xor_name::XorName;
 
 // Type of data used for the 'Actor' in CRDT vector clocks
@@ -27,13 +42,19 @@ type ActorType = String;
 /// Data mutation operation to apply to Sequence.
 pub type DataOp<T> = CrdtOperation<ActorType, T>;
 
+/// Data mutation operation appending several entries to a Sequence at once.
+pub type DataBatchOp<T> = CrdtBatchOperation<ActorType, T>;
+
 /// Public Sequence.
 pub type PublicSeqData = SequenceCrdt<ActorType, PublicPolicy>;
 /// Private Sequence.
 pub type PrivateSeqData = SequenceCrdt<ActorType, PrivatePolicy>;
 
 /// Object storing a Sequence variant.
-#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+///
+/// Encoded as a `SeqDataEncoding`, so Sequences encoded with an earlier layout can
+/// still be decoded.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 enum SeqData {
     /// Public Sequence Data.
     Public(PublicSeqData),
@@ -41,6 +62,84 @@ enum SeqData {
     Private(PrivateSeqData),
 }
 
+/// Layouts a `SeqData` has been encoded with, the earliest first. Variants are only
+/// ever appended, for those encoded before to keep their tag.
+#[derive(Deserialize)]
+enum SeqDataEncoding {
+    /// Public Sequence from before the author of entries was kept.
+    Public(SequenceCrdtV0<ActorType, PublicPolicyV0>),
+    /// Private Sequence from before the author of entries was kept.
+    Private(SequenceCrdtV0<ActorType, PrivatePolicyV0>),
+    /// Public Sequence.
+    PublicV1(PublicSeqData),
+    /// Private Sequence.
+    PrivateV1(PrivateSeqData),
+}
+
+impl Serialize for SeqData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Public(data) => {
+                serializer.serialize_newtype_variant("SeqDataEncoding", 2, "PublicV1", data)
+            }
+            Self::Private(data) => {
+                serializer.serialize_newtype_variant("SeqDataEncoding", 3, "PrivateV1", data)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SeqData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Ok(match SeqDataEncoding::deserialize(deserializer)? {
+            SeqDataEncoding::Public(data) => Self::Public(SequenceCrdt::from_v0(data)),
+            SeqDataEncoding::Private(data) => Self::Private(SequenceCrdt::from_v0(data)),
+            SeqDataEncoding::PublicV1(data) => Self::Public(data),
+            SeqDataEncoding::PrivateV1(data) => Self::Private(data),
+        })
+    }
+}
+
+/// State of a Sequence replica taken as a snapshot.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+enum SnapshotState {
+    /// State of a Public Sequence.
+    Public(CrdtState<ActorType, PublicPolicy>),
+    /// State of a Private Sequence.
+    Private(CrdtState<ActorType, PrivatePolicy>),
+}
+
+/// State of a Sequence replica, for other replicas to bootstrap from without
+/// replaying every op applied so far.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    /// The entries, along with the CRDT metadata needed to apply later ops to them,
+    /// and the current policy.
+    state: SnapshotState,
+    /// Marker of the state the snapshot was taken at.
+    checkpoint: EntryHash,
+    /// The PublicKey of the replica the snapshot was taken from.
+    pub source: PublicKey,
+    /// The signature of source on the checkpoint, required to bootstrap from the snapshot.
+    pub signature: Option<Signature>,
+}
+
+impl Snapshot {
+    /// Returns the marker of the state the snapshot was taken at, i.e. the hash of that state.
+    pub fn checkpoint(&self) -> EntryHash {
+        self.checkpoint
+    }
+
+    /// Returns the bytes the source is expected to sign, i.e. the checkpoint.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&self.checkpoint)
+    }
+
+    fn compute_checkpoint(state: &SnapshotState) -> Result<EntryHash> {
+        Ok(hash_entry(&utils::serialise(state)?))
+    }
+}
+
 /// Object storing the Sequence
 #[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 pub struct Data {
@@ -66,6 +165,7 @@ impl Data {
         let policy = policy.unwrap_or(PublicPolicy {
             owner: authority,
             permissions: BTreeMap::new(),
+            validator: None,
         });
 
         Self {
@@ -94,6 +194,7 @@ impl Data {
         let policy = policy.unwrap_or(PrivatePolicy {
             owner: authority,
             permissions: BTreeMap::new(),
+            validator: None,
         });
 
         Self {
@@ -106,6 +207,61 @@ impl Data {
         }
     }
 
+    /// Constructs a Sequence replica out of a snapshot of another one.
+    /// The 'authority' and 'actor' are those of this replica, as in `new_public`
+    /// and `new_private`. The snapshot has to be signed by the owner of the
+    /// Sequence, or with the same 'authority' as this replica's.
+    pub fn from_snapshot(
+        authority: PublicKey,
+        actor: ActorType,
+        snapshot: Snapshot,
+    ) -> Result<Self> {
+        if Snapshot::compute_checkpoint(&snapshot.state)? != snapshot.checkpoint {
+            return Err(Error::CrdtUnexpectedState);
+        }
+        let signature = snapshot
+            .signature
+            .as_ref()
+            .ok_or(Error::CrdtMissingOpSignature)?;
+        snapshot
+            .source
+            .verify(signature, &snapshot.bytes_to_sign()?)?;
+
+        let data = match snapshot.state {
+            SnapshotState::Public(state) => {
+                SeqData::Public(PublicSeqData::from_state(actor, state))
+            }
+            SnapshotState::Private(state) => {
+                SeqData::Private(PrivateSeqData::from_state(actor, state))
+            }
+        };
+        let replica = Self { authority, data };
+        if snapshot.source != authority && snapshot.source != replica.owner() {
+            return Err(Error::AccessDenied(snapshot.source));
+        }
+
+        Ok(replica)
+    }
+
+    /// Returns a snapshot of this replica, optionally
+    /// verifying read permissions if a pk is provided.
+    /// The snapshot is to be signed over `Snapshot::bytes_to_sign` by this
+    /// replica's authority.
+    pub fn snapshot(&self, requester: Option<PublicKey>) -> Result<Snapshot> {
+        self.check_permission(Action::Read, requester)?;
+
+        let state = match &self.data {
+            SeqData::Public(data) => SnapshotState::Public(data.state()),
+            SeqData::Private(data) => SnapshotState::Private(data.state()),
+        };
+        Ok(Snapshot {
+            checkpoint: Snapshot::compute_checkpoint(&state)?,
+            state,
+            source: self.authority,
+            signature: None,
+        })
+    }
+
     /// Returns the address.
     pub fn address(&self) -> &Address {
         match &self.data {
@@ -175,6 +331,37 @@ impl Data {
         Ok(entries)
     }
 
+    /// Reads up to `limit` entries starting at `cursor`, returning them in a page
+    /// along with the cursor to read the next page from.
+    /// A `limit` of zero is rejected, as its page would never move the cursor forward.
+    pub fn iter_from(
+        &self,
+        cursor: Cursor,
+        limit: u64,
+        requester: Option<PublicKey>,
+    ) -> Result<Page> {
+        let len = self.len(requester)?;
+        if limit == 0 {
+            return Err(Error::InvalidOperation);
+        }
+        let start = cursor.index();
+        if start > len {
+            return Err(Error::NoSuchEntry);
+        }
+        let end = std::cmp::min(start.saturating_add(limit), len);
+
+        let entries = self
+            .in_range(Index::FromStart(start), Index::FromStart(end), None)?
+            .unwrap_or_default();
+        let next = if end < len {
+            Some(Cursor::new(end))
+        } else {
+            None
+        };
+
+        Ok(Page { entries, next })
+    }
+
     /// Returns a value at 'index', if present.
     pub fn get(&self, index: Index, requester: Option<PublicKey>) -> Result<Option<&Vec<u8>>> {
         self.check_permission(Action::Read, requester)?;
@@ -185,6 +372,37 @@ impl Data {
         })
     }
 
+    /// Returns a value at 'index' together with its author and timestamp, if present.
+    pub fn get_with_meta(
+        &self,
+        index: Index,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<(&Entry, &EntryMeta)>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(match &self.data {
+            SeqData::Public(data) => data.get_with_meta(index),
+            SeqData::Private(data) => data.get_with_meta(index),
+        })
+    }
+
+    /// Gets a list of items, together with their author and timestamp,
+    /// which are within the given indices.
+    /// Note the range of items is [start, end), i.e. the end index is not inclusive.
+    pub fn in_range_with_meta(
+        &self,
+        start: Index,
+        end: Index,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<Vec<(Entry, EntryMeta)>>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(match &self.data {
+            SeqData::Public(data) => data.in_range_with_meta(start, end),
+            SeqData::Private(data) => data.in_range_with_meta(start, end),
+        })
+    }
+
     /// Returns the last entry, if it's not empty.
     pub fn last_entry(&self, requester: Option<PublicKey>) -> Result<Option<&Entry>> {
         self.check_permission(Action::Read, requester)?;
@@ -195,9 +413,53 @@ impl Data {
         })
     }
 
-    /// Generate unsigned crdt op, adding the new entry.
+    /// Returns the hash of the last entry, if it's not empty.
+    pub fn last_entry_hash(&self, requester: Option<PublicKey>) -> Result<Option<EntryHash>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(match &self.data {
+            SeqData::Public(data) => data.last_entry_hash(),
+            SeqData::Private(data) => data.last_entry_hash(),
+        })
+    }
+
+    /// Returns a commitment to the current entries, for third parties to verify
+    /// the proofs built with `open` against.
+    pub fn commitment(&self, requester: Option<PublicKey>) -> Result<Commitment> {
+        let entries = self.all_entries(requester)?;
+
+        Ok(commitment::commit(&entries))
+    }
+
+    /// Returns the proof that the entry at `index` is part of the current entries,
+    /// verifiable against the `commitment` to them.
+    pub fn open(&self, index: Index, requester: Option<PublicKey>) -> Result<EntryProof> {
+        let entries = self.all_entries(requester)?;
+        let index = match index {
+            Index::FromStart(index) => index as usize,
+            Index::FromEnd(index) => entries
+                .len()
+                .checked_sub(index as usize)
+                .ok_or(Error::NoSuchEntry)?,
+        };
+
+        commitment::open(&entries, index).ok_or(Error::NoSuchEntry)
+    }
+
+    /// Returns the number of entries and bytes appended by each key.
+    pub fn stats(&self, requester: Option<PublicKey>) -> Result<&BTreeMap<PublicKey, AppendStats>> {
+        self.check_permission(Action::Read, requester)?;
+
+        Ok(match &self.data {
+            SeqData::Public(data) => data.stats(),
+            SeqData::Private(data) => data.stats(),
+        })
+    }
+
+    /// Generate unsigned crdt op, adding the new entry. Users whose permissions expire
+    /// have to use `create_unsigned_timestamped_append_op` instead.
     pub fn create_unsigned_append_op(&mut self, entry: Entry) -> Result<DataOp<Entry>> {
-        self.check_permission(Action::Append, None)?;
+        self.check_append(self.authority, None)?;
 
         match &mut self.data {
             SeqData::Public(data) => data.create_append_op(entry, self.authority),
@@ -205,9 +467,168 @@ impl Data {
         }
     }
 
+    /// Generate a single unsigned crdt op adding all the `entries`, in order, which
+    /// other replicas apply atomically with `apply_batch_op`.
+    pub fn append_batch(&mut self, entries: Vec<Entry>) -> Result<DataBatchOp<Entry>> {
+        self.check_append(self.authority, None)?;
+
+        match &self.data {
+            SeqData::Public(data) => data.create_append_batch_op(entries, self.authority),
+            SeqData::Private(data) => data.create_append_batch_op(entries, self.authority),
+        }
+    }
+
+    /// Generate unsigned crdt op, adding the new entry along with the time it is
+    /// appended at, as reported by `get_with_meta`. The op must be signed over
+    /// `DataOp::bytes_to_sign`, which covers the timestamp.
+    pub fn create_unsigned_timestamped_append_op(
+        &mut self,
+        entry: Entry,
+        timestamp: u64,
+    ) -> Result<DataOp<Entry>> {
+        self.check_append(self.authority, Some(timestamp))?;
+        let mut op = match &mut self.data {
+            SeqData::Public(data) => data.create_append_op(entry, self.authority),
+            SeqData::Private(data) => data.create_append_op(entry, self.authority),
+        }?;
+        op.timestamp = Some(timestamp);
+        Ok(op)
+    }
+
+    /// Generate unsigned crdt op, adding `payload` prefixed with the byte of its `kind`.
+    /// Such entries are to be read with `get_typed`.
+    pub fn append_typed(&mut self, kind: EntryKind, payload: &[u8]) -> Result<DataOp<Entry>> {
+        let mut entry = Vec::with_capacity(payload.len() + 1);
+        entry.push(kind.to_byte()?);
+        entry.extend_from_slice(payload);
+        self.create_unsigned_append_op(entry)
+    }
+
+    /// Returns the kind and payload of the value at 'index', if present,
+    /// of a Sequence appended to with `append_typed`.
+    pub fn get_typed(
+        &self,
+        index: Index,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<(EntryKind, &[u8])>> {
+        self.get(index, requester)?
+            .map(|entry| match entry.split_first() {
+                Some((byte, payload)) => Ok((EntryKind::from_byte(*byte)?, payload)),
+                None => Err(Error::FailedToParse("Empty typed entry".to_string())),
+            })
+            .transpose()
+    }
+
+    /// Generate unsigned crdt op, adding the new entry only if the hash of the
+    /// last entry of this replica is `expected_last` (`None` for an empty Sequence),
+    /// failing with `Error::LastEntryMismatch` otherwise.
+    /// The op carries the precondition, but it's advisory to other replicas, which
+    /// apply the op whatever their last entry, so they converge whichever order ops
+    /// reach them in. Concurrent conditional appends made on different replicas thus
+    /// all end up in the Sequence.
+    /// The op must be signed over `DataOp::bytes_to_sign`.
+    pub fn append_if_last(
+        &mut self,
+        entry: Entry,
+        expected_last: Option<EntryHash>,
+    ) -> Result<DataOp<Entry>> {
+        self.check_append(self.authority, None)?;
+
+        match &mut self.data {
+            SeqData::Public(data) => {
+                data.create_append_if_last_op(entry, expected_last, self.authority)
+            }
+            SeqData::Private(data) => {
+                data.create_append_if_last_op(entry, expected_last, self.authority)
+            }
+        }
+    }
+
+    /// Generate unsigned crdt op removing the entry at `index`.
+    /// Only entries of a private Sequence can be removed, by its owner or
+    /// users with the `Delete` permission.
+    pub fn remove(&mut self, index: Index) -> Result<DataOp<Entry>> {
+        self.check_can_delete(self.authority, None)?;
+
+        match &self.data {
+            SeqData::Public(_) => Err(Error::InvalidOperation),
+            SeqData::Private(data) => data.create_remove_op(index, self.authority),
+        }
+    }
+
+    /// Generate unsigned crdt ops removing the entries from `len` onwards, so that
+    /// at most `len` entries are left. Only a private Sequence can be truncated,
+    /// by its owner or users with the `Delete` permission.
+    pub fn truncate(&mut self, len: u64) -> Result<Vec<DataOp<Entry>>> {
+        self.check_can_delete(self.authority, None)?;
+
+        match &self.data {
+            SeqData::Public(_) => Err(Error::InvalidOperation),
+            SeqData::Private(data) => Ok(data.create_truncate_ops(len, self.authority)),
+        }
+    }
+
+    /// Generate unsigned crdt ops removing the oldest `count` entries, e.g. to enforce
+    /// a retention policy on a private log. Only a private Sequence can be trimmed,
+    /// by its owner or users with the `Delete` permission.
+    pub fn trim_front(&mut self, count: u64) -> Result<Vec<DataOp<Entry>>> {
+        self.check_can_delete(self.authority, None)?;
+
+        match &self.data {
+            SeqData::Public(_) => Err(Error::InvalidOperation),
+            SeqData::Private(data) => Ok(data.create_trim_front_ops(count, self.authority)),
+        }
+    }
+
     /// Apply a signed data CRDT operation.
+    /// The time the op claims to be made at isn't trusted, so ops only granted by
+    /// permissions which expire are rejected, they need to be applied with `apply_op_at`.
+    /// Appends to a Sequence whose policy requires an `EntryValidator` are rejected,
+    /// they need to be applied with `apply_op_with_validator` instead.
     pub fn apply_op(&mut self, op: DataOp<Entry>) -> Result<()> {
-        self.check_permission(Action::Append, Some(op.source))?;
+        self.check_no_validator(&op)?;
+        self.apply_checked_op(op, None)
+    }
+
+    /// Apply a signed data CRDT operation like `apply_op`, but checking it against
+    /// the permissions at time `now`, as known to the node applying it, rather than at
+    /// the time the op claims, which its source is free to pick.
+    pub fn apply_op_at(&mut self, op: DataOp<Entry>, now: u64) -> Result<()> {
+        self.check_no_validator(&op)?;
+        self.apply_checked_op(op, Some(now))
+    }
+
+    /// Apply a signed data CRDT operation, appends being checked by `validator`,
+    /// which has to be the one the policy requires.
+    pub fn apply_op_with_validator(
+        &mut self,
+        op: DataOp<Entry>,
+        validator: &dyn EntryValidator,
+    ) -> Result<()> {
+        self.check_validator(validator)?;
+        if let Op::Insert { val, .. } = &op.crdt_op {
+            let meta = EntryMeta {
+                author: op.source,
+                timestamp: op.timestamp,
+            };
+            validator.validate(val, &meta)?;
+        }
+        self.apply_checked_op(op, None)
+    }
+
+    fn check_no_validator(&self, op: &DataOp<Entry>) -> Result<()> {
+        match op.crdt_op {
+            Op::Insert { .. } if self.validator().is_some() => Err(Error::ValidatorMismatch),
+            _ => Ok(()),
+        }
+    }
+
+    // Applies the op once checked at time `now`, if known.
+    fn apply_checked_op(&mut self, op: DataOp<Entry>, now: Option<u64>) -> Result<()> {
+        match op.crdt_op {
+            Op::Insert { .. } => self.check_append(op.source, now)?,
+            Op::Delete { .. } => self.check_can_delete(op.source, now)?,
+        }
 
         match &mut self.data {
             SeqData::Public(data) => data.apply_op(op),
@@ -215,6 +636,98 @@ impl Data {
         }
     }
 
+    /// Merges the state of `other`, a replica of the same Sequence, into this one, so an
+    /// out of sync replica can converge from a peer's state.
+    /// The entries and removals `other` holds are checked against the signatures of the
+    /// ops which brought them, and the permissions of their authors, which can't be checked
+    /// against the time they were made at as it's unknown. If any of them is rejected, the
+    /// error is returned and this replica is left as it was.
+    /// Sequences whose policy requires an `EntryValidator` are merged with
+    /// `merge_with_validator` instead.
+    pub fn merge(&mut self, other: &Data) -> Result<()> {
+        if self.validator().is_some() {
+            return Err(Error::ValidatorMismatch);
+        }
+        self.merge_checked(other, None)
+    }
+
+    /// Merges the state of `other` like `merge`, the entries it brings being checked by
+    /// `validator`, which has to be the one the policy requires.
+    pub fn merge_with_validator(
+        &mut self,
+        other: &Data,
+        validator: &dyn EntryValidator,
+    ) -> Result<()> {
+        self.check_validator(validator)?;
+        self.merge_checked(other, Some(validator))
+    }
+
+    fn merge_checked(
+        &mut self,
+        other: &Data,
+        validator: Option<&dyn EntryValidator>,
+    ) -> Result<()> {
+        if self.address() != other.address() {
+            return Err(Error::InvalidOperation);
+        }
+
+        match (&mut self.data, &other.data) {
+            (SeqData::Public(data), SeqData::Public(other)) if data.policy() == other.policy() => {
+                data.merge(other, |policy, crdt_op, meta| match crdt_op {
+                    Op::Delete { .. } => Err(Error::InvalidOperation),
+                    Op::Insert { .. } => check_merged_op(policy, crdt_op, meta, validator),
+                })
+            }
+            (SeqData::Private(data), SeqData::Private(other))
+                if data.policy() == other.policy() =>
+            {
+                data.merge(other, |policy, crdt_op, meta| {
+                    check_merged_op(policy, crdt_op, meta, validator)
+                })
+            }
+            _ => Err(Error::InvalidOperation),
+        }
+    }
+
+    /// Apply a signed batch of appends, either all of them or none.
+    /// Batches for a Sequence whose policy requires an `EntryValidator` are rejected,
+    /// they need to be applied with `apply_batch_op_with_validator` instead.
+    pub fn apply_batch_op(&mut self, op: DataBatchOp<Entry>) -> Result<()> {
+        if self.validator().is_some() {
+            return Err(Error::ValidatorMismatch);
+        }
+        self.apply_checked_batch_op(op)
+    }
+
+    /// Apply a signed batch of appends, either all of them or none, each of them
+    /// checked by `validator`, which has to be the one the policy requires.
+    pub fn apply_batch_op_with_validator(
+        &mut self,
+        op: DataBatchOp<Entry>,
+        validator: &dyn EntryValidator,
+    ) -> Result<()> {
+        self.check_validator(validator)?;
+        let meta = EntryMeta {
+            author: op.source,
+            timestamp: None,
+        };
+        for crdt_op in &op.crdt_ops {
+            if let Op::Insert { val, .. } = crdt_op {
+                validator.validate(val, &meta)?;
+            }
+        }
+        self.apply_checked_batch_op(op)
+    }
+
+    fn apply_checked_batch_op(&mut self, op: DataBatchOp<Entry>) -> Result<()> {
+        self.check_append(op.source, None)?;
+
+        match &mut self.data {
+            SeqData::Public(data) => data.apply_batch_op(op),
+            SeqData::Private(data) => data.apply_batch_op(op),
+        }
+    }
+
     /// Returns user permissions, if applicable.
     pub fn permissions(&self, user: User, requester: Option<PublicKey>) -> Result<Permissions> {
         self.check_permission(Action::Read, requester)?;
@@ -246,15 +759,73 @@ impl Data {
 
     /// Helper to check permissions for given `action`
     /// for the given requester's public key.
+    /// The time being unknown, permissions which expire are treated as lapsed: nodes
+    /// serving requesters with such permissions check them with `check_permission_at`.
     ///
     /// Returns:
     /// `Ok(())` if the permissions are valid,
     /// `Err::AccessDenied` if the action is not allowed.
     pub fn check_permission(&self, action: Action, requester: Option<PublicKey>) -> Result<()> {
+        self.check_permission_at(action, requester, Some(u64::MAX))
+    }
+
+    /// Helper to check permissions for given `action` for the given requester's
+    /// public key at time `now`, i.e. rejecting it if the permissions granting
+    /// it have expired by then. Expiry isn't checked if `now` is unknown.
+    pub fn check_permission_at(
+        &self,
+        action: Action,
+        requester: Option<PublicKey>,
+        now: Option<u64>,
+    ) -> Result<()> {
         let requester = requester.unwrap_or(self.authority);
         match &self.data {
-            SeqData::Public(data) => data.policy().is_action_allowed(requester, action),
-            SeqData::Private(data) => data.policy().is_action_allowed(requester, action),
+            SeqData::Public(data) => data.policy().is_action_allowed_at(requester, action, now),
+            SeqData::Private(data) => data.policy().is_action_allowed_at(requester, action, now),
+        }
+    }
+
+    /// Helper to check `requester` can append at time `now`. Permissions which
+    /// expire only grant appends checked at a known time: if it's unknown, they're
+    /// treated as lapsed, rather than as granting appends forever.
+    fn check_append(&self, requester: PublicKey, now: Option<u64>) -> Result<()> {
+        let now = now.unwrap_or(u64::MAX);
+        self.check_permission_at(Action::Append, Some(requester), Some(now))
+    }
+
+    /// Returns the config hash of the `EntryValidator` the policy requires appends to pass, if any.
+    pub fn validator(&self) -> Option<[u8; 32]> {
+        match &self.data {
+            SeqData::Public(data) => data.policy().validator,
+            SeqData::Private(data) => data.policy().validator,
+        }
+    }
+
+    /// Helper to check `validator` is the one the policy requires.
+    fn check_validator(&self, validator: &dyn EntryValidator) -> Result<()> {
+        if self.validator() == Some(validator.config_hash()) {
+            Ok(())
+        } else {
+            Err(Error::ValidatorMismatch)
+        }
+    }
+
+    /// Helper to read all the entries, in order.
+    fn all_entries(&self, requester: Option<PublicKey>) -> Result<Entries> {
+        Ok(self
+            .in_range(Index::FromStart(0), Index::FromEnd(0), requester)?
+            .unwrap_or_default())
+    }
+
+    /// Helper to check the requester can remove entries at time `now`, i.e. the
+    /// Sequence is private and the requester has the `Delete` permission, which
+    /// like for appends is treated as lapsed if it expires and `now` is unknown.
+    fn check_can_delete(&self, requester: PublicKey, now: Option<u64>) -> Result<()> {
+        if self.is_public() {
+            Err(Error::InvalidOperation)
+        } else {
+            let now = now.unwrap_or(u64::MAX);
+            self.check_permission_at(Action::Delete, Some(requester), Some(now))
         }
     }
 
@@ -266,25 +837,180 @@ impl Data {
         }
     }
 
+    /// Checks `signature` over `payload` was combined by the group `key`, which has
+    /// to be the owner of the data.
+    pub fn check_threshold_owner<T: AsRef<[u8]>>(
+        &self,
+        key: &ThresholdKey,
+        payload: T,
+        signature: &Signature,
+    ) -> Result<()> {
+        key.verify_owner_sig(&self.owner(), payload, signature)
+    }
+
     /// Returns the PK which the messages are expected to be signed with by this replica.
     pub fn replica_authority(&self) -> PublicKey {
         self.authority
     }
 }
 
+// Checks an op taken from the state of another replica when merging it: the time it
+// was made at being unknown, the permissions granting it aren't checked for expiry.
+fn check_merged_op<P: Perm>(
+    policy: &P,
+    crdt_op: &Op<Entry, ActorType>,
+    meta: &EntryMeta,
+    validator: Option<&dyn EntryValidator>,
+) -> Result<()> {
+    match crdt_op {
+        Op::Insert { val, .. } => {
+            policy.is_action_allowed_at(meta.author, Action::Append, None)?;
+            validator.map_or(Ok(()), |validator| validator.validate(val, meta))
+        }
+        Op::Delete { .. } => policy.is_action_allowed_at(meta.author, Action::Delete, None),
+    }
+}
+
+/// A Sequence whose entries are values of type `T`, stored serialised with bincode.
+///
+/// Entries which can't be deserialised into a `T` are reported as `Error::Serialisation`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TypedSequence<T> {
+    data: Data,
+    _entries: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> TypedSequence<T> {
+    /// Wraps the given Sequence.
+    pub fn new(data: Data) -> Self {
+        Self {
+            data,
+            _entries: PhantomData,
+        }
+    }
+
+    /// Returns the underlying Sequence.
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
+
+    /// Returns the underlying Sequence, unwrapping it.
+    pub fn into_data(self) -> Data {
+        self.data
+    }
+
+    /// Returns the length of the sequence, optionally
+    /// verifying read permissions if a pk is provided
+    pub fn len(&self, requester: Option<PublicKey>) -> Result<u64> {
+        self.data.len(requester)
+    }
+
+    /// Returns true if the sequence is empty.
+    pub fn is_empty(&self, requester: Option<PublicKey>) -> Result<bool> {
+        self.data.is_empty(requester)
+    }
+
+    /// Generate unsigned crdt op, adding the serialised value.
+    pub fn append(&mut self, value: &T) -> Result<DataOp<Entry>> {
+        let entry = utils::serialise(value)?;
+        self.data.create_unsigned_append_op(entry)
+    }
+
+    /// Apply a signed data CRDT operation.
+    pub fn apply_op(&mut self, op: DataOp<Entry>) -> Result<()> {
+        self.data.apply_op(op)
+    }
+
+    /// Returns the value at 'index', if present.
+    pub fn get(&self, index: Index, requester: Option<PublicKey>) -> Result<Option<T>> {
+        self.data
+            .get(index, requester)?
+            .map(|entry| Self::parse(entry))
+            .transpose()
+    }
+
+    /// Returns the last value, if it's not empty.
+    pub fn last_entry(&self, requester: Option<PublicKey>) -> Result<Option<T>> {
+        self.data
+            .last_entry(requester)?
+            .map(|entry| Self::parse(entry))
+            .transpose()
+    }
+
+    /// Gets a list of values which are within the given indices.
+    /// Note the range of items is [start, end), i.e. the end index is not inclusive.
+    pub fn in_range(
+        &self,
+        start: Index,
+        end: Index,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<Vec<T>>> {
+        self.data
+            .in_range(start, end, requester)?
+            .map(|entries| entries.iter().map(|entry| Self::parse(entry)).collect())
+            .transpose()
+    }
+
+    fn parse(entry: &[u8]) -> Result<T> {
+        utils::deserialise(entry)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::SeqData;
     use crate::{
-        utils, Error, Keypair, Result, Sequence, SequenceAddress, SequenceEntry, SequenceIndex,
-        SequenceKind, SequenceOp, SequencePermissions, SequencePrivatePermissions,
+        utils, Error, Keypair, Result, Sequence, SequenceAction, SequenceAddress,
+        SequenceAppendStats, SequenceCursor, SequenceEntry, SequenceEntryKind, SequenceEntryMeta,
+        SequenceEntryValidator, SequenceIndex, SequenceKind, SequenceOp, SequenceOpBuffer,
+        SequencePermissions, SequencePolicyTemplate, SequencePrivatePermissions,
         SequencePrivatePolicy, SequencePublicPermissions, SequencePublicPolicy, SequenceUser,
+        Signature, ThresholdKey, TypedSequence,
     };
     use anyhow::anyhow;
     use proptest::prelude::*;
     use rand::rngs::OsRng;
     use std::{collections::BTreeMap, sync::Arc};
+    use threshold_crypto::SecretKeySet;
     use xor_name::XorName;
 
+    #[test]
+    fn sequence_threshold_owner() -> anyhow::Result<()> {
+        let sk_set = SecretKeySet::random(1, &mut OsRng);
+        let group = ThresholdKey::new(sk_set.public_keys());
+        let payload = b"request";
+        let shares: Vec<_> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).sign(payload)))
+            .collect();
+        let sig = Signature::Bls(
+            group
+                .pk_set()
+                .combine_signatures(shares.iter().map(|(i, share)| (*i, share)))
+                .map_err(|_| Error::InvalidSignature)?,
+        );
+
+        let sequence = Sequence::new_public(
+            group.public_key(),
+            "group".to_string(),
+            XorName::random(),
+            43_000,
+            None,
+        );
+        assert_eq!(sequence.owner(), group.public_key());
+        sequence.check_threshold_owner(&group, payload, &sig)?;
+        assert_eq!(
+            sequence.check_threshold_owner(&group, payload, &Signature::from(shares[0].clone())),
+            Err(Error::InvalidSignature)
+        );
+
+        let other = ThresholdKey::new(SecretKeySet::random(1, &mut OsRng).public_keys());
+        assert_eq!(
+            sequence.check_threshold_owner(&other, payload, &sig),
+            Err(Error::AccessDenied(other.public_key()))
+        );
+        Ok(())
+    }
+
     #[test]
     fn sequence_create_public() {
         let sequence_name = XorName::random();
@@ -319,10 +1045,83 @@ mod tests {
         assert_eq!(*sequence.address(), sequence_address);
     }
 
+    // Public Sequence of an Ed25519 owner, which a user is allowed to append to,
+    // holding two entries, encoded before the layout of Sequences was versioned.
+    const PUBLIC_SEQUENCE_V0: &str = "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c0000000005000000000000006163746f72000000000505050505050505050505050505050505050505050505050505050505050505803e000000000000020000000000000001000000000000000000000000000000000101000000000000000100000005000000000000006163746f72010000000000000003000000000000006f6e650100000000000000010100000000000000010000000101000000000000000100000005000000000000006163746f720200000000000000030000000000000074776f010000000000000005000000000000006163746f7202000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c0100000000000000010000000000000020000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940101";
+    // Private Sequence of an Ed25519 owner, which a user is allowed to read and append
+    // to, holding a single entry, encoded before the layout of Sequences was versioned.
+    const PRIVATE_SEQUENCE_V0: &str = "0000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c0100000005000000000000006163746f72010000000606060606060606060606060606060606060606060606060606060606060606813e000000000000010000000000000001000000000000000000000000000000000101000000000000000100000005000000000000006163746f7201000000000000000600000000000000736563726574010000000000000005000000000000006163746f7201000000000000000000000020000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c01000000000000000000000020000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940101";
+
     #[test]
-    fn sequence_concurrent_append_ops() -> Result<()> {
-        let authority_keypair1 = Keypair::new_ed25519(&mut OsRng);
-        let authority1 = authority_keypair1.public_key();
+    fn sequence_decodes_earliest_layout() -> anyhow::Result<()> {
+        let keypair = |byte| {
+            Ok::<_, anyhow::Error>(Keypair::from(ed25519_dalek::SecretKey::from_bytes(
+                &[byte; 32],
+            )?))
+        };
+        let owner_keypair = keypair(1)?;
+        let owner = owner_keypair.public_key();
+        let user = keypair(2)?.public_key();
+
+        let mut sequence: Sequence = utils::deserialise(&hex::decode(PUBLIC_SEQUENCE_V0)?)?;
+        assert_eq!(sequence.replica_authority(), owner);
+        assert_eq!(
+            *sequence.address(),
+            SequenceAddress::Public {
+                name: XorName([5; 32]),
+                tag: 16_000
+            }
+        );
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            SequenceUser::Key(user),
+            SequencePublicPermissions::new(true),
+        );
+        assert_eq!(
+            sequence.public_policy()?,
+            &SequencePublicPolicy {
+                owner,
+                permissions,
+                validator: None,
+            }
+        );
+        assert_eq!(sequence.len(None)?, 2);
+        assert_eq!(
+            sequence.get(SequenceIndex::FromStart(0), None)?,
+            Some(&b"one".to_vec())
+        );
+        // Entries are recorded as appended by the owner
+        let meta = SequenceEntryMeta {
+            author: owner,
+            timestamp: None,
+        };
+        assert_eq!(
+            sequence.get_with_meta(SequenceIndex::FromEnd(1), None)?,
+            Some((&b"two".to_vec(), &meta))
+        );
+        assert_eq!(sequence.stats(None)?[&owner].appends, 2);
+
+        // It can be appended to, and is encoded with the latest layout from then on
+        let op = sequence.create_unsigned_append_op(b"three".to_vec())?;
+        sequence.apply_op(sign_sequence_op(op, &owner_keypair)?)?;
+        assert_eq!(sequence.len(None)?, 3);
+        let decoded: Sequence = utils::deserialise(&utils::serialise(&sequence)?)?;
+        assert_eq!(decoded, sequence);
+
+        let sequence: Sequence = utils::deserialise(&hex::decode(PRIVATE_SEQUENCE_V0)?)?;
+        assert_eq!(sequence.len(Some(user))?, 1);
+        assert_eq!(
+            sequence.permissions(SequenceUser::Key(user), Some(owner))?,
+            SequencePermissions::Private(SequencePrivatePermissions::new(true, true))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_concurrent_append_ops() -> Result<()> {
+        let authority_keypair1 = Keypair::new_ed25519(&mut OsRng);
+        let authority1 = authority_keypair1.public_key();
         let authority_keypair2 = Keypair::new_ed25519(&mut OsRng);
         let authority2 = authority_keypair2.public_key();
         let sequence_name: XorName = rand::random();
@@ -343,6 +1142,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: authority1,
                 permissions: perms.clone(),
+                validator: None,
             }),
         );
         let mut replica2 = Sequence::new_public(
@@ -353,6 +1153,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: authority1,
                 permissions: perms,
+                validator: None,
             }),
         );
 
@@ -452,6 +1253,1022 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sequence_append_if_last() -> anyhow::Result<()> {
+        // Both replicas are written to with the same authority, each as an actor of its own
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let authority = keypair.public_key();
+        let name = XorName::random();
+        let mut replica1 =
+            Sequence::new_public(authority, "replica1".to_string(), name, 43_000, None);
+        let mut replica2 =
+            Sequence::new_public(authority, "replica2".to_string(), name, 43_000, None);
+
+        // Both replicas expect an empty Sequence
+        let op1 = sign_sequence_op(replica1.append_if_last(b"first".to_vec(), None)?, &keypair)?;
+        let op2 = sign_sequence_op(replica2.append_if_last(b"other".to_vec(), None)?, &keypair)?;
+
+        // The concurrent ops reach the replicas in opposite orders, and are both
+        // applied by each, the precondition being advisory once the op is created
+        replica1.apply_op(op1.clone())?;
+        replica1.apply_op(op2.clone())?;
+        replica2.apply_op(op2)?;
+        replica2.apply_op(op1)?;
+        assert_eq!(replica1.len(None)?, 2);
+        assert_eq!(
+            replica1.last_entry_hash(None)?,
+            replica2.last_entry_hash(None)?
+        );
+
+        // A stale precondition fails locally, a current one succeeds
+        let last_hash = replica2.last_entry_hash(None)?;
+        match replica2.append_if_last(b"stale".to_vec(), None) {
+            Err(Error::LastEntryMismatch { expected, actual }) => {
+                assert_eq!(expected, None);
+                assert_eq!(actual, last_hash);
+            }
+            other => return Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+        let op3 = sign_sequence_op(
+            replica2.append_if_last(b"third".to_vec(), last_hash)?,
+            &keypair,
+        )?;
+        replica2.apply_op(op3.clone())?;
+        replica1.apply_op(op3)?;
+
+        verify_data_convergence(vec![replica1, replica2], 3)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_append_if_last_precondition_is_signed() -> anyhow::Result<()> {
+        let mut replicas = create_public_seq_replicas(1);
+        let (keypair, sequence) = &mut replicas[0];
+
+        let mut op = sign_sequence_op(sequence.append_if_last(b"entry".to_vec(), None)?, keypair)?;
+        // Stripping the precondition invalidates the signature
+        op.expected_last = None;
+        assert_eq!(sequence.apply_op(op), Err(Error::InvalidSignature));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_append_stats() -> anyhow::Result<()> {
+        let mut replicas = create_public_seq_replicas(1);
+        let (keypair, sequence) = &mut replicas[0];
+        let author = keypair.public_key();
+
+        let op1 = sign_sequence_op(
+            sequence.create_unsigned_append_op(b"first".to_vec())?,
+            keypair,
+        )?;
+        sequence.apply_op(op1.clone())?;
+        let op2 = sign_sequence_op(
+            sequence.create_unsigned_append_op(b"2nd".to_vec())?,
+            keypair,
+        )?;
+        sequence.apply_op(op2)?;
+        // Re-applying an op doesn't count the entry twice
+        assert_eq!(sequence.apply_op(op1), Err(Error::OpAlreadyApplied));
+
+        let stats = sequence.stats(None)?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(
+            stats.get(&author),
+            Some(&SequenceAppendStats {
+                appends: 2,
+                bytes: 8
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_duplicate_op_delivery() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replicas = gen_priv_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            1,
+        );
+        let (_, mut replica1) = replicas.remove(0);
+
+        let append_op = sign_sequence_op(
+            replica1.create_unsigned_append_op(b"entry".to_vec())?,
+            &owner_keypair,
+        )?;
+        replica1.apply_op(append_op.clone())?;
+        assert_eq!(
+            replica1.apply_op(append_op.clone()),
+            Err(Error::OpAlreadyApplied)
+        );
+        assert_eq!(replica1.len(None)?, 1);
+
+        // A replayed append doesn't bring back a trimmed entry
+        for op in replica1.trim_front(1)? {
+            replica1.apply_op(sign_sequence_op(op, &owner_keypair)?)?;
+        }
+        assert_eq!(replica1.apply_op(append_op), Err(Error::OpAlreadyApplied));
+        assert_eq!(replica1.len(None)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_merge_replica_state() -> anyhow::Result<()> {
+        // Both replicas are written to by the owner, each as an actor of its own
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let name = XorName::random();
+        let mut replica1 = Sequence::new_private(owner, "replica1".to_string(), name, 43_000, None);
+        let mut replica2 = Sequence::new_private(owner, "replica2".to_string(), name, 43_000, None);
+
+        // replica2 only gets the first of replica1's ops
+        let mut ops = vec![];
+        for entry in [b"value0".to_vec(), b"value1".to_vec(), b"value2".to_vec()] {
+            let op = sign_sequence_op(replica1.create_unsigned_append_op(entry)?, &owner_keypair)?;
+            replica1.apply_op(op.clone())?;
+            ops.push(op);
+        }
+        replica2.apply_op(ops[0].clone())?;
+        let op = sign_sequence_op(
+            replica2.create_unsigned_append_op(b"value3".to_vec())?,
+            &owner_keypair,
+        )?;
+        replica2.apply_op(op)?;
+        let op = sign_sequence_op(
+            replica1.remove(SequenceIndex::FromStart(0))?,
+            &owner_keypair,
+        )?;
+        replica1.apply_op(op)?;
+
+        replica2.merge(&replica1)?;
+        replica1.merge(&replica2)?;
+        verify_data_convergence(vec![replica1.clone(), replica2.clone()], 3)?;
+        let entries = replica2
+            .in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?
+            .unwrap_or_default();
+        assert!(entries.contains(&b"value3".to_vec()));
+        assert!(!entries.contains(&b"value0".to_vec()));
+
+        // Ops reflected in the merged state are not applied again
+        assert_eq!(
+            replica2.apply_op(ops.remove(1)),
+            Err(Error::OpAlreadyApplied)
+        );
+
+        let other = create_private_seq_replica_with(Some(owner_keypair), None);
+        assert_eq!(replica1.merge(&other), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_merge_rejects_forged_state() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user = user_keypair.public_key();
+        let mut replicas = gen_priv_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            2,
+        );
+        let (_, mut peer) = replicas.remove(1);
+        let (_, mut replica) = replicas.remove(0);
+
+        let op = sign_sequence_op(
+            peer.create_unsigned_append_op(b"value0".to_vec())?,
+            &owner_keypair,
+        )?;
+        peer.apply_op(op)?;
+
+        // The peer skipped the permission checks, taking an append of a user who can't
+        let forged = match &mut peer.data {
+            SeqData::Private(data) => {
+                let op = data.create_append_op(b"forged".to_vec(), user)?;
+                let op = sign_sequence_op(op, &user_keypair)?;
+                data.apply_op(op)
+            }
+            SeqData::Public(_) => Err(Error::InvalidOperation),
+        };
+        assert_eq!(forged, Ok(()));
+        assert_eq!(peer.len(None)?, 2);
+
+        let before = replica.clone();
+        check_op_not_allowed_failure(replica.merge(&peer))?;
+        assert_eq!(replica, before);
+        assert_eq!(replica.len(None)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_merge_replica_bootstrapped_from_snapshot() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let name = XorName::random();
+        let mut replica1 = Sequence::new_private(owner, "replica1".to_string(), name, 43_000, None);
+
+        let op = sign_sequence_op(
+            replica1.create_unsigned_append_op(b"value0".to_vec())?,
+            &owner_keypair,
+        )?;
+        replica1.apply_op(op)?;
+        let mut op = replica1.append_batch(vec![b"value1".to_vec(), b"value2".to_vec()])?;
+        op.signature = Some(owner_keypair.sign(&op.bytes_to_sign()?));
+        replica1.apply_batch_op(op)?;
+
+        let mut snapshot = replica1.snapshot(None)?;
+        snapshot.signature = Some(owner_keypair.sign(&snapshot.bytes_to_sign()?));
+        let mut replica2 = Sequence::from_snapshot(owner, "replica2".to_string(), snapshot)?;
+
+        // A replica holding no more than a snapshot still hands its entries over
+        let mut replica3 = Sequence::new_private(owner, "replica3".to_string(), name, 43_000, None);
+        replica3.merge(&replica2)?;
+        verify_data_convergence(vec![replica1.clone(), replica3.clone()], 3)?;
+
+        // along with the entries it removes, including those appended in a batch
+        let op = sign_sequence_op(
+            replica2.remove(SequenceIndex::FromStart(1))?,
+            &owner_keypair,
+        )?;
+        replica2.apply_op(op)?;
+        replica3.merge(&replica2)?;
+        replica1.merge(&replica3)?;
+        verify_data_convergence(vec![replica1.clone(), replica2, replica3], 2)?;
+        let entries = replica1
+            .in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?
+            .unwrap_or_default();
+        assert_eq!(entries, vec![b"value0".to_vec(), b"value2".to_vec()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_append_batch() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replicas = gen_pub_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            2,
+        );
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        let entries = vec![b"value0".to_vec(), b"value1".to_vec(), b"value2".to_vec()];
+        let mut op = replica1.append_batch(entries.clone())?;
+        op.signature = Some(owner_keypair.sign(&op.bytes_to_sign()?));
+        assert_eq!(op.crdt_ops.len(), 3);
+
+        for replica in &mut [&mut replica1, &mut replica2] {
+            replica.apply_batch_op(op.clone())?;
+        }
+        verify_data_convergence(vec![replica1.clone(), replica2.clone()], 3)?;
+        assert_eq!(
+            replica2.in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?,
+            Some(entries)
+        );
+
+        // A batch is applied only once
+        assert_eq!(replica2.apply_batch_op(op), Err(Error::OpAlreadyApplied));
+        assert_eq!(replica2.len(None)?, 3);
+
+        assert_eq!(replica1.append_batch(vec![]), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_buffer_ops_not_causally_ready() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replicas = gen_priv_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            2,
+        );
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        let append_op = sign_sequence_op(
+            replica1.create_unsigned_append_op(b"value0".to_vec())?,
+            &owner_keypair,
+        )?;
+        replica1.apply_op(append_op.clone())?;
+        let remove_op = sign_sequence_op(
+            replica1.remove(SequenceIndex::FromStart(0))?,
+            &owner_keypair,
+        )?;
+        replica1.apply_op(remove_op.clone())?;
+
+        // The removal reaches replica2 before the entry it removes
+        assert_eq!(
+            replica2.apply_op(remove_op.clone()),
+            Err(Error::OpNotCausallyReady)
+        );
+        let mut buffer = SequenceOpBuffer::new();
+        buffer.apply(&mut replica2, remove_op)?;
+        assert_eq!(buffer.buffered_count(), 1);
+        assert!(buffer.drain_ready(&mut replica2).is_empty());
+
+        buffer.apply(&mut replica2, append_op)?;
+        assert_eq!(buffer.buffered_count(), 0);
+        verify_data_convergence(vec![replica1, replica2], 0)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_op_buffer_limit() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let name = XorName::random();
+        let mut replica1 = Sequence::new_private(owner, "replica1".to_string(), name, 43_000, None);
+        let mut replica2 = Sequence::new_private(owner, "replica2".to_string(), name, 43_000, None);
+
+        let mut remove_ops = vec![];
+        for entry in [b"value0".to_vec(), b"value1".to_vec(), b"value2".to_vec()] {
+            let op = sign_sequence_op(replica1.create_unsigned_append_op(entry)?, &owner_keypair)?;
+            replica1.apply_op(op)?;
+        }
+        for op in replica1.truncate(0)? {
+            let op = sign_sequence_op(op, &owner_keypair)?;
+            replica1.apply_op(op.clone())?;
+            remove_ops.push(op);
+        }
+
+        // None of the removals is ready on replica2, which only holds two of them
+        let mut buffer = SequenceOpBuffer::with_max_per_source(2);
+        buffer.apply(&mut replica2, remove_ops[0].clone())?;
+        buffer.apply(&mut replica2, remove_ops[1].clone())?;
+        assert_eq!(
+            buffer.apply(&mut replica2, remove_ops[2].clone()),
+            Err(Error::OpBufferFull(owner))
+        );
+        assert_eq!(buffer.buffered_count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_entry_kinds() -> anyhow::Result<()> {
+        let (keypair, mut replica) =
+            gen_pub_seq_replicas(None, XorName::random(), 43_000, None, 1).remove(0);
+        let entries = vec![
+            (SequenceEntryKind::Text, &b"hello"[..]),
+            (SequenceEntryKind::Ref, &b"address"[..]),
+            (SequenceEntryKind::Tombstone, &b""[..]),
+            (SequenceEntryKind::Custom(200), &b"app data"[..]),
+        ];
+        for (kind, payload) in entries.iter() {
+            let op = sign_sequence_op(replica.append_typed(*kind, payload)?, &keypair)?;
+            replica.apply_op(op)?;
+        }
+
+        for (index, (kind, payload)) in entries.into_iter().enumerate() {
+            assert_eq!(
+                replica.get_typed(SequenceIndex::FromStart(index as u64), None)?,
+                Some((kind, payload))
+            );
+        }
+        assert_eq!(replica.get_typed(SequenceIndex::FromStart(4), None)?, None);
+
+        // Reserved kinds can't be appended, nor read
+        assert!(matches!(
+            replica.append_typed(SequenceEntryKind::Custom(3), b""),
+            Err(Error::FailedToParse(_))
+        ));
+        let op = sign_sequence_op(replica.create_unsigned_append_op(vec![3])?, &keypair)?;
+        replica.apply_op(op)?;
+        assert!(matches!(
+            replica.get_typed(SequenceIndex::FromEnd(1), None),
+            Err(Error::FailedToParse(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_commitment_proofs() -> anyhow::Result<()> {
+        let (keypair, mut replica) =
+            gen_pub_seq_replicas(None, XorName::random(), 43_000, None, 1).remove(0);
+        assert!(replica.commitment(None)?.is_empty());
+        assert_eq!(
+            replica.open(SequenceIndex::FromStart(0), None),
+            Err(Error::NoSuchEntry)
+        );
+
+        for entry in [b"a", b"b", b"c", b"d", b"e"] {
+            let op =
+                sign_sequence_op(replica.create_unsigned_append_op(entry.to_vec())?, &keypair)?;
+            replica.apply_op(op)?;
+        }
+        let commitment = replica.commitment(None)?;
+        assert_eq!(commitment.len(), 5);
+
+        for index in 0..5 {
+            let proof = replica.open(SequenceIndex::FromStart(index), None)?;
+            assert_eq!(proof.index(), index);
+            assert!(commitment.verify(&proof));
+        }
+        let last = replica.open(SequenceIndex::FromEnd(1), None)?;
+        assert_eq!(last.entry(), &b"e".to_vec());
+
+        // A proof doesn't hold against a commitment to other entries
+        let op = sign_sequence_op(replica.create_unsigned_append_op(b"f".to_vec())?, &keypair)?;
+        replica.apply_op(op)?;
+        assert!(!replica.commitment(None)?.verify(&last));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_entry_validator() -> anyhow::Result<()> {
+        struct MaxLen(usize);
+        impl SequenceEntryValidator for MaxLen {
+            fn config_hash(&self) -> [u8; 32] {
+                let mut hash = [0; 32];
+                hash[..8].copy_from_slice(&(self.0 as u64).to_le_bytes());
+                hash
+            }
+            fn validate(&self, entry: &[u8], _meta: &SequenceEntryMeta) -> Result<()> {
+                if entry.len() > self.0 {
+                    Err(Error::SchemaViolation("entry too long".to_string()))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let validator = MaxLen(6);
+        let policy = SequencePrivatePolicy {
+            owner: owner_keypair.public_key(),
+            permissions: BTreeMap::default(),
+            validator: Some(validator.config_hash()),
+        };
+        let mut replica =
+            create_private_seq_replica_with(Some(owner_keypair.clone()), Some(policy));
+
+        let op = sign_sequence_op(
+            replica.create_unsigned_append_op(b"value0".to_vec())?,
+            &owner_keypair,
+        )?;
+        assert_eq!(replica.apply_op(op.clone()), Err(Error::ValidatorMismatch));
+        assert_eq!(
+            replica.apply_op_with_validator(op.clone(), &MaxLen(7)),
+            Err(Error::ValidatorMismatch)
+        );
+        replica.apply_op_with_validator(op, &validator)?;
+
+        let op = sign_sequence_op(
+            replica.create_unsigned_append_op(b"value01".to_vec())?,
+            &owner_keypair,
+        )?;
+        assert!(matches!(
+            replica.apply_op_with_validator(op, &validator),
+            Err(Error::SchemaViolation(_))
+        ));
+
+        let mut op = replica.append_batch(vec![b"value1".to_vec(), b"value12".to_vec()])?;
+        op.signature = Some(owner_keypair.sign(&op.bytes_to_sign()?));
+        assert!(matches!(
+            replica.apply_batch_op_with_validator(op, &validator),
+            Err(Error::SchemaViolation(_))
+        ));
+        assert_eq!(replica.len(None)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_read_pages() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let mut replica = create_private_seq_replica_with(Some(owner_keypair.clone()), None);
+        for i in 0..5u8 {
+            let op = sign_sequence_op(replica.create_unsigned_append_op(vec![i])?, &owner_keypair)?;
+            replica.apply_op(op)?;
+        }
+
+        let mut pages = vec![];
+        let mut cursor = Some(SequenceCursor::start());
+        while let Some(next) = cursor {
+            let page = replica.iter_from(next, 2, None)?;
+            cursor = page.next;
+            pages.push(page.entries);
+        }
+        assert_eq!(
+            pages,
+            vec![
+                vec![vec![0], vec![1]],
+                vec![vec![2], vec![3]],
+                vec![vec![4]]
+            ]
+        );
+
+        // A page reaching the end has no next cursor, and a cursor
+        // beyond the end of the Sequence doesn't point at any entry
+        let page = replica.iter_from(SequenceCursor::start(), 5, None)?;
+        assert_eq!(page.entries.len(), 5);
+        assert_eq!(page.next, None);
+        assert_eq!(
+            replica.iter_from(SequenceCursor::new(6), 5, None),
+            Err(Error::NoSuchEntry)
+        );
+
+        // An empty page wouldn't ever get to the end of the Sequence
+        assert_eq!(
+            replica.iter_from(SequenceCursor::start(), 0, None),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_permissions_expire() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user = user_keypair.public_key();
+
+        let mut perms = SequencePrivatePermissions::new(true, true);
+        perms.set_delete(true);
+        perms.set_expires_at(Some(1_000));
+        let mut policy = SequencePolicyTemplate::owner_only(owner);
+        let _ = policy.permissions.insert(user, perms);
+        let replica =
+            create_private_seq_replica_with(Some(owner_keypair.clone()), Some(policy.clone()));
+
+        replica.check_permission_at(SequenceAction::Read, Some(user), Some(1_000))?;
+        check_op_not_allowed_failure(replica.check_permission_at(
+            SequenceAction::Read,
+            Some(user),
+            Some(1_001),
+        ))?;
+        replica.check_permission_at(SequenceAction::Read, Some(owner), Some(1_001))?;
+
+        // Without a time to check them at, permissions which expire are treated as lapsed
+        check_op_not_allowed_failure(replica.check_permission(SequenceAction::Read, Some(user)))?;
+        check_op_not_allowed_failure(replica.len(Some(user)))?;
+
+        // The time an append claims to be made at is only checked by its source
+        let mut user_replica = Sequence::new_private(
+            user,
+            user.to_string(),
+            *replica.name(),
+            replica.tag(),
+            Some(policy.clone()),
+        );
+        let mut node_replica = replica;
+        let op = sign_sequence_op(
+            user_replica.create_unsigned_timestamped_append_op(b"value0".to_vec(), 900)?,
+            &user_keypair,
+        )?;
+        check_op_not_allowed_failure(
+            user_replica.create_unsigned_timestamped_append_op(b"value1".to_vec(), 1_100),
+        )?;
+        check_op_not_allowed_failure(user_replica.create_unsigned_append_op(b"value1".to_vec()))?;
+        check_op_not_allowed_failure(node_replica.clone().apply_op(op.clone()))?;
+
+        // Nodes check ops at their own time rather than the one claimed
+        check_op_not_allowed_failure(node_replica.clone().apply_op_at(op.clone(), 1_100))?;
+        node_replica.apply_op_at(op, 950)?;
+
+        // Merging the replica holding it doesn't depend on the time of the merge
+        let mut merged = Sequence::new_private(
+            owner,
+            "merged".to_string(),
+            *node_replica.name(),
+            node_replica.tag(),
+            Some(policy.clone()),
+        );
+        merged.merge(&node_replica)?;
+        assert_eq!(merged.len(None)?, 1);
+
+        // Removals included, once the permission to remove entries expired
+        let mut remove_op = node_replica.remove(SequenceIndex::FromStart(0))?;
+        remove_op.source = user;
+        let remove_op = sign_sequence_op(remove_op, &user_keypair)?;
+        check_op_not_allowed_failure(node_replica.clone().apply_op(remove_op.clone()))?;
+        check_op_not_allowed_failure(node_replica.clone().apply_op_at(remove_op.clone(), 1_001))?;
+        node_replica.apply_op_at(remove_op, 1_000)?;
+        assert_eq!(node_replica.len(None)?, 0);
+        merged.merge(&node_replica)?;
+        assert_eq!(merged.len(None)?, 0);
+
+        // Public grants lapse back to the permissions for anyone
+        let mut perms = SequencePublicPermissions::new(true);
+        perms.set_expires_at(Some(1_000));
+        let mut policy = SequencePolicyTemplate::public_feed(owner);
+        let _ = policy.permissions.insert(SequenceUser::Key(user), perms);
+        let (_, feed) =
+            gen_pub_seq_replicas(None, XorName::random(), 43_000, Some(policy), 1).remove(0);
+        feed.check_permission_at(SequenceAction::Append, Some(user), Some(1_000))?;
+        check_op_not_allowed_failure(feed.check_permission_at(
+            SequenceAction::Append,
+            Some(user),
+            Some(1_001),
+        ))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_policy_templates() -> anyhow::Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        let member = Keypair::new_ed25519(&mut OsRng).public_key();
+        let other = Keypair::new_ed25519(&mut OsRng).public_key();
+
+        let (_, feed) = gen_pub_seq_replicas(
+            None,
+            XorName::random(),
+            43_000,
+            Some(SequencePolicyTemplate::public_feed(owner)),
+            1,
+        )
+        .remove(0);
+        feed.check_permission(SequenceAction::Append, Some(owner))?;
+        feed.check_permission(SequenceAction::Read, Some(other))?;
+        check_op_not_allowed_failure(feed.check_permission(SequenceAction::Append, Some(other)))?;
+
+        let group = create_private_seq_replica_with(
+            None,
+            Some(SequencePolicyTemplate::group_append(owner, vec![member])),
+        );
+        group.check_permission(SequenceAction::Append, Some(member))?;
+        group.check_permission(SequenceAction::Read, Some(member))?;
+        check_op_not_allowed_failure(group.check_permission(SequenceAction::Delete, Some(member)))?;
+        check_op_not_allowed_failure(group.check_permission(SequenceAction::Read, Some(other)))?;
+
+        let private =
+            create_private_seq_replica_with(None, Some(SequencePolicyTemplate::owner_only(owner)));
+        private.check_permission(SequenceAction::Append, Some(owner))?;
+        check_op_not_allowed_failure(private.check_permission(SequenceAction::Read, Some(member)))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_entries_meta() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let (_, mut replica) = gen_priv_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            1,
+        )
+        .remove(0);
+
+        for (entry, timestamp) in [
+            (b"value0".to_vec(), Some(1_000)),
+            (b"value1".to_vec(), None),
+            (b"value2".to_vec(), Some(3_000)),
+        ] {
+            let op = match timestamp {
+                Some(timestamp) => {
+                    replica.create_unsigned_timestamped_append_op(entry, timestamp)?
+                }
+                None => replica.create_unsigned_append_op(entry)?,
+            };
+            replica.apply_op(sign_sequence_op(op, &owner_keypair)?)?;
+        }
+
+        // Changing the timestamp of a signed op invalidates its signature
+        let mut op = sign_sequence_op(
+            replica.create_unsigned_timestamped_append_op(b"value3".to_vec(), 4_000)?,
+            &owner_keypair,
+        )?;
+        op.timestamp = Some(1);
+        assert_eq!(replica.apply_op(op), Err(Error::InvalidSignature));
+
+        let meta = |timestamp| SequenceEntryMeta {
+            author: owner,
+            timestamp,
+        };
+        assert_eq!(
+            replica.get_with_meta(SequenceIndex::FromStart(1), None)?,
+            Some((&b"value1".to_vec(), &meta(None)))
+        );
+
+        // Metadata of removed entries is dropped along with them
+        let op = sign_sequence_op(replica.remove(SequenceIndex::FromStart(0))?, &owner_keypair)?;
+        replica.apply_op(op)?;
+        assert_eq!(
+            replica.in_range_with_meta(
+                SequenceIndex::FromStart(0),
+                SequenceIndex::FromEnd(0),
+                None
+            )?,
+            Some(vec![
+                (b"value1".to_vec(), meta(None)),
+                (b"value2".to_vec(), meta(Some(3_000))),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_typed_entries() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let (_, replica) = gen_pub_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            1,
+        )
+        .remove(0);
+        let mut sequence = TypedSequence::<(String, u64)>::new(replica);
+
+        for value in [("a".to_string(), 1), ("b".to_string(), 2)] {
+            let op = sign_sequence_op(sequence.append(&value)?, &owner_keypair)?;
+            sequence.apply_op(op)?;
+        }
+
+        assert_eq!(sequence.len(None)?, 2);
+        assert_eq!(
+            sequence.get(SequenceIndex::FromStart(1), None)?,
+            Some(("b".to_string(), 2))
+        );
+        assert_eq!(sequence.last_entry(None)?, Some(("b".to_string(), 2)));
+        assert_eq!(
+            sequence.in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?,
+            Some(vec![("a".to_string(), 1), ("b".to_string(), 2)])
+        );
+
+        // Entries of another type can't be read as such
+        let sequence = TypedSequence::<(u64, u64, u64)>::new(sequence.into_data());
+        match sequence.get(SequenceIndex::FromStart(0), None) {
+            Err(Error::Serialisation(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_sequence_entries() -> anyhow::Result<()> {
+        use crate::CompressedSequence;
+
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let (_, replica) = gen_pub_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            1,
+        )
+        .remove(0);
+        let mut sequence = CompressedSequence::new(replica);
+
+        let small = b"small".to_vec();
+        let large = b"log line\n".repeat(1_000);
+        for entry in &[&small, &large] {
+            let op = sign_sequence_op(sequence.append(entry)?, &owner_keypair)?;
+            sequence.apply_op(op)?;
+        }
+
+        // Entries are read as appended, though the large one is stored compressed
+        assert_eq!(
+            sequence.get(SequenceIndex::FromStart(0), None)?,
+            Some(small.clone())
+        );
+        assert_eq!(sequence.last_entry(None)?, Some(large.clone()));
+        assert_eq!(
+            sequence.in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?,
+            Some(vec![small, large.clone()])
+        );
+        let stored = sequence.data().last_entry(None)?.cloned();
+        assert!(stored.is_some_and(|stored| stored.len() < large.len()));
+
+        // An entry appended without a known flag, as another client could, isn't applied
+        let mut data = sequence.data().clone();
+        let op = sign_sequence_op(data.create_unsigned_append_op(vec![7, 0])?, &owner_keypair)?;
+        assert!(matches!(
+            sequence.apply_op(op),
+            Err(Error::Serialisation(_))
+        ));
+        assert_eq!(sequence.len(None)?, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_bootstrap_from_snapshot() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let mut replicas = gen_priv_seq_replicas(
+            Some(owner_keypair.clone()),
+            XorName::random(),
+            43_000,
+            None,
+            1,
+        );
+        let (_, mut replica1) = replicas.remove(0);
+
+        let mut ops = vec![];
+        for entry in [b"value0".to_vec(), b"value1".to_vec()] {
+            let op = sign_sequence_op(replica1.create_unsigned_append_op(entry)?, &owner_keypair)?;
+            replica1.apply_op(op.clone())?;
+            ops.push(op);
+        }
+
+        let mut snapshot = replica1.snapshot(None)?;
+        snapshot.signature = Some(owner_keypair.sign(&snapshot.bytes_to_sign()?));
+        let mut replica2 =
+            Sequence::from_snapshot(owner, "replica2".to_string(), snapshot.clone())?;
+        verify_data_convergence(vec![replica1.clone(), replica2.clone()], 2)?;
+
+        // Ops applied before the snapshot are rejected if replayed
+        assert_eq!(
+            replica2.apply_op(ops.remove(0)),
+            Err(Error::OpAlreadyApplied)
+        );
+
+        // and later ops from both replicas merge as usual
+        let op1 = sign_sequence_op(
+            replica1.create_unsigned_append_op(b"value2".to_vec())?,
+            &owner_keypair,
+        )?;
+        let op2 = sign_sequence_op(
+            replica2.create_unsigned_append_op(b"value3".to_vec())?,
+            &owner_keypair,
+        )?;
+        for replica in &mut [&mut replica1, &mut replica2] {
+            replica.apply_op(op1.clone())?;
+            replica.apply_op(op2.clone())?;
+        }
+        verify_data_convergence(vec![replica1, replica2], 4)?;
+
+        // A tampered snapshot is rejected
+        let mut tampered = snapshot.clone();
+        tampered.checkpoint[0] ^= 1;
+        assert_eq!(
+            Sequence::from_snapshot(owner, "replica3".to_string(), tampered),
+            Err(Error::CrdtUnexpectedState)
+        );
+
+        // and so is one not signed, or signed by neither the owner nor this replica's authority
+        let mut unsigned = snapshot.clone();
+        unsigned.signature = None;
+        assert_eq!(
+            Sequence::from_snapshot(owner, "replica3".to_string(), unsigned),
+            Err(Error::CrdtMissingOpSignature)
+        );
+        let other_keypair = Keypair::new_ed25519(&mut OsRng);
+        let other = other_keypair.public_key();
+        let mut untrusted = snapshot;
+        untrusted.source = other;
+        untrusted.signature = Some(other_keypair.sign(&untrusted.bytes_to_sign()?));
+        assert_eq!(
+            Sequence::from_snapshot(owner, "replica3".to_string(), untrusted.clone()),
+            Err(Error::AccessDenied(other))
+        );
+        let _ = Sequence::from_snapshot(other, "replica3".to_string(), untrusted)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_private_trim_front() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user = user_keypair.public_key();
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(user, SequencePrivatePermissions::new(true, true));
+        let policy = SequencePrivatePolicy {
+            owner,
+            permissions: perms,
+            validator: None,
+        };
+        let mut sequence =
+            create_private_seq_replica_with(Some(owner_keypair.clone()), Some(policy.clone()));
+
+        let entries = vec![b"value0".to_vec(), b"value1".to_vec(), b"value2".to_vec()];
+        for entry in &entries {
+            let op = sign_sequence_op(
+                sequence.create_unsigned_append_op(entry.clone())?,
+                &owner_keypair,
+            )?;
+            sequence.apply_op(op)?;
+        }
+
+        // A user with Append permissions can't trim
+        let mut user_replica = create_private_seq_replica_with(Some(user_keypair), Some(policy));
+        check_op_not_allowed_failure(user_replica.trim_front(1))?;
+
+        let trim_ops = sequence.trim_front(2)?;
+        assert_eq!(trim_ops.len(), 2);
+        for op in trim_ops {
+            let op = sign_sequence_op(op, &owner_keypair)?;
+            // Nor can they sign trim ops of their own
+            let mut forged = op.clone();
+            forged.source = user;
+            check_op_not_allowed_failure(sequence.apply_op(forged))?;
+            sequence.apply_op(op)?;
+        }
+
+        assert_eq!(sequence.len(None)?, 1);
+        assert_eq!(sequence.last_entry(None)?, Some(&entries[2]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_private_remove_and_truncate() -> anyhow::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let user_keypair = Keypair::new_ed25519(&mut OsRng);
+        let user = user_keypair.public_key();
+        let mut user_perms = SequencePrivatePermissions::new(true, true);
+        user_perms.set_delete(true);
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(user, user_perms);
+        let policy = SequencePrivatePolicy {
+            owner,
+            permissions: perms,
+            validator: None,
+        };
+        let mut sequence =
+            create_private_seq_replica_with(Some(owner_keypair.clone()), Some(policy.clone()));
+        let mut user_replica =
+            create_private_seq_replica_with(Some(user_keypair.clone()), Some(policy));
+
+        let entries: Vec<_> = (0..5).map(|i| format!("value{}", i).into_bytes()).collect();
+        for entry in &entries {
+            let op = sign_sequence_op(
+                sequence.create_unsigned_append_op(entry.clone())?,
+                &owner_keypair,
+            )?;
+            sequence.apply_op(op.clone())?;
+            user_replica.apply_op(op)?;
+        }
+
+        // A user with the Delete permission can remove entries
+        let op = sign_sequence_op(
+            user_replica.remove(SequenceIndex::FromStart(1))?,
+            &user_keypair,
+        )?;
+        sequence.apply_op(op.clone())?;
+        user_replica.apply_op(op)?;
+        assert_eq!(
+            sequence.remove(SequenceIndex::FromStart(4)),
+            Err(Error::NoSuchEntry)
+        );
+
+        for op in sequence.truncate(2)? {
+            let op = sign_sequence_op(op, &owner_keypair)?;
+            sequence.apply_op(op.clone())?;
+            user_replica.apply_op(op)?;
+        }
+
+        let expected = vec![entries[0].clone(), entries[2].clone()];
+        for replica in &[sequence, user_replica] {
+            assert_eq!(
+                replica.in_range(SequenceIndex::FromStart(0), SequenceIndex::FromEnd(0), None)?,
+                Some(expected.clone())
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_public_remove_fails() -> anyhow::Result<()> {
+        let mut replicas = create_public_seq_replicas(1);
+        let (_, sequence) = &mut replicas[0];
+        assert_eq!(
+            sequence.remove(SequenceIndex::FromStart(0)),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(sequence.truncate(0), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_public_trim_front_fails() -> anyhow::Result<()> {
+        let mut replicas = create_public_seq_replicas(1);
+        let (_, sequence) = &mut replicas[0];
+        assert_eq!(sequence.trim_front(1), Err(Error::InvalidOperation));
+
+        Ok(())
+    }
+
     #[test]
     fn sequence_query_public_policy() -> anyhow::Result<()> {
         // one replica will allow append ops to anyone
@@ -464,6 +2281,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: owner1,
                 permissions: perms1.clone(),
+                validator: None,
             }),
         );
 
@@ -480,6 +2298,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: authority2,
                 permissions: perms2.clone(),
+                validator: None,
             }),
         );
 
@@ -526,6 +2345,7 @@ mod tests {
             Some(SequencePrivatePolicy {
                 owner: authority1,
                 permissions: perms1.clone(),
+                validator: None,
             }),
         );
 
@@ -534,6 +2354,7 @@ mod tests {
             Some(SequencePrivatePolicy {
                 owner: authority2,
                 permissions: perms2.clone(),
+                validator: None,
             }),
         );
 
@@ -578,6 +2399,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: owner1,
                 permissions: perms1,
+                validator: None,
             }),
         );
 
@@ -594,6 +2416,7 @@ mod tests {
             Some(SequencePublicPolicy {
                 owner: authority2,
                 permissions: perms2,
+                validator: None,
             }),
         );
 
@@ -642,6 +2465,7 @@ mod tests {
             Some(SequencePrivatePolicy {
                 owner: authority1,
                 permissions: perms1,
+                validator: None,
             }),
         );
 
@@ -650,6 +2474,7 @@ mod tests {
             Some(SequencePrivatePolicy {
                 owner: authority2,
                 permissions: perms2,
+                validator: None,
             }),
         );
 
@@ -736,7 +2561,7 @@ mod tests {
         mut op: SequenceOp<SequenceEntry>,
         keypair: &Keypair,
     ) -> Result<SequenceOp<SequenceEntry>> {
-        let bytes = utils::serialise(&op.crdt_op)?;
+        let bytes = op.bytes_to_sign()?;
         let signature = keypair.sign(&bytes);
         op.signature = Some(signature);
         Ok(op)
@@ -879,6 +2704,7 @@ mod tests {
                 let policy = SequencePublicPolicy {
                     owner,
                     permissions: BTreeMap::default(),
+                    validator: None,
                 };
                 let replica = Sequence::new_public(owner, actor, xorname, tag, Some(policy));
 
@@ -911,6 +2737,7 @@ mod tests {
             let policy = SequencePublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                validator: None,
             };
 
             let mut replicas = gen_pub_seq_replicas(
@@ -941,6 +2768,7 @@ mod tests {
             let policy = SequencePublicPolicy {
                 owner: owner_keypair.public_key(),
                 permissions: BTreeMap::default(),
+                validator: None,
             };
 
             // Instantiate the same Sequence on two replicas
@@ -0,0 +1,105 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{Data, DataOp, Entry};
+use crate::{Error, Result};
+
+/// Number of ops from a single source an `OpBuffer` holds by default.
+pub const MAX_BUFFERED_OPS_PER_SOURCE: usize = 1024;
+
+/// Holds the ops a Sequence replica rejected with `Error::OpNotCausallyReady`,
+/// re-attempting them as the ops they depend on get applied.
+///
+/// The number of ops held from each source is capped, so that a source sending
+/// ops which never become ready can't grow the buffer without bounds, nor
+/// crowd out the ops of others.
+#[derive(Clone, Debug)]
+pub struct OpBuffer {
+    ops: Vec<DataOp<Entry>>,
+    max_per_source: usize,
+}
+
+impl Default for OpBuffer {
+    fn default() -> Self {
+        Self::with_max_per_source(MAX_BUFFERED_OPS_PER_SOURCE)
+    }
+}
+
+impl OpBuffer {
+    /// Constructs an empty buffer, holding up to `MAX_BUFFERED_OPS_PER_SOURCE`
+    /// ops from each source.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs an empty buffer, holding up to `max_per_source` ops from each source.
+    pub fn with_max_per_source(max_per_source: usize) -> Self {
+        Self {
+            ops: Vec::new(),
+            max_per_source,
+        }
+    }
+
+    /// Applies `op` to `sequence`, buffering it if it's not causally ready yet.
+    /// Once applied, the buffered ops which became ready are applied too.
+    /// An op which isn't ready is rejected with `Error::OpBufferFull` if as many
+    /// ops from its source as the buffer holds are already waiting.
+    pub fn apply(&mut self, sequence: &mut Data, op: DataOp<Entry>) -> Result<()> {
+        match sequence.apply_op(op.clone()) {
+            Ok(()) => {
+                let _ = self.drain_ready(sequence);
+                Ok(())
+            }
+            Err(Error::OpNotCausallyReady) => {
+                let buffered = self
+                    .ops
+                    .iter()
+                    .filter(|buffered| buffered.source == op.source)
+                    .count();
+                if buffered >= self.max_per_source {
+                    return Err(Error::OpBufferFull(op.source));
+                }
+                self.ops.push(op);
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns the number of ops waiting for their dependencies.
+    pub fn buffered_count(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Re-attempts the buffered ops until none of them can be applied anymore,
+    /// returning those applied. Ops failing with an error other than
+    /// `Error::OpNotCausallyReady`, e.g. as they've been applied meanwhile,
+    /// are dropped as they never will be.
+    pub fn drain_ready(&mut self, sequence: &mut Data) -> Vec<DataOp<Entry>> {
+        let mut applied = vec![];
+        loop {
+            let mut pending = vec![];
+            let mut progress = false;
+            for op in self.ops.drain(..) {
+                match sequence.apply_op(op.clone()) {
+                    Ok(()) => {
+                        applied.push(op);
+                        progress = true;
+                    }
+                    Err(Error::OpNotCausallyReady) => pending.push(op),
+                    Err(_) => progress = true,
+                }
+            }
+            self.ops = pending;
+            if !progress || self.ops.is_empty() {
+                return applied;
+            }
+        }
+    }
+}
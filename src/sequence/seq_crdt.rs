@@ -8,16 +8,18 @@
 // Software.
 
 use super::metadata::Entries;
-use super::metadata::{Address, Entry, Index, Perm};
+use super::metadata::{Address, AppendStats, Entry, EntryHash, EntryMeta, Index, Perm};
 use crate::Signature;
 use crate::{utils, Error, PublicKey, Result};
 pub use crdts::list::Op;
-use crdts::{list::List, CmRDT};
+use crdts::{dot::OrdDot, identifier::Identifier, list::List, CmRDT, Dot, VClock};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display},
     hash::Hash,
 };
+use tiny_keccak::{Hasher, Sha3};
 
 /// CRDT Data operation applicable to other Sequence replica.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -30,10 +32,171 @@ pub struct CrdtOperation<A: Ord, T> {
     pub source: PublicKey,
     /// The signature of source on the crdt_top, required to apply the op
     pub signature: Option<Signature>,
+    /// Hash of the entry the source saw as the last one when creating the op, if the
+    /// append was conditional. `Some(None)` means the Sequence was empty. It's signed
+    /// over, but only checked where the op is created: it's advisory to other replicas.
+    pub expected_last: Option<Option<EntryHash>>,
+    /// Time the source claims to have appended the entry at, if it provided one.
+    pub timestamp: Option<u64>,
 }
 
-/// Sequence data type as a CRDT with Access Control
+impl<A: Ord + Serialize, T: Serialize> CrdtOperation<A, T> {
+    /// Returns the bytes the source is expected to sign.
+    /// An unconditional op is signed on its `crdt_op` alone, a conditional one on
+    /// the `crdt_op` together with its `expected_last` precondition, and a timestamped
+    /// one on both of them together with its `timestamp`.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        let bytes = match (&self.expected_last, self.timestamp) {
+            (None, None) => utils::serialise(&self.crdt_op),
+            (Some(expected_last), None) => utils::serialise(&(&self.crdt_op, expected_last)),
+            (expected_last, Some(timestamp)) => {
+                utils::serialise(&(&self.crdt_op, expected_last, timestamp))
+            }
+        };
+        bytes.map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT operation to verify signature: {}",
+                err
+            ))
+        })
+    }
+}
+
+/// CRDT operation appending several entries to a Sequence at once,
+/// applied atomically by other replicas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrdtBatchOperation<A: Ord, T> {
+    /// Address of a Sequence object on the network.
+    pub address: Address,
+    /// The data operations to apply, in order.
+    pub crdt_ops: Vec<Op<T, A>>,
+    /// The PublicKey of the entity that generated the operation
+    pub source: PublicKey,
+    /// The signature of source on the crdt_ops, required to apply the op
+    pub signature: Option<Signature>,
+}
+
+impl<A: Ord + Serialize, T: Serialize> CrdtBatchOperation<A, T> {
+    /// Returns the bytes the source is expected to sign, i.e. all the `crdt_ops`.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>> {
+        utils::serialise(&self.crdt_ops).map_err(|err| {
+            Error::Serialisation(format!(
+                "Could not serialise CRDT operation to verify signature: {}",
+                err
+            ))
+        })
+    }
+}
+
+/// Signature of the op an entry was appended with, kept along with the entry
+/// for other replicas to check it when merging this replica's state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[allow(clippy::large_enum_variant)]
+enum AppendProof<A: Ord> {
+    /// Appended with `apply_op`, by an op signed along with its precondition, if any.
+    Single {
+        signature: Signature,
+        expected_last: Option<Option<EntryHash>>,
+    },
+    /// Appended with `apply_batch_op`, by the batch keyed by the dot of its first append.
+    Batch(OrdDot<A>),
+}
+
+/// Signed batch of appends, kept while any of its entries is held.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct SignedBatch<A: Ord> {
+    source: PublicKey,
+    signature: Signature,
+    entries: Vec<(Identifier<OrdDot<A>>, Entry)>,
+}
+
+impl<A: Ord + Clone + Serialize> SignedBatch<A> {
+    // Checks the signature of the batch keyed by `key`, made for the Sequence at `address`.
+    fn verify(&self, address: Address, key: &OrdDot<A>) -> Result<()> {
+        if self.entries.first().map(|(id, _)| id.value()) != Some(key) {
+            return Err(Error::CrdtUnexpectedState);
+        }
+        let op = CrdtBatchOperation {
+            address,
+            crdt_ops: self
+                .entries
+                .iter()
+                .map(|(id, val)| Op::Insert {
+                    id: id.clone(),
+                    val: val.clone(),
+                })
+                .collect(),
+            source: self.source,
+            signature: Some(self.signature.clone()),
+        };
+        self.source.verify(&self.signature, &op.bytes_to_sign()?)
+    }
+}
+
+/// Signed op an entry was removed with, kept for other replicas to remove it too
+/// when merging this replica's state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct SignedRemoval<A: Ord> {
+    dot: Dot<A>,
+    source: PublicKey,
+    signature: Signature,
+    expected_last: Option<Option<EntryHash>>,
+    timestamp: Option<u64>,
+}
+
+/// Change taken from the state of another replica when merging it.
+enum Change<A: Ord> {
+    /// Appending an entry, with the signature of the op it was appended with.
+    Append(Op<Entry, A>, EntryMeta, AppendProof<A>),
+    /// Skipping the append of an entry removed before it was taken.
+    Skip(Identifier<OrdDot<A>>),
+    /// Removing an entry, with the signed op it was removed with.
+    Remove(Identifier<OrdDot<A>>, SignedRemoval<A>),
+}
+
+/// Signatures backing the state of a Sequence replica: those of the ops its entries
+/// were appended with, and of those which removed entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub(super) struct Proofs<A: Ord> {
+    appends: BTreeMap<Identifier<OrdDot<A>>, AppendProof<A>>,
+    batches: BTreeMap<OrdDot<A>, SignedBatch<A>>,
+    removals: BTreeMap<Identifier<OrdDot<A>>, SignedRemoval<A>>,
+}
+
+impl<A: Ord> Default for Proofs<A> {
+    fn default() -> Self {
+        Self {
+            appends: BTreeMap::new(),
+            batches: BTreeMap::new(),
+            removals: BTreeMap::new(),
+        }
+    }
+}
+
+/// State of a Sequence replica other replicas can bootstrap from: its entries, along
+/// with the CRDT metadata needed to apply later ops to them, and its policy.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub(super) struct CrdtState<A: Ord, P> {
+    address: Address,
+    data: List<Entry, A>,
+    meta: BTreeMap<Identifier<OrdDot<A>>, EntryMeta>,
+    clock: VClock<A>,
+    proofs: Proofs<A>,
+    policy: P,
+}
+
+/// Layout of a `SequenceCrdt` encoded before the author of its entries was kept, with
+/// a policy of layout `P`.
+#[derive(Deserialize)]
+pub(super) struct SequenceCrdtV0<A: Ord, P> {
+    actor: A,
+    address: Address,
+    data: List<Entry, A>,
+    policy: P,
+}
+
+/// Sequence data type as a CRDT with Access Control
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SequenceCrdt<A: Ord, P> {
     /// Actor of this piece of data
     pub(crate) actor: A,
@@ -43,6 +206,41 @@ pub struct SequenceCrdt<A: Ord, P> {
     data: List<Entry, A>,
     /// The Policy matrix containing ownership and users permissions.
     policy: P,
+    /// Appends made by each key, maintained as ops are applied.
+    stats: BTreeMap<PublicKey, AppendStats>,
+    /// Version vector of the CRDT ops applied, to detect replayed ops.
+    clock: VClock<A>,
+    /// Author and timestamp of each item, in the same order as the items.
+    meta: BTreeMap<Identifier<OrdDot<A>>, EntryMeta>,
+    /// Signatures of the ops which brought the entries, for other replicas to check
+    /// them when merging this one.
+    proofs: Proofs<A>,
+}
+
+// Replicas are compared on their state, leaving out the signatures backing it
+// and the version vector of the ops which brought it.
+impl<A: Ord + Eq, P: Eq> PartialEq for SequenceCrdt<A, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.actor == other.actor
+            && self.address == other.address
+            && self.data == other.data
+            && self.policy == other.policy
+            && self.stats == other.stats
+            && self.meta == other.meta
+    }
+}
+
+impl<A: Ord + Eq, P: Eq> Eq for SequenceCrdt<A, P> {}
+
+impl<A: Ord + Hash, P: Hash> Hash for SequenceCrdt<A, P> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.actor.hash(state);
+        self.address.hash(state);
+        self.data.hash(state);
+        self.policy.hash(state);
+        self.stats.hash(state);
+        self.meta.hash(state);
+    }
 }
 
 impl<A, P> Display for SequenceCrdt<A, P>
@@ -74,6 +272,74 @@ where
             address,
             data: List::new(),
             policy,
+            stats: BTreeMap::new(),
+            clock: VClock::new(),
+            meta: BTreeMap::new(),
+            proofs: Proofs::default(),
+        }
+    }
+
+    /// Constructs a 'SequenceCrdt' with the given `actor` out of the state of
+    /// another replica. Its append stats only count the entries it holds.
+    pub(super) fn from_state(actor: A, state: CrdtState<A, P>) -> Self {
+        let mut stats = BTreeMap::<PublicKey, AppendStats>::new();
+        for (meta, entry) in state.meta.values().zip(state.data.iter()) {
+            let stats = stats.entry(meta.author).or_default();
+            stats.appends += 1;
+            stats.bytes += entry.len() as u64;
+        }
+
+        Self {
+            actor,
+            address: state.address,
+            data: state.data,
+            policy: state.policy,
+            stats,
+            clock: state.clock,
+            meta: state.meta,
+            proofs: state.proofs,
+        }
+    }
+
+    /// Constructs a 'SequenceCrdt' from one decoded with its earliest layout, recording
+    /// its entries as appended by the owner of its policy, without a timestamp. With no
+    /// signatures backing them, other replicas can't take them when merging this one.
+    pub(super) fn from_v0<Q: Into<P>>(legacy: SequenceCrdtV0<A, Q>) -> Self
+    where
+        P: Perm,
+    {
+        let mut crdt = Self::new(legacy.actor, legacy.address, legacy.policy.into());
+        let author = *crdt.policy.owner();
+        for (id, entry) in legacy.data.iter_entries() {
+            crdt.clock.apply(id.value().clone().into());
+            let stats = crdt.stats.entry(author).or_default();
+            stats.appends += 1;
+            stats.bytes += entry.len() as u64;
+            let _ = crdt.meta.insert(
+                id.clone(),
+                EntryMeta {
+                    author,
+                    timestamp: None,
+                },
+            );
+        }
+        crdt.data = legacy.data;
+
+        crdt
+    }
+
+    /// Returns the state other replicas can bootstrap from with `from_state`.
+    pub(super) fn state(&self) -> CrdtState<A, P>
+    where
+        P: Clone,
+    {
+        CrdtState {
+            address: self.address,
+            data: self.data.clone(),
+            meta: self.meta.clone(),
+            clock: self.clock.clone(),
+            proofs: self.proofs.clone(),
+            policy: self.policy.clone(),
         }
     }
 
@@ -104,25 +370,368 @@ where
             crdt_op,
             source,
             signature: None,
+            expected_last: None,
+            timestamp: None,
         })
     }
 
+    /// Create crdt op to append a new item to the SequenceCrdt only if the
+    /// hash of the current last entry is `expected_last` (`None` for an empty Sequence).
+    ///
+    /// The precondition is only checked here. Replicas applying the op don't check it
+    /// again, as whether it holds would then depend on the order ops reach them in, so
+    /// concurrent conditional appends made on different replicas all end up applied.
+    pub fn create_append_if_last_op(
+        &self,
+        entry: Entry,
+        expected_last: Option<EntryHash>,
+        source: PublicKey,
+    ) -> Result<CrdtOperation<A, Entry>> {
+        self.check_last_entry(expected_last)?;
+
+        let mut op = self.create_append_op(entry, source)?;
+        op.expected_last = Some(expected_last);
+        Ok(op)
+    }
+
+    /// Create a single crdt op appending all the `entries` to the SequenceCrdt, in order.
+    pub fn create_append_batch_op(
+        &self,
+        entries: Vec<Entry>,
+        source: PublicKey,
+    ) -> Result<CrdtBatchOperation<A, Entry>> {
+        if entries.is_empty() {
+            return Err(Error::InvalidOperation);
+        }
+
+        // Each append needs to follow the previous one, so they're generated on
+        // a scratch copy of the LSeq, leaving this replica untouched until applied.
+        let mut data = self.data.clone();
+        let crdt_ops = entries
+            .into_iter()
+            .map(|entry| {
+                let crdt_op = data.append(entry, self.actor.clone());
+                data.apply(crdt_op.clone());
+                crdt_op
+            })
+            .collect();
+
+        Ok(CrdtBatchOperation {
+            address: *self.address(),
+            crdt_ops,
+            source,
+            signature: None,
+        })
+    }
+
+    /// Create crdt op removing the item at `index` from the SequenceCrdt.
+    pub fn create_remove_op(
+        &self,
+        index: Index,
+        source: PublicKey,
+    ) -> Result<CrdtOperation<A, Entry>> {
+        let address = *self.address();
+        let crdt_op = to_absolute_index(index, self.len() as usize)
+            .and_then(|index| self.data.delete_index(index, self.actor.clone()))
+            .ok_or(Error::NoSuchEntry)?;
+
+        Ok(CrdtOperation {
+            address,
+            crdt_op,
+            source,
+            signature: None,
+            expected_last: None,
+            timestamp: None,
+        })
+    }
+
+    /// Create crdt ops removing the items from `len` onwards,
+    /// leaving the SequenceCrdt with at most `len` items.
+    pub fn create_truncate_ops(&self, len: u64, source: PublicKey) -> Vec<CrdtOperation<A, Entry>> {
+        let address = *self.address();
+
+        // Removing from the back, each removal following the previous one on a
+        // scratch copy of the LSeq so that they're all applied by other replicas.
+        let mut data = self.data.clone();
+        (len..self.len())
+            .rev()
+            .filter_map(|index| {
+                let crdt_op = data.delete_index(index as usize, self.actor.clone())?;
+                data.apply(crdt_op.clone());
+                Some(crdt_op)
+            })
+            .map(|crdt_op| CrdtOperation {
+                address,
+                crdt_op,
+                source,
+                signature: None,
+                expected_last: None,
+                timestamp: None,
+            })
+            .collect()
+    }
+
+    /// Create crdt ops removing the first `count` items of the SequenceCrdt,
+    /// or all of them if it holds fewer items.
+    pub fn create_trim_front_ops(
+        &self,
+        count: u64,
+        source: PublicKey,
+    ) -> Vec<CrdtOperation<A, Entry>> {
+        let address = *self.address();
+        let count = std::cmp::min(count, self.len()) as usize;
+
+        // Each removal follows the previous one on a scratch copy of the LSeq,
+        // as done for the truncate ops.
+        let mut data = self.data.clone();
+        (0..count)
+            .filter_map(|_| {
+                let crdt_op = data.delete_index(0, self.actor.clone())?;
+                data.apply(crdt_op.clone());
+                Some(crdt_op)
+            })
+            .map(|crdt_op| CrdtOperation {
+                address,
+                crdt_op,
+                source,
+                signature: None,
+                expected_last: None,
+                timestamp: None,
+            })
+            .collect()
+    }
+
     /// Apply a remote data CRDT operation to this replica of the Sequence.
     pub fn apply_op(&mut self, op: CrdtOperation<A, Entry>) -> Result<()> {
         // Let's first check the op is validly signed.
         // Note: Perms for the op are checked at the upper Sequence layer.
 
-        let sig = op.signature.ok_or(Error::CrdtMissingOpSignature)?;
-        let bytes_to_verify = utils::serialise(&op.crdt_op).map_err(|err| {
-            Error::Serialisation(format!(
-                "Could not serialise CRDT operation to verify signature: {}",
-                err
-            ))
-        })?;
-        op.source.verify(&sig, &bytes_to_verify)?;
+        let sig = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        let bytes_to_verify = op.bytes_to_sign()?;
+        op.source.verify(sig, &bytes_to_verify)?;
+        let signature = sig.clone();
+
+        // An op delivered more than once is only applied the first time, otherwise
+        // e.g. replaying an append after the entry was removed would bring it back.
+        if self.is_applied(&op.crdt_op.dot()) {
+            return Err(Error::OpAlreadyApplied);
+        }
 
-        // Apply the CRDT operation to the LSeq data
-        self.data.apply(op.crdt_op);
+        // Removing an item before its append arrived would have no effect, and the item
+        // would then show up once appended, so the removal needs to wait for it.
+        if let Op::Delete { id, .. } = &op.crdt_op {
+            if !self.meta.contains_key(id) && !self.is_applied(&id.value().clone().into()) {
+                return Err(Error::OpNotCausallyReady);
+            }
+        }
+
+        self.apply_crdt_op(op.crdt_op.clone(), op.source, op.timestamp);
+        match op.crdt_op {
+            Op::Insert { id, .. } => {
+                let proof = AppendProof::Single {
+                    signature,
+                    expected_last: op.expected_last,
+                };
+                let _ = self.proofs.appends.insert(id, proof);
+            }
+            Op::Delete { id, dot } => self.record_removal(
+                id,
+                SignedRemoval {
+                    dot,
+                    source: op.source,
+                    signature,
+                    expected_last: op.expected_last,
+                    timestamp: op.timestamp,
+                },
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Apply a remote batch CRDT operation to this replica of the Sequence.
+    /// Either all of its appends are applied, or none of them.
+    pub fn apply_batch_op(&mut self, op: CrdtBatchOperation<A, Entry>) -> Result<()> {
+        let sig = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        let bytes_to_verify = op.bytes_to_sign()?;
+        op.source.verify(sig, &bytes_to_verify)?;
+
+        let mut entries = Vec::with_capacity(op.crdt_ops.len());
+        for crdt_op in &op.crdt_ops {
+            match crdt_op {
+                Op::Insert { id, val } => entries.push((id.clone(), val.clone())),
+                Op::Delete { .. } => return Err(Error::InvalidOperation),
+            }
+            if self.is_applied(&crdt_op.dot()) {
+                return Err(Error::OpAlreadyApplied);
+            }
+        }
+
+        let key = match entries.first() {
+            Some((id, _)) => id.value().clone(),
+            None => return Err(Error::InvalidOperation),
+        };
+        for crdt_op in op.crdt_ops {
+            let id = crdt_op.id().clone();
+            self.apply_crdt_op(crdt_op, op.source, None);
+            let _ = self
+                .proofs
+                .appends
+                .insert(id, AppendProof::Batch(key.clone()));
+        }
+        let batch = SignedBatch {
+            source: op.source,
+            signature: sig.clone(),
+            entries,
+        };
+        let _ = self.proofs.batches.insert(key, batch);
+
+        Ok(())
+    }
+
+    /// Merges the state of `other`, a replica of the same Sequence, into this one.
+    /// The entries and removals of `other` this replica is missing are only taken once
+    /// checked against the signatures `other` holds for them, and by `check`, which is
+    /// given the policy along with each of their ops and its author. If any of them is
+    /// rejected, the error is returned and this replica is left as it was.
+    pub(super) fn merge(
+        &mut self,
+        other: &Self,
+        check: impl Fn(&P, &Op<Entry, A>, &EntryMeta) -> Result<()>,
+    ) -> Result<()>
+    where
+        P: Clone,
+    {
+        let mut changes = Vec::new();
+        let mut verified_batches = BTreeSet::new();
+
+        for (id, val) in other.data.iter_entries() {
+            let dot: Dot<A> = id.value().clone().into();
+            if self.is_applied(&dot) {
+                continue;
+            }
+            let meta = *other.meta.get(id).ok_or(Error::CrdtUnexpectedState)?;
+            let proof = other
+                .proofs
+                .appends
+                .get(id)
+                .ok_or(Error::CrdtUnexpectedState)?;
+            let crdt_op = Op::Insert {
+                id: id.clone(),
+                val: val.clone(),
+            };
+            match proof {
+                AppendProof::Single {
+                    signature,
+                    expected_last,
+                } => {
+                    let op = CrdtOperation {
+                        address: self.address,
+                        crdt_op: crdt_op.clone(),
+                        source: meta.author,
+                        signature: Some(signature.clone()),
+                        expected_last: *expected_last,
+                        timestamp: meta.timestamp,
+                    };
+                    meta.author.verify(signature, &op.bytes_to_sign()?)?;
+                }
+                AppendProof::Batch(key) => {
+                    let batch = other
+                        .proofs
+                        .batches
+                        .get(key)
+                        .ok_or(Error::CrdtUnexpectedState)?;
+                    let in_batch = batch
+                        .entries
+                        .iter()
+                        .any(|(batch_id, batch_val)| batch_id == id && batch_val == val);
+                    if batch.source != meta.author || meta.timestamp.is_some() || !in_batch {
+                        return Err(Error::CrdtUnexpectedState);
+                    }
+                    if verified_batches.insert(key) {
+                        batch.verify(self.address, key)?;
+                    }
+                }
+            }
+            check(&self.policy, &crdt_op, &meta)?;
+            changes.push((dot, Change::Append(crdt_op, meta, proof.clone())));
+        }
+
+        for (id, removal) in &other.proofs.removals {
+            if self.is_applied(&removal.dot) {
+                continue;
+            }
+            let op = CrdtOperation {
+                address: self.address,
+                crdt_op: Op::Delete {
+                    id: id.clone(),
+                    dot: removal.dot.clone(),
+                },
+                source: removal.source,
+                signature: Some(removal.signature.clone()),
+                expected_last: removal.expected_last,
+                timestamp: removal.timestamp,
+            };
+            removal
+                .source
+                .verify(&removal.signature, &op.bytes_to_sign()?)?;
+            let meta = EntryMeta {
+                author: removal.source,
+                timestamp: removal.timestamp,
+            };
+            check(&self.policy, &op.crdt_op, &meta)?;
+
+            // The append of an entry removed before this replica got it is never to be
+            // applied here either.
+            let appended: Dot<A> = id.value().clone().into();
+            if !self.is_applied(&appended) {
+                changes.push((appended, Change::Skip(id.clone())));
+            }
+            changes.push((
+                removal.dot.clone(),
+                Change::Remove(id.clone(), removal.clone()),
+            ));
+        }
+
+        // The ops of each actor are applied in the order it made them, as the LSeq expects.
+        changes.sort_by(|(dot, _), (other_dot, _)| {
+            (dot.counter, &dot.actor).cmp(&(other_dot.counter, &other_dot.actor))
+        });
+
+        let mut merged = self.clone();
+        for (dot, change) in changes {
+            match change {
+                Change::Append(crdt_op, meta, proof) => {
+                    if let AppendProof::Batch(key) = &proof {
+                        if let Some(batch) = other.proofs.batches.get(key) {
+                            let _ = merged
+                                .proofs
+                                .batches
+                                .entry(key.clone())
+                                .or_insert_with(|| batch.clone());
+                        }
+                    }
+                    let id = crdt_op.id().clone();
+                    merged.apply_crdt_op(crdt_op, meta.author, meta.timestamp);
+                    let _ = merged.proofs.appends.insert(id, proof);
+                }
+                Change::Skip(id) => {
+                    // Deleting the entry with the dot of its append keeps the LSeq's
+                    // clock in step, without the entry ever being inserted.
+                    merged.clock.apply(dot.clone());
+                    merged.data.apply(Op::Delete { id, dot });
+                }
+                Change::Remove(id, removal) => {
+                    let crdt_op = Op::Delete {
+                        id: id.clone(),
+                        dot: removal.dot.clone(),
+                    };
+                    merged.apply_crdt_op(crdt_op, removal.source, removal.timestamp);
+                    merged.record_removal(id, removal);
+                }
+            }
+        }
+        *self = merged;
 
         Ok(())
     }
@@ -133,11 +742,27 @@ where
         self.data.position(i)
     }
 
+    /// Gets the entry at `index` together with its author and timestamp, if it exists.
+    pub fn get_with_meta(&self, index: Index) -> Option<(&Entry, &EntryMeta)> {
+        let i = to_absolute_index(index, self.len() as usize)?;
+        self.data.position(i).zip(self.meta.values().nth(i))
+    }
+
     /// Gets the last entry.
     pub fn last_entry(&self) -> Option<&Entry> {
         self.data.last()
     }
 
+    /// Gets the hash of the last entry.
+    pub fn last_entry_hash(&self) -> Option<EntryHash> {
+        self.last_entry().map(|entry| hash_entry(entry))
+    }
+
+    /// Gets the appends made by each key.
+    pub fn stats(&self) -> &BTreeMap<PublicKey, AppendStats> {
+        &self.stats
+    }
+
     /// Gets the Policy of the object.
     pub fn policy(&self) -> &P {
         &self.policy
@@ -164,10 +789,95 @@ where
 
         Some(entries)
     }
+
+    /// Gets a list of items, together with their author and timestamp,
+    /// which are within the given indices.
+    /// Note the range of items is [start, end), i.e. the end index is not inclusive.
+    pub fn in_range_with_meta(&self, start: Index, end: Index) -> Option<Vec<(Entry, EntryMeta)>> {
+        let entries = self.in_range(start, end)?;
+        let start_index = to_absolute_index(start, self.len() as usize)?;
+
+        Some(
+            entries
+                .into_iter()
+                .zip(self.meta.values().skip(start_index).copied())
+                .collect(),
+        )
+    }
+
+    // Applies a verified CRDT operation to the LSeq data, keeping track of it.
+    fn apply_crdt_op(&mut self, crdt_op: Op<Entry, A>, source: PublicKey, timestamp: Option<u64>) {
+        let (id, appended) = match &crdt_op {
+            Op::Insert { id, val } => (id.clone(), val.len() as u64),
+            Op::Delete { id, .. } => (id.clone(), 0),
+        };
+        let len_before = self.data.len();
+
+        self.clock.apply(crdt_op.dot());
+        self.data.apply(crdt_op);
+
+        if self.data.len() > len_before {
+            let stats = self.stats.entry(source).or_default();
+            stats.appends += 1;
+            stats.bytes += appended;
+            let _ = self.meta.insert(
+                id,
+                EntryMeta {
+                    author: source,
+                    timestamp,
+                },
+            );
+        } else if self.data.len() < len_before {
+            let _ = self.meta.remove(&id);
+        }
+    }
+
+    // Returns true if the op with the given dot was applied here. Like for the
+    // LSeq, the ops of each actor are expected in the order it made them.
+    fn is_applied(&self, dot: &Dot<A>) -> bool {
+        dot.counter <= self.clock.get(&dot.actor)
+    }
+
+    // Keeps the signed op which removed the entry `id`, dropping the signature of its
+    // append, and that of its batch once none of the entries it appended are held.
+    fn record_removal(&mut self, id: Identifier<OrdDot<A>>, removal: SignedRemoval<A>) {
+        if let Some(AppendProof::Batch(key)) = self.proofs.appends.remove(&id) {
+            let held = self
+                .proofs
+                .appends
+                .values()
+                .any(|proof| proof == &AppendProof::Batch(key.clone()));
+            if !held {
+                let _ = self.proofs.batches.remove(&key);
+            }
+        }
+        let _ = self.proofs.removals.entry(id).or_insert(removal);
+    }
+
+    // Checks the hash of the current last entry matches the expected one.
+    fn check_last_entry(&self, expected_last: Option<EntryHash>) -> Result<()> {
+        let actual = self.last_entry_hash();
+        if actual == expected_last {
+            Ok(())
+        } else {
+            Err(Error::LastEntryMismatch {
+                expected: expected_last,
+                actual,
+            })
+        }
+    }
 }
 
 // Private helpers
 
+pub(super) fn hash_entry(entry: &[u8]) -> EntryHash {
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(entry);
+    hasher.finalize(&mut output);
+    output
+}
+
 fn to_absolute_index(index: Index, count: usize) -> Option<usize> {
     match index {
         Index::FromStart(index) if (index as usize) <= count => Some(index as usize),
@@ -0,0 +1,187 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Canonical data generated from a fixed seed, along with the addresses and serialised
+//! bytes the Rust types give it, for implementations of clients in other languages
+//! to check they're compatible with. The vectors only change along with the wire format.
+
+use crate::{
+    utils, Chunk, ChunkAddress, ChunkKind, Error, PrivateChunk, PublicChunk, PublicKey, Result,
+    MAX_INLINE_SIZE_IN_BYTES,
+};
+use serde::Serialize;
+use tiny_keccak::{Hasher, Sha3};
+
+/// Seed all the test vectors are generated from.
+pub const SEED: [u8; 32] = *b"sn_data_types::test_vectors:seed";
+
+// Sizes of the values of the chunks generated, for each kind of chunk.
+const CHUNK_VALUE_SIZES: [usize; 5] = [0, 1, 32, MAX_INLINE_SIZE_IN_BYTES, 4096];
+
+/// A chunk generated from the seed, along with what the Rust types make of it.
+#[derive(Clone, Eq, PartialEq, Serialize, Debug)]
+pub struct ChunkVector {
+    /// Kind of the chunk.
+    pub kind: ChunkKind,
+    /// Value of the chunk.
+    pub value: Vec<u8>,
+    /// Owner of the chunk, for private ones.
+    pub owner: Option<PublicKey>,
+    /// Address of the chunk.
+    pub address: ChunkAddress,
+    /// The chunk serialised with bincode, as sent over the wire.
+    pub serialised: Vec<u8>,
+}
+
+/// Returns the Ed25519 key owning the private chunks generated, derived from the seed.
+pub fn owner() -> Result<PublicKey> {
+    let secret = ed25519_dalek::SecretKey::from_bytes(&expand(b"owner", 32)).map_err(|e| {
+        Error::FailedToParse(format!(
+            "Couldn't parse ed25519 secret key from bytes: {}",
+            e
+        ))
+    })?;
+
+    Ok(PublicKey::from(ed25519_dalek::PublicKey::from(&secret)))
+}
+
+/// Returns public and private chunks of various sizes generated from the seed.
+pub fn chunks() -> Result<Vec<ChunkVector>> {
+    let owner = owner()?;
+    let mut vectors = Vec::with_capacity(2 * CHUNK_VALUE_SIZES.len());
+    for size in CHUNK_VALUE_SIZES.iter() {
+        let value = expand(format!("chunk:{}", size).as_bytes(), *size);
+        let public = Chunk::Public(PublicChunk::new(value.clone()));
+        let private = Chunk::Private(PrivateChunk::new(value, owner));
+        for chunk in [public, private] {
+            vectors.push(ChunkVector {
+                kind: chunk.kind(),
+                value: chunk.value().clone(),
+                owner: chunk.owner().copied(),
+                address: *chunk.address(),
+                serialised: utils::serialise(&chunk)?,
+            });
+        }
+    }
+
+    Ok(vectors)
+}
+
+// Expands the seed, separated by `label`, into `len` bytes, using SHA3-256 in counter mode.
+fn expand(label: &[u8], len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut counter: u64 = 0;
+    while bytes.len() < len {
+        let mut hasher = Sha3::v256();
+        let mut output = [0; 32];
+        hasher.update(&SEED);
+        hasher.update(label);
+        hasher.update(&counter.to_le_bytes());
+        hasher.finalize(&mut output);
+        bytes.extend_from_slice(&output);
+        counter += 1;
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::chunks;
+    use crate::{predict_chunk_address, utils, Chunk, Result};
+    use tiny_keccak::{Hasher, Sha3};
+
+    // Hex of the address names and SHA3-256 digests of the serialised bytes of the chunk
+    // vectors, in the order they're generated. These only change along with the wire format.
+    const GOLDEN: [(&str, &str); 10] = [
+        (
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            "368cfb2a50e59cdfdb94b2996a34e607a001634d1d7a288c6754ce51527cc5aa",
+        ),
+        (
+            "a337fa947eeb4b3db3fba155432e384a1c762af71adb7c0293bc0efaa6a3fc8a",
+            "3e96355dd6bc82511adf5c5d0a60c901e72f8f4239a3d15beb8ee865200aec78",
+        ),
+        (
+            "1103e78451508635718a70e3c4267b210213a081e8e614250734e5c54006f28b",
+            "d32462ea7abe6b7336f22db2beb07ca8e9824687766b0e45c80d0a20dc9452ee",
+        ),
+        (
+            "7dd79584624464b46ebb36e457ffc0e66549eaec521a3b8bf4763e0a6dc12182",
+            "3550819a9e6b4ff8ea6e9cef41df4c17012ca29dc3fd65a0840723c343b44475",
+        ),
+        (
+            "63439dabf6a5e14decc24cb8e3f6d929623d31518565024baf4bc44745569dbe",
+            "b0be0085c22458f07e5e1edc52be3664eb954181ef3d1378701712aa13ff1971",
+        ),
+        (
+            "392028dc937cd325f3ef092beb168574acf2dc70a07478a8b11e06b17811392f",
+            "6a5a6f4c306117626c81316f9e5e01417fe6f0326aad539addf6d86c436009dc",
+        ),
+        (
+            "8259681c3c58133e75eb6d3929f15c95256d4de99a4ca28debcf510dfe34aaed",
+            "7ed1bf0d25fa2b807466752e97c9a9838fe43f74cc11116f71258d83a00707fb",
+        ),
+        (
+            "205fa3e912a36f49a197a3aa0fab342af6e8ad67de3801bd2778bd24900be2b8",
+            "d6b05894d3a7f9cf1cb605f15eda4f881166100f91e4e4441b903944d65c7b74",
+        ),
+        (
+            "9b116ee8b1f02b088175a8bbe6b9ca23b59fccdd634fc90abd000585943b0e8f",
+            "119388fcf6477d9aae7c4df9f22cfce98652bc8b3c46822518d179aee7f1bba4",
+        ),
+        (
+            "6ac7cd2172d26dd6545472101207add7dcf0672f12a6749935a00e60070eef3e",
+            "254150a9ed9f9f17b8ebc625e02c93632d2c146e934733068054ec3465b1e18c",
+        ),
+    ];
+
+    #[test]
+    fn chunk_vectors_are_deterministic() -> Result<()> {
+        let vectors = chunks()?;
+        assert_eq!(vectors, chunks()?);
+
+        for vector in vectors {
+            let address = predict_chunk_address(vector.kind, &vector.value, vector.owner.as_ref())?;
+            assert_eq!(address, vector.address);
+            let chunk: Chunk = utils::deserialise(&vector.serialised)?;
+            assert_eq!(*chunk.address(), vector.address);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn chunk_vectors_match_golden_values() -> Result<()> {
+        let vectors = chunks()?;
+        assert_eq!(vectors.len(), GOLDEN.len());
+
+        for (vector, (address, digest)) in vectors.iter().zip(GOLDEN.iter()) {
+            assert_eq!(hex::encode(vector.address.name().0), *address);
+            let mut hasher = Sha3::v256();
+            let mut output = [0; 32];
+            hasher.update(&vector.serialised);
+            hasher.finalize(&mut output);
+            assert_eq!(hex::encode(output), *digest);
+        }
+
+        // The smallest vectors are pinned in full, for the layout to be readable here
+        assert_eq!(
+            hex::encode(&vectors[0].serialised),
+            "03000000000000000000000000"
+        );
+        assert_eq!(
+            hex::encode(&vectors[3].serialised),
+            "020000000100000000000000ec000000002000000000000000\
+             d21fd79626a9fd27d7ee0674ebac2426b2188b4f8245037c3c6f7c11be579d7600"
+        );
+
+        Ok(())
+    }
+}
@@ -8,14 +8,18 @@
 // Software.
 
 use super::{
-    keys::{PublicKey, Signature, SignatureShare},
+    keys::{Keypair, PublicKey, Signature, SignatureShare},
     token::Token,
     utils, Error, Result,
 };
-use crate::SectionElders;
+use crate::{DataAddress, SectionElders};
 use crdts::Dot;
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Formatter},
+    ops::Range,
+};
 use threshold_crypto::PublicKeySet;
 use tiny_keccak::{Hasher, Sha3};
 
@@ -26,6 +30,25 @@ pub type CreditId = [u8; 256 / 8];
 /// Msg, containing any data to the recipient.
 pub type Msg = String;
 
+/// Maximum length in bytes of the msg of a transfer.
+pub const MAX_MSG_LEN: usize = 256;
+
+/// Validates the msg of a transfer, which recipients are shown as the memo of the credit:
+/// it must be at most `MAX_MSG_LEN` bytes, and without control characters.
+pub fn validate_msg(msg: &str) -> Result<()> {
+    if msg.len() > MAX_MSG_LEN {
+        Err(Error::InvalidMsg(format!(
+            "{} bytes long, at most {} allowed",
+            msg.len(),
+            MAX_MSG_LEN
+        )))
+    } else if msg.chars().any(char::is_control) {
+        Err(Error::InvalidMsg("contains control characters".to_string()))
+    } else {
+        Ok(())
+    }
+}
+
 /// Contains info on who the replicas
 /// of this wallet are, and the wallet history at them.
 #[derive(Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -47,6 +70,25 @@ impl Debug for WalletHistory {
     }
 }
 
+/// What a transfer is for, so that fees, refunds and analytics can tell
+/// payments for storing data apart from other transfers without guessing.
+///
+/// It isn't part of the credit the Actor signs: Replicas record it along with the
+/// events of the transfer, see `ReplicaEvent::with_transfer_kind`.
+#[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Debug, Default)]
+pub enum TransferKind {
+    /// Payment for storing data.
+    Payment {
+        /// Address of the data paid for.
+        target_data: DataAddress,
+    },
+    /// Transfer between peers.
+    #[default]
+    P2P,
+    /// Reward paid out to a node.
+    Reward,
+}
+
 /// A cmd to transfer of tokens between two keys.
 #[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
 pub struct Transfer {
@@ -70,7 +112,10 @@ impl Transfer {
     }
 
     /// The destination.
+    ///
+    /// Returns `Err(Error::InvalidMsg)` if the msg doesn't pass `validate_msg`.
     pub fn credit(&self) -> Result<Credit> {
+        validate_msg(&self.msg)?;
         Ok(Credit {
             id: self.debit().credit_id()?,
             amount: self.amount,
@@ -144,6 +189,15 @@ impl Credit {
     pub fn recipient(&self) -> PublicKey {
         self.recipient
     }
+
+    /// Get the memo the sender gave for this credit, if any
+    pub fn memo(&self) -> Option<&str> {
+        if self.msg.is_empty() {
+            None
+        } else {
+            Some(&self.msg)
+        }
+    }
 }
 
 /// The history of a transfer Actor.
@@ -174,6 +228,124 @@ impl ActorHistory {
     pub fn len(&self) -> usize {
         self.credits.len() + self.debits.len()
     }
+
+    /// Returns the balance of `key` resulting from the history.
+    ///
+    /// Returns `Err(Error::ExcessiveValue)` if the history does not add up to a valid balance.
+    pub fn balance(&self, key: PublicKey) -> Result<Token> {
+        let credited = self
+            .credits
+            .iter()
+            .filter(|credit| credit.recipient() == key)
+            .try_fold(Token::zero(), |sum, credit| {
+                sum.checked_add(credit.amount())
+            });
+        let debited = self
+            .debits
+            .iter()
+            .filter(|debit| debit.sender() == key)
+            .try_fold(Token::zero(), |sum, debit| sum.checked_add(debit.amount()));
+        credited
+            .zip(debited)
+            .and_then(|(credited, debited)| credited.checked_sub(debited))
+            .ok_or(Error::ExcessiveValue)
+    }
+
+    /// Dry runs the validation Replicas apply to a debit on top of this history,
+    /// returning the resulting balance of the sender if the debit would be valid,
+    /// or the error the Replicas would reject it with.
+    pub fn simulate(&self, debit: &Debit) -> Result<Token> {
+        let sender = debit.sender();
+        if debit.amount() == Token::zero() {
+            return Err(Error::InvalidOperation);
+        }
+
+        let next_debit = self
+            .debits
+            .iter()
+            .filter(|proof| proof.sender() == sender)
+            .count() as u64;
+        if debit.id().counter != next_debit {
+            return Err(Error::InvalidSuccessor(next_debit));
+        }
+
+        let balance = self.balance(sender)?;
+        balance
+            .checked_sub(debit.amount())
+            .ok_or(Error::InsufficientBalance {
+                balance,
+                amount: debit.amount(),
+            })
+    }
+}
+
+/// The debits of a wallet Actor which have been sent to the Replicas
+/// but are not yet agreed on.
+///
+/// Replicas only accept the debit with the counter following the last agreed one,
+/// so counters are handed out here in order, and never twice, until the
+/// corresponding debit is either confirmed or aborted.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PendingDebits {
+    /// Number of debits agreed on by the Replicas.
+    confirmed: u64,
+    /// Number of debits awaiting agreement.
+    in_flight: u64,
+}
+
+impl PendingDebits {
+    /// Returns a queue for a wallet which has `confirmed` agreed debits,
+    /// e.g. the number of debits in its `ActorHistory`.
+    pub fn new(confirmed: u64) -> Self {
+        Self {
+            confirmed,
+            in_flight: 0,
+        }
+    }
+
+    /// Reserves and returns the counter to use for the next debit.
+    pub fn next_counter(&mut self) -> u64 {
+        let counter = self.confirmed + self.in_flight;
+        self.in_flight += 1;
+        counter
+    }
+
+    /// Marks the debit with `counter` as agreed on by the Replicas.
+    ///
+    /// Returns `Err(Error::InvalidSuccessor)` with the expected counter if `counter`
+    /// is not the oldest pending debit, as debits are agreed on in order.
+    pub fn confirm(&mut self, counter: u64) -> Result<()> {
+        if self.in_flight == 0 || counter != self.confirmed {
+            return Err(Error::InvalidSuccessor(self.confirmed));
+        }
+        self.confirmed += 1;
+        self.in_flight -= 1;
+        Ok(())
+    }
+
+    /// Releases the counter of a debit the Replicas will not agree on, so it can be reused.
+    ///
+    /// Every debit pending after it can no longer be agreed on either, so they
+    /// are aborted as well. Returns all aborted counters, in order.
+    /// Returns `Err(Error::NoSuchEntry)` if `counter` is not pending.
+    pub fn abort(&mut self, counter: u64) -> Result<Vec<u64>> {
+        let pending = self.pending();
+        if !pending.contains(&counter) {
+            return Err(Error::NoSuchEntry);
+        }
+        self.in_flight = counter - self.confirmed;
+        Ok((counter..pending.end).collect())
+    }
+
+    /// Returns the counters of the pending debits.
+    pub fn pending(&self) -> Range<u64> {
+        self.confirmed..self.confirmed + self.in_flight
+    }
+
+    /// Returns the number of debits agreed on by the Replicas.
+    pub fn confirmed(&self) -> u64 {
+        self.confirmed
+    }
 }
 
 /// The aggregated Replica signatures of the Actor debit cmd.
@@ -220,6 +392,11 @@ impl CreditAgreementProof {
         self.signed_credit.recipient()
     }
 
+    /// Get the memo the sender gave for this credit, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.signed_credit.credit.memo()
+    }
+
     /// Get the PublicKeySet of the replica that validated this credit
     pub fn replica_keys(&self) -> ReplicaPublicKeySet {
         self.debiting_replicas_keys.clone()
@@ -563,6 +740,112 @@ impl SignedCreditShare {
     }
 }
 
+/// A debit from a wallet owned jointly by two keys, i.e. whose actor is a BLS
+/// key set with a threshold of 1, so that it takes the transfer shares of both
+/// owners to make a `SignedTransfer`.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct MultiSigDebit {
+    /// The debit.
+    debit: Debit,
+    /// The credit.
+    credit: Credit,
+    /// The key set of the owners.
+    owners: PublicKeySet,
+    /// Transfer shares collected so far, by share index.
+    shares: BTreeMap<usize, SignedTransferShare>,
+}
+
+impl MultiSigDebit {
+    /// Number of distinct owner shares needed.
+    pub const REQUIRED_SHARES: usize = 2;
+
+    /// Creates a debit of the wallet of `owners` to be signed by both of them.
+    pub fn new(debit: Debit, credit: Credit, owners: PublicKeySet) -> Result<Self> {
+        if owners.threshold() + 1 != Self::REQUIRED_SHARES
+            || debit.sender() != PublicKey::Bls(owners.public_key())
+            || debit.amount() != credit.amount()
+            || debit.credit_id()? != *credit.id()
+        {
+            return Err(Error::InvalidOperation);
+        }
+        Ok(Self {
+            debit,
+            credit,
+            owners,
+            shares: BTreeMap::new(),
+        })
+    }
+
+    /// Get the debit id
+    pub fn id(&self) -> DebitId {
+        self.debit.id()
+    }
+
+    /// Adds the transfer share of one of the owners, after verifying
+    /// it's over this debit and validly signed by that owner.
+    pub fn add_share(&mut self, share: SignedTransferShare) -> Result<()> {
+        if share.debit().debit != self.debit
+            || share.credit().credit != self.credit
+            || share.actors() != &self.owners
+        {
+            return Err(Error::InvalidOperation);
+        }
+
+        let owner = self.owners.public_key_share(share.share_index());
+        let debit_bytes = utils::serialise(&self.debit)?;
+        let credit_bytes = utils::serialise(&self.credit)?;
+        if !owner.verify(&share.debit().actor_signature.share, debit_bytes)
+            || !owner.verify(&share.credit().actor_signature.share, credit_bytes)
+        {
+            return Err(Error::InvalidSignature);
+        }
+
+        let _ = self.shares.insert(share.share_index(), share);
+        Ok(())
+    }
+
+    /// Returns true if both owners' shares have been added.
+    pub fn is_complete(&self) -> bool {
+        self.shares.len() >= Self::REQUIRED_SHARES
+    }
+
+    /// Combines the shares of both owners into a `SignedTransfer`.
+    /// Returns `Err(Error::InvalidOperation)` if a share is still missing.
+    pub fn signed_transfer(&self) -> Result<SignedTransfer> {
+        if !self.is_complete() {
+            return Err(Error::InvalidOperation);
+        }
+
+        let debit_sig = self
+            .owners
+            .combine_signatures(
+                self.shares
+                    .iter()
+                    .map(|(index, share)| (*index, &share.debit().actor_signature.share)),
+            )
+            .map_err(|_| Error::InvalidSignature)?;
+        let credit_sig = self
+            .owners
+            .combine_signatures(
+                self.shares
+                    .iter()
+                    .map(|(index, share)| (*index, &share.credit().actor_signature.share)),
+            )
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(SignedTransfer {
+            debit: SignedDebit {
+                debit: self.debit.clone(),
+                actor_signature: Signature::Bls(debit_sig),
+            },
+            credit: SignedCredit {
+                credit: self.credit.clone(),
+                actor_signature: Signature::Bls(credit_sig),
+            },
+        })
+    }
+}
+
 // ------------------------------------------------------------
 //                      Replica
 // ------------------------------------------------------------
@@ -583,6 +866,9 @@ pub enum ReplicaEvent {
     /// The event raised when
     /// PropagateTransfer cmd has been successful.
     TransferPropagated(TransferPropagated),
+    /// One of the above events, for a transfer
+    /// which isn't a P2P one.
+    TransferOfKind(TransferOfKind),
 }
 
 /// The debiting Replica event raised when
@@ -724,6 +1010,20 @@ impl TransferPropagated {
     pub fn recipient(&self) -> PublicKey {
         self.credit_proof.recipient()
     }
+
+    /// Get the memo the sender gave for this credit, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.credit_proof.memo()
+    }
+}
+
+/// A Replica event along with what the transfer it's about is for.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct TransferOfKind {
+    /// What the transfer is for.
+    pub kind: TransferKind,
+    /// The event of the transfer.
+    pub event: Box<ReplicaEvent>,
 }
 
 /// Public Key Set for a group of transfer replicas.
@@ -749,3 +1049,862 @@ impl Debug for KnownGroupAdded {
 /// Notification of a credit sent to a recipient.
 #[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
 pub struct CreditNotification(pub CreditAgreementProof);
+
+/// The balance of a key as it was right after
+/// a given event in the Replica event history.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct HistoricalBalance {
+    /// The key the balance is for.
+    pub key: PublicKey,
+    /// Index of the last event taken into account.
+    pub event_index: u64,
+    /// The balance after applying the events up to and including `event_index`.
+    pub balance: Token,
+}
+
+impl HistoricalBalance {
+    /// Computes the balance of `key` by replaying the registered debits
+    /// and propagated credits in `events[..=event_index]`.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if there is no event at `event_index`,
+    /// and `Err(Error::ExcessiveValue)` if the history does not add up to a valid balance.
+    pub fn from_events(key: PublicKey, event_index: u64, events: &[ReplicaEvent]) -> Result<Self> {
+        if event_index >= events.len() as u64 {
+            return Err(Error::NoSuchEntry);
+        }
+
+        let balance = replay_balance(key, Token::zero(), &events[..=event_index as usize])?;
+
+        Ok(Self {
+            key,
+            event_index,
+            balance,
+        })
+    }
+}
+
+/// Hash of a Replica event.
+pub type EventHash = [u8; 256 / 8];
+
+impl ReplicaEvent {
+    /// Returns the event recorded along with `kind`, what the transfer it's about is for,
+    /// in place of any kind it was recorded with. Events of P2P transfers are recorded
+    /// without a kind, keeping the encoding they had before kinds were recorded.
+    pub fn with_transfer_kind(self, kind: TransferKind) -> Self {
+        let event = match self {
+            Self::TransferOfKind(event) => *event.event,
+            event => event,
+        };
+        match kind {
+            TransferKind::P2P => event,
+            kind => Self::TransferOfKind(TransferOfKind {
+                kind,
+                event: Box::new(event),
+            }),
+        }
+    }
+
+    /// Returns what the transfer the event is about is for.
+    /// Events not recorded along with a kind are for P2P transfers.
+    pub fn transfer_kind(&self) -> &TransferKind {
+        match self {
+            Self::TransferOfKind(event) => &event.kind,
+            _ => &TransferKind::P2P,
+        }
+    }
+
+    /// Returns the event of the transfer, without what it's for.
+    pub fn transfer_event(&self) -> &ReplicaEvent {
+        match self {
+            Self::TransferOfKind(event) => &event.event,
+            event => event,
+        }
+    }
+
+    /// Checks the kind the event was recorded with can be trusted, as it isn't signed
+    /// by the Actor: rewards have to be paid by `section_key`, and kinds aren't
+    /// recorded over another kind, nor for P2P transfers.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the kind isn't valid for the event.
+    pub fn check_transfer_kind(&self, section_key: &PublicKey) -> Result<()> {
+        let event = match self {
+            Self::TransferOfKind(event) => event,
+            _ => return Ok(()),
+        };
+        let paid_by_section = match event.event.as_ref() {
+            Self::TransferOfKind(_) => return Err(Error::InvalidOperation),
+            Self::TransferValidationProposed(event) => event.sender() == *section_key,
+            Self::TransferValidated(event) => event.sender() == *section_key,
+            Self::TransferRegistered(event) => event.sender() == *section_key,
+            // The sender of a propagated credit isn't known, but rewards are paid
+            // out by the Replicas of the section itself.
+            Self::TransferPropagated(event) => {
+                PublicKey::Bls(event.credit_proof.debiting_replicas_keys.public_key())
+                    == *section_key
+            }
+        };
+        match event.kind {
+            TransferKind::P2P => Err(Error::InvalidOperation),
+            TransferKind::Reward if !paid_by_section => Err(Error::InvalidOperation),
+            TransferKind::Reward | TransferKind::Payment { .. } => Ok(()),
+        }
+    }
+
+    /// Returns the hash of the serialised event.
+    pub fn hash(&self) -> Result<EventHash> {
+        let bytes = utils::serialise(self)?;
+        let mut hasher = Sha3::v256();
+        let mut output = [0; 32];
+        hasher.update(&bytes);
+        hasher.finalize(&mut output);
+        Ok(output)
+    }
+}
+
+/// A section signed summary of the Replica event history of a key, so that
+/// the events it covers can be truncated while the balance stays verifiable.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct HistoryCheckpoint {
+    /// The key the balance is for.
+    pub key: PublicKey,
+    /// Number of events covered, including those covered by previous checkpoints.
+    pub event_count: u64,
+    /// The balance after applying all the events covered.
+    pub balance: Token,
+    /// Hash of the last event covered.
+    pub last_event_hash: EventHash,
+    /// Section signature over the above.
+    pub section_sig: Signature,
+}
+
+impl HistoryCheckpoint {
+    /// Returns the bytes for the section to sign, for a checkpoint covering `events`
+    /// on top of those covered by `previous`, if any.
+    pub fn bytes_to_sign(
+        key: PublicKey,
+        previous: Option<&HistoryCheckpoint>,
+        events: &[ReplicaEvent],
+    ) -> Result<Vec<u8>> {
+        let (event_count, balance, last_event_hash) = Self::summarise(key, previous, events)?;
+        utils::serialise(&(key, event_count, balance, last_event_hash))
+    }
+
+    /// Creates a checkpoint covering `events` on top of those covered by `previous`, if any.
+    /// The `section_sig` is expected to be over the bytes returned by `bytes_to_sign`.
+    pub fn new(
+        key: PublicKey,
+        previous: Option<&HistoryCheckpoint>,
+        events: &[ReplicaEvent],
+        section_sig: Signature,
+    ) -> Result<Self> {
+        let (event_count, balance, last_event_hash) = Self::summarise(key, previous, events)?;
+        Ok(Self {
+            key,
+            event_count,
+            balance,
+            last_event_hash,
+            section_sig,
+        })
+    }
+
+    /// Verifies the checkpoint has been signed by the section key.
+    pub fn verify(&self, section_key: &PublicKey) -> Result<()> {
+        let bytes = utils::serialise(&(
+            self.key,
+            self.event_count,
+            self.balance,
+            self.last_event_hash,
+        ))?;
+        section_key.verify(&self.section_sig, bytes)
+    }
+
+    /// Returns the balance after applying the `events` following the checkpoint.
+    pub fn balance_after(&self, events: &[ReplicaEvent]) -> Result<Token> {
+        replay_balance(self.key, self.balance, events)
+    }
+
+    fn summarise(
+        key: PublicKey,
+        previous: Option<&HistoryCheckpoint>,
+        events: &[ReplicaEvent],
+    ) -> Result<(u64, Token, EventHash)> {
+        if let Some(previous) = previous {
+            if previous.key != key {
+                return Err(Error::InvalidOperation);
+            }
+        }
+
+        let (prev_count, prev_balance, prev_hash) = match previous {
+            Some(previous) => (
+                previous.event_count,
+                previous.balance,
+                Some(previous.last_event_hash),
+            ),
+            None => (0, Token::zero(), None),
+        };
+
+        let last_event_hash = match events.last() {
+            Some(event) => event.hash()?,
+            None => prev_hash.ok_or(Error::NoSuchEntry)?,
+        };
+        let balance = replay_balance(key, prev_balance, events)?;
+
+        Ok((prev_count + events.len() as u64, balance, last_event_hash))
+    }
+}
+
+/// Gaps and forks found in the registered debits of a wallet history.
+#[derive(Clone, Default, Eq, PartialEq, Debug, thiserror::Error)]
+#[error("Invalid wallet history, missing debits: {missing_counters:?}, forked at: {fork_at:?}")]
+pub struct StreamError {
+    /// Counters of the debits missing before the last one in the history.
+    pub missing_counters: Vec<u64>,
+    /// Lowest counter registered for two different transfers, if any.
+    pub fork_at: Option<u64>,
+}
+
+/// Validates the registered debits of `key` in `events` have consecutive counters
+/// starting at 0, each registered for a single transfer, e.g. to detect a partial
+/// history returned by a lagging Replica. Events delivered more than once are fine.
+pub fn validate_stream(
+    key: PublicKey,
+    events: &[ReplicaEvent],
+) -> std::result::Result<(), StreamError> {
+    let mut debits: BTreeMap<u64, &TransferAgreementProof> = BTreeMap::new();
+    let mut fork_at: Option<u64> = None;
+    for event in events {
+        if let ReplicaEvent::TransferRegistered(e) = event.transfer_event() {
+            if e.sender() != key {
+                continue;
+            }
+            let counter = e.id().counter;
+            match debits.get(&counter) {
+                Some(proof) if **proof != e.transfer_proof => {
+                    fork_at = Some(fork_at.map_or(counter, |fork| fork.min(counter)));
+                }
+                Some(_) => {}
+                None => {
+                    let _ = debits.insert(counter, &e.transfer_proof);
+                }
+            }
+        }
+    }
+
+    let missing_counters: Vec<u64> = match debits.keys().next_back() {
+        Some(last) => (0..*last)
+            .filter(|counter| !debits.contains_key(counter))
+            .collect(),
+        None => vec![],
+    };
+
+    if missing_counters.is_empty() && fork_at.is_none() {
+        Ok(())
+    } else {
+        Err(StreamError {
+            missing_counters,
+            fork_at,
+        })
+    }
+}
+
+/// Applies the registered debits and propagated credits of `key`
+/// in `events` to the given `balance`.
+fn replay_balance(key: PublicKey, mut balance: Token, events: &[ReplicaEvent]) -> Result<Token> {
+    for event in events {
+        balance = match event.transfer_event() {
+            ReplicaEvent::TransferRegistered(e) if e.sender() == key => {
+                balance.checked_sub(e.amount())
+            }
+            ReplicaEvent::TransferPropagated(e) if e.recipient() == key => {
+                balance.checked_add(e.amount())
+            }
+            _ => Some(balance),
+        }
+        .ok_or(Error::ExcessiveValue)?;
+    }
+    Ok(balance)
+}
+
+// ------------------------------------------------------------
+//                      STANDING ORDERS
+// ------------------------------------------------------------
+
+/// A recurring transfer of a fixed amount, signed by the sender so that
+/// an automated payment agent can execute it on the sender's behalf.
+/// Times are expressed in seconds since the UNIX epoch.
+#[derive(Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct StandingOrder {
+    /// The key paying the order.
+    pub from: PublicKey,
+    /// The destination to transfer to.
+    pub to: PublicKey,
+    /// The amount to transfer on each execution.
+    pub amount: Token,
+    /// The number of seconds between executions.
+    pub interval: u64,
+    /// When the first execution is due.
+    pub first_due: u64,
+    /// The maximum number of executions, unbounded if `None`.
+    pub max_executions: Option<u64>,
+    /// The number of times the order has been executed, only ever increased by
+    /// `record_execution`, as it's not part of the signed terms.
+    executions: u64,
+    /// Signature of the sender over the terms of the order.
+    pub owner_sig: Signature,
+}
+
+impl StandingOrder {
+    /// Creates a standing order signed by the sender's keypair.
+    pub fn new(
+        keypair: &Keypair,
+        to: PublicKey,
+        amount: Token,
+        interval: u64,
+        first_due: u64,
+        max_executions: Option<u64>,
+    ) -> Result<Self> {
+        let from = keypair.public_key();
+        let bytes = utils::serialise(&(from, to, amount, interval, first_due, max_executions))?;
+        let order = Self {
+            from,
+            to,
+            amount,
+            interval,
+            first_due,
+            max_executions,
+            executions: 0,
+            owner_sig: keypair.sign(&bytes),
+        };
+        order.validate()?;
+        Ok(order)
+    }
+
+    /// Validates the terms of the order and the sender's signature over them.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the order could never transfer anything.
+    pub fn validate(&self) -> Result<()> {
+        if self.amount == Token::zero() || self.interval == 0 || self.max_executions == Some(0) {
+            return Err(Error::InvalidOperation);
+        }
+        let bytes = utils::serialise(&(
+            self.from,
+            self.to,
+            self.amount,
+            self.interval,
+            self.first_due,
+            self.max_executions,
+        ))?;
+        self.from.verify(&self.owner_sig, &bytes)
+    }
+
+    /// Returns the number of times the order has been executed.
+    pub fn executions(&self) -> u64 {
+        self.executions
+    }
+
+    /// Returns true if all the executions of the order have been made.
+    pub fn is_exhausted(&self) -> bool {
+        matches!(self.max_executions, Some(max) if self.executions >= max)
+    }
+
+    /// Returns when the next execution is due, or `None` if the order is exhausted.
+    pub fn next_due(&self) -> Option<u64> {
+        if self.is_exhausted() {
+            return None;
+        }
+        self.executions
+            .checked_mul(self.interval)
+            .and_then(|elapsed| self.first_due.checked_add(elapsed))
+    }
+
+    /// Returns true if an execution is due at `now`.
+    pub fn due(&self, now: u64) -> bool {
+        matches!(self.next_due(), Some(next_due) if now >= next_due)
+    }
+
+    /// Records an execution of the order.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the order is exhausted.
+    pub fn record_execution(&mut self) -> Result<()> {
+        if self.is_exhausted() {
+            return Err(Error::InvalidOperation);
+        }
+        self.executions += 1;
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------
+//                      FEES
+// ------------------------------------------------------------
+
+/// The economic parameters a section applies when pricing
+/// the storage of data, i.e. `(base + per_byte * num_bytes) * multiplier`.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct FeePolicy {
+    /// The flat fee charged for any store.
+    pub base: Token,
+    /// The fee charged per byte stored.
+    pub per_byte: Token,
+    /// A section-specific factor applied to the fee,
+    /// e.g. to reflect the section's storage fullness.
+    pub multiplier: u64,
+}
+
+impl FeePolicy {
+    /// Returns the fee for storing `num_bytes` under this policy.
+    ///
+    /// Returns `Err(Error::ExcessiveValue)` if the fee would exceed the maximum value for `Token`.
+    pub fn apply(&self, num_bytes: u64) -> Result<Token> {
+        self.per_byte
+            .as_nano()
+            .checked_mul(num_bytes)
+            .and_then(|variable| self.base.as_nano().checked_add(variable))
+            .and_then(|fee| fee.checked_mul(self.multiplier))
+            .map(Token::from_nano)
+            .ok_or(Error::ExcessiveValue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        validate_stream, ActorHistory, Credit, CreditAgreementProof, Debit, FeePolicy,
+        HistoricalBalance, HistoryCheckpoint, MultiSigDebit, PendingDebits, ReplicaEvent,
+        SignedCredit, SignedCreditShare, SignedDebit, SignedDebitShare, SignedTransferShare,
+        StandingOrder, StreamError, Token, Transfer, TransferAgreementProof, TransferKind,
+        TransferOfKind, TransferPropagated, TransferRegistered, MAX_MSG_LEN,
+    };
+    use crate::{
+        utils, ChunkAddress, DataAddress, Error, Keypair, PublicKey, Result, Signature,
+        SignatureShare,
+    };
+    use crdts::Dot;
+    use rand::rngs::OsRng;
+    use threshold_crypto::{SecretKey, SecretKeySet};
+    use xor_name::XorName;
+
+    #[test]
+    fn fee_policy_apply() -> Result<()> {
+        let policy = FeePolicy {
+            base: Token::from_nano(10),
+            per_byte: Token::from_nano(2),
+            multiplier: 3,
+        };
+        assert_eq!(Token::from_nano(30), policy.apply(0)?);
+        assert_eq!(Token::from_nano(90), policy.apply(10)?);
+
+        let free = FeePolicy {
+            multiplier: 0,
+            ..policy
+        };
+        assert_eq!(Token::zero(), free.apply(1024)?);
+        Ok(())
+    }
+
+    #[test]
+    fn fee_policy_apply_overflow() {
+        let policy = FeePolicy {
+            base: Token::from_nano(1),
+            per_byte: Token::from_nano(u64::MAX),
+            multiplier: 1,
+        };
+        assert_eq!(Err(Error::ExcessiveValue), policy.apply(2));
+        assert_eq!(Err(Error::ExcessiveValue), policy.apply(1));
+    }
+
+    #[test]
+    fn historical_balance_requires_existing_event() {
+        let key = Keypair::new_ed25519(&mut OsRng).public_key();
+        assert_eq!(
+            Err(Error::NoSuchEntry),
+            HistoricalBalance::from_events(key, 0, &[])
+        );
+    }
+
+    #[test]
+    fn historical_balance_replays_credits_and_debits() -> Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let key = owner.public_key();
+        let other = Keypair::new_ed25519(&mut OsRng).public_key();
+        let events = vec![
+            credit_event(key, 100),
+            debit_event(&owner, 0, 30),
+            // Credits to other keys aren't taken into account
+            credit_event(other, 1_000),
+            credit_event(key, 5),
+            debit_event(&owner, 1, 25),
+        ];
+
+        let balance_at = |event_index| {
+            HistoricalBalance::from_events(key, event_index, &events).map(|b| b.balance)
+        };
+        assert_eq!(balance_at(0), Ok(Token::from_nano(100)));
+        assert_eq!(balance_at(1), Ok(Token::from_nano(70)));
+        assert_eq!(balance_at(2), Ok(Token::from_nano(70)));
+
+        let last = HistoricalBalance::from_events(key, 4, &events)?;
+        assert_eq!(last.key, key);
+        assert_eq!(last.event_index, 4);
+        assert_eq!(last.balance, Token::from_nano(50));
+
+        assert_eq!(balance_at(5), Err(Error::NoSuchEntry));
+
+        // Debits beyond the balance don't add up to a valid one
+        let overdrawn = [credit_event(key, 10), debit_event(&owner, 0, 11)];
+        assert_eq!(
+            HistoricalBalance::from_events(key, 1, &overdrawn),
+            Err(Error::ExcessiveValue)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn history_checkpoint() -> Result<()> {
+        let key = Keypair::new_ed25519(&mut OsRng).public_key();
+        let events: Vec<_> = (0..3).map(|nano| credit_event(key, 10 + nano)).collect();
+        let section_key = SecretKey::random();
+        let sign = |bytes: Vec<u8>| Signature::Bls(section_key.sign(bytes));
+
+        let bytes = HistoryCheckpoint::bytes_to_sign(key, None, &events[..2])?;
+        let first = HistoryCheckpoint::new(key, None, &events[..2], sign(bytes))?;
+        first.verify(&PublicKey::Bls(section_key.public_key()))?;
+        assert_eq!(first.event_count, 2);
+        assert_eq!(first.balance, Token::from_nano(21));
+        assert_eq!(first.last_event_hash, events[1].hash()?);
+        assert_eq!(first.balance_after(&events[2..])?, Token::from_nano(33));
+
+        // A checkpoint can be built on top of a previous one
+        let bytes = HistoryCheckpoint::bytes_to_sign(key, Some(&first), &events[2..])?;
+        let second = HistoryCheckpoint::new(key, Some(&first), &events[2..], sign(bytes))?;
+        second.verify(&PublicKey::Bls(section_key.public_key()))?;
+        assert_eq!(second.event_count, 3);
+        assert_eq!(second.balance, Token::from_nano(33));
+
+        let mut forged = second;
+        forged.balance = Token::from_nano(1_000);
+        assert_eq!(
+            Err(Error::InvalidSignature),
+            forged.verify(&PublicKey::Bls(section_key.public_key()))
+        );
+        assert_eq!(
+            Err(Error::NoSuchEntry),
+            HistoryCheckpoint::bytes_to_sign(key, None, &[])
+        );
+        Ok(())
+    }
+
+    fn credit_event(recipient: PublicKey, nano: u64) -> ReplicaEvent {
+        let sender = Keypair::new_ed25519(&mut OsRng);
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let credit = Credit {
+            id: [nano as u8; 32],
+            amount: Token::from_nano(nano),
+            recipient,
+            msg: "credit".to_string(),
+        };
+        let credit_proof = CreditAgreementProof {
+            signed_credit: SignedCredit {
+                actor_signature: sender.sign(&credit.id),
+                credit,
+            },
+            debiting_replicas_sig: Signature::Bls(replicas.secret_key_share(0).sign(b"credit").0),
+            debiting_replicas_keys: replicas.public_keys(),
+        };
+        ReplicaEvent::TransferPropagated(TransferPropagated { credit_proof })
+    }
+
+    fn debit_event(sender: &Keypair, counter: u64, nano: u64) -> ReplicaEvent {
+        let replicas = SecretKeySet::random(0, &mut OsRng);
+        let debit = Debit {
+            id: Dot::new(sender.public_key(), counter),
+            amount: Token::from_nano(nano),
+        };
+        let credit = Credit {
+            id: [counter as u8; 32],
+            amount: Token::from_nano(nano),
+            recipient: Keypair::new_ed25519(&mut OsRng).public_key(),
+            msg: "debit".to_string(),
+        };
+        let transfer_proof = TransferAgreementProof {
+            signed_debit: SignedDebit {
+                actor_signature: sender.sign(&[counter as u8]),
+                debit,
+            },
+            signed_credit: SignedCredit {
+                actor_signature: sender.sign(&credit.id),
+                credit,
+            },
+            debit_sig: Signature::Bls(replicas.secret_key_share(0).sign(b"debit").0),
+            credit_sig: Signature::Bls(replicas.secret_key_share(0).sign(b"credit").0),
+            debiting_replicas_keys: replicas.public_keys(),
+        };
+        ReplicaEvent::TransferRegistered(TransferRegistered { transfer_proof })
+    }
+
+    #[test]
+    fn wallet_history_gaps_and_forks() {
+        let sender = Keypair::new_ed25519(&mut OsRng);
+        let key = sender.public_key();
+        let debits: Vec<_> = (0..4)
+            .map(|counter| debit_event(&sender, counter, 10))
+            .collect();
+
+        let mut events = debits.clone();
+        events.push(credit_event(key, 5));
+        events.push(debits[1].clone());
+        assert_eq!(validate_stream(key, &events), Ok(()));
+        assert_eq!(validate_stream(key, &[]), Ok(()));
+
+        let partial = vec![debits[0].clone(), debits[3].clone()];
+        assert_eq!(
+            validate_stream(key, &partial),
+            Err(StreamError {
+                missing_counters: vec![1, 2],
+                fork_at: None,
+            })
+        );
+
+        events.push(debit_event(&sender, 2, 20));
+        assert_eq!(
+            validate_stream(key, &events),
+            Err(StreamError {
+                missing_counters: vec![],
+                fork_at: Some(2),
+            })
+        );
+    }
+
+    #[test]
+    fn transfer_msg_is_credited_as_memo() -> Result<()> {
+        let from = Keypair::new_ed25519(&mut OsRng).public_key();
+        let to = Keypair::new_ed25519(&mut OsRng).public_key();
+        let transfer = |msg: &str| Transfer {
+            amount: Token::from_nano(10),
+            to,
+            debit_id: Dot::new(from, 0),
+            msg: msg.to_string(),
+        };
+
+        assert_eq!(
+            transfer("rent, march").credit()?.memo(),
+            Some("rent, march")
+        );
+        assert_eq!(transfer("").credit()?.memo(), None);
+        assert!(matches!(
+            transfer(&"x".repeat(MAX_MSG_LEN + 1)).credit(),
+            Err(Error::InvalidMsg(_))
+        ));
+        assert!(matches!(
+            transfer("rent\u{0}").credit(),
+            Err(Error::InvalidMsg(_))
+        ));
+
+        match credit_event(to, 10) {
+            ReplicaEvent::TransferPropagated(event) => assert_eq!(event.memo(), Some("credit")),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn transfer_kind_is_recorded_along_with_events() -> Result<()> {
+        let section = Keypair::new_ed25519(&mut OsRng);
+        let section_key = section.public_key();
+        let payment = TransferKind::Payment {
+            target_data: DataAddress::Chunk(ChunkAddress::Public(XorName::random())),
+        };
+
+        // Events of P2P transfers keep their encoding
+        let event = credit_event(section_key, 10);
+        let p2p = event.clone().with_transfer_kind(TransferKind::P2P);
+        assert_eq!(utils::serialise(&p2p)?, utils::serialise(&event)?);
+        assert_eq!(p2p.transfer_kind(), &TransferKind::P2P);
+        p2p.check_transfer_kind(&section_key)?;
+
+        let paid = event.clone().with_transfer_kind(payment.clone());
+        assert_eq!(paid.transfer_kind(), &payment);
+        assert_eq!(paid.transfer_event(), &event);
+        paid.check_transfer_kind(&section_key)?;
+        assert_eq!(paid.with_transfer_kind(TransferKind::P2P), event);
+
+        // Rewards have to be paid by the section
+        let sender = Keypair::new_ed25519(&mut OsRng);
+        let reward = debit_event(&sender, 0, 10).with_transfer_kind(TransferKind::Reward);
+        assert_eq!(
+            reward.check_transfer_kind(&section_key),
+            Err(Error::InvalidOperation)
+        );
+        let reward = debit_event(&section, 0, 10).with_transfer_kind(TransferKind::Reward);
+        reward.check_transfer_kind(&section_key)?;
+        assert_eq!(
+            HistoricalBalance::from_events(
+                section_key,
+                1,
+                &[credit_event(section_key, 10), reward]
+            )
+            .map(|balance| balance.balance),
+            Ok(Token::zero())
+        );
+
+        // and kinds are neither recorded over another kind, nor for P2P transfers
+        let nested = ReplicaEvent::TransferOfKind(TransferOfKind {
+            kind: payment,
+            event: Box::new(event.clone().with_transfer_kind(TransferKind::Reward)),
+        });
+        assert_eq!(
+            nested.check_transfer_kind(&section_key),
+            Err(Error::InvalidOperation)
+        );
+        let p2p = ReplicaEvent::TransferOfKind(TransferOfKind {
+            kind: TransferKind::P2P,
+            event: Box::new(event),
+        });
+        assert_eq!(
+            p2p.check_transfer_kind(&section_key),
+            Err(Error::InvalidOperation)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multisig_debit_needs_both_owners() -> Result<()> {
+        let owners = SecretKeySet::random(1, &mut OsRng);
+        let sender = PublicKey::Bls(owners.public_keys().public_key());
+        let debit = Debit {
+            id: Dot::new(sender, 0),
+            amount: Token::from_nano(10),
+        };
+        let credit = Credit {
+            id: debit.credit_id()?,
+            amount: debit.amount(),
+            recipient: Keypair::new_ed25519(&mut OsRng).public_key(),
+            msg: "joint payment".to_string(),
+        };
+        let share = |index: usize| -> Result<SignedTransferShare> {
+            let secret = owners.secret_key_share(index);
+            SignedTransferShare::new(
+                SignedDebitShare {
+                    debit: debit.clone(),
+                    actor_signature: SignatureShare {
+                        index,
+                        share: secret.sign(utils::serialise(&debit)?),
+                    },
+                },
+                SignedCreditShare {
+                    credit: credit.clone(),
+                    actor_signature: SignatureShare {
+                        index,
+                        share: secret.sign(utils::serialise(&credit)?),
+                    },
+                },
+                owners.public_keys(),
+            )
+        };
+
+        let mut multisig = MultiSigDebit::new(debit.clone(), credit.clone(), owners.public_keys())?;
+        multisig.add_share(share(0)?)?;
+        // The same owner signing twice doesn't complete the debit
+        multisig.add_share(share(0)?)?;
+        assert!(!multisig.is_complete());
+        assert_eq!(Err(Error::InvalidOperation), multisig.signed_transfer());
+
+        multisig.add_share(share(1)?)?;
+        assert!(multisig.is_complete());
+        let transfer = multisig.signed_transfer()?;
+        sender.verify(
+            &transfer.debit.actor_signature,
+            utils::serialise(&transfer.debit.debit)?,
+        )?;
+        sender.verify(
+            &transfer.credit.actor_signature,
+            utils::serialise(&transfer.credit.credit)?,
+        )?;
+
+        // A share signed by a key outside of the owners is rejected
+        let mut forged = share(0)?;
+        forged.debit.actor_signature.share = SecretKeySet::random(1, &mut OsRng)
+            .secret_key_share(0)
+            .sign(utils::serialise(&debit)?);
+        assert_eq!(Err(Error::InvalidSignature), multisig.add_share(forged));
+        Ok(())
+    }
+
+    #[test]
+    fn actor_history_simulate_debit() {
+        let key = Keypair::new_ed25519(&mut OsRng).public_key();
+        let history = ActorHistory::empty();
+        assert_eq!(Ok(Token::zero()), history.balance(key));
+
+        let debit = |counter, nano| Debit {
+            id: Dot::new(key, counter),
+            amount: Token::from_nano(nano),
+        };
+        assert_eq!(
+            Err(Error::InsufficientBalance {
+                balance: Token::zero(),
+                amount: Token::from_nano(10),
+            }),
+            history.simulate(&debit(0, 10))
+        );
+        assert_eq!(
+            Err(Error::InvalidSuccessor(0)),
+            history.simulate(&debit(1, 10))
+        );
+        assert_eq!(Err(Error::InvalidOperation), history.simulate(&debit(0, 0)));
+    }
+
+    #[test]
+    fn pending_debits_in_order() {
+        let mut queue = PendingDebits::new(3);
+        assert_eq!(3, queue.next_counter());
+        assert_eq!(4, queue.next_counter());
+        assert_eq!(5, queue.next_counter());
+        assert_eq!(3..6, queue.pending());
+
+        // debits are agreed on in order
+        assert_eq!(Err(Error::InvalidSuccessor(3)), queue.confirm(4));
+        assert_eq!(Ok(()), queue.confirm(3));
+        assert_eq!(Err(Error::InvalidSuccessor(4)), queue.confirm(3));
+
+        // aborting a debit aborts all later ones and releases their counters
+        assert_eq!(Err(Error::NoSuchEntry), queue.abort(3));
+        assert_eq!(Ok(vec![4, 5]), queue.abort(4));
+        assert!(queue.pending().is_empty());
+        assert_eq!(4, queue.next_counter());
+        assert_eq!(Ok(()), queue.confirm(4));
+        assert_eq!(5, queue.confirmed());
+        assert_eq!(Err(Error::InvalidSuccessor(5)), queue.confirm(5));
+    }
+
+    #[test]
+    fn standing_order_due() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let to = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut order =
+            StandingOrder::new(&keypair, to, Token::from_nano(10), 100, 1_000, Some(2))?;
+
+        assert!(!order.due(999));
+        assert!(order.due(1_000));
+        order.record_execution()?;
+        assert_eq!(Some(1_100), order.next_due());
+        assert!(!order.due(1_050));
+        assert!(order.due(1_100));
+        order.record_execution()?;
+        assert_eq!(order.executions(), 2);
+        assert!(order.is_exhausted());
+        assert!(!order.due(u64::MAX));
+        assert_eq!(Err(Error::InvalidOperation), order.record_execution());
+
+        // Executions are not part of the signed terms
+        order.validate()?;
+        order.amount = Token::from_nano(1_000);
+        assert_eq!(Err(Error::InvalidSignature), order.validate());
+
+        assert_eq!(
+            Err(Error::InvalidOperation),
+            StandingOrder::new(&keypair, to, Token::from_nano(10), 0, 1_000, None)
+        );
+        Ok(())
+    }
+}
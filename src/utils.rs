@@ -43,3 +43,17 @@ pub(crate) fn decode<I: AsRef<str>, O: DeserializeOwned>(encoded: I) -> Result<O
     }
     deserialise(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))
 }
+
+/// Encodes to padded standard base64, without a multibase prefix.
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    // Drop the multibase code prefixed to the encoding.
+    multibase::encode(Base::Base64Pad, bytes)[1..].to_string()
+}
+
+/// Decodes from padded standard base64, without a multibase prefix.
+pub(crate) fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
+    let code = Base::Base64Pad.code();
+    let (_, decoded) = multibase::decode(format!("{}{}", code, encoded))
+        .map_err(|e| Error::FailedToParse(e.to_string()))?;
+    Ok(decoded)
+}
@@ -0,0 +1,18 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+// `SecretKey` is serialisable on purpose, as clients and nodes persist it, while `SymmetricKey`
+// must not be serialisable. The expected diagnostics are those of the toolchain pinned in
+// `rust-toolchain`, and are regenerated with `TRYBUILD=overwrite` when it's bumped.
+#[test]
+fn secret_keys_are_only_serialisable_where_intended() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/secret_key_is_serialize.rs");
+    cases.compile_fail("tests/ui/symmetric_key_is_not_serialize.rs");
+}
@@ -0,0 +1,7 @@
+use sn_data_types::SymmetricKey;
+
+fn assert_serialize<T: serde::Serialize>() {}
+
+fn main() {
+    assert_serialize::<SymmetricKey>();
+}